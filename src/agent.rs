@@ -0,0 +1,409 @@
+//! `chainz agent`: a session cache for decrypted encrypted-key secrets.
+//!
+//! The agent keeps secrets in mlock'd, zeroized buffers for a fixed TTL and
+//! serves them over a Unix socket inside the private config directory.
+//! Clients fail open: when no agent answers, key resolution prompts as before.
+
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, time::Duration};
+use zeroize::Zeroizing;
+
+const SOCKET_FILE: &str = "agent.sock";
+/// Overrides the socket location (for example, to run several agents).
+const SOCKET_ENV: &str = "CHAINZ_AGENT_SOCK";
+/// Keeps a wedged agent from stalling key resolution.
+#[cfg(unix)]
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Request {
+    Get { id: String },
+    Put { id: String, secret: String },
+    Lock,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Response {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    secret: Option<String>,
+    #[serde(default)]
+    wiped: usize,
+}
+
+pub(crate) fn socket_path() -> Option<PathBuf> {
+    match std::env::var_os(SOCKET_ENV) {
+        Some(path) if !path.is_empty() => Some(PathBuf::from(path)),
+        _ => Some(crate::config::config_dir()?.join(SOCKET_FILE)),
+    }
+}
+
+/// Parse a TTL such as `900`, `90s`, `15m`, or `8h`.
+pub(crate) fn parse_ttl(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    let (digits, unit) = match input.find(|c: char| !c.is_ascii_digit()) {
+        Some(split) => input.split_at(split),
+        None => (input, "s"),
+    };
+    let value: u64 = digits
+        .parse()
+        .map_err(|_| format!("invalid TTL '{}'", input))?;
+    let seconds = match unit {
+        "s" => value,
+        "m" => value.saturating_mul(60),
+        "h" => value.saturating_mul(60 * 60),
+        _ => return Err(format!("invalid TTL unit in '{}'; use s, m, or h", input)),
+    };
+    if seconds == 0 {
+        return Err("TTL must be greater than zero".to_string());
+    }
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Ask a running agent for a cached secret.
+pub(crate) fn get(id: &str) -> Option<Zeroizing<String>> {
+    request(&Request::Get { id: id.to_string() })?
+        .secret
+        .map(Zeroizing::new)
+}
+
+/// Offer a decrypted secret to a running agent. Best-effort by design.
+pub(crate) fn put(id: &str, secret: &str) {
+    let mut put = Request::Put {
+        id: id.to_string(),
+        secret: secret.to_string(),
+    };
+    let _ = request(&put);
+    if let Request::Put { secret, .. } = &mut put {
+        zeroize::Zeroize::zeroize(secret);
+    }
+}
+
+/// Wipe every secret held by the agent. Returns `None` when none is running.
+pub(crate) fn lock() -> Option<usize> {
+    request(&Request::Lock).map(|response| response.wiped)
+}
+
+fn request(request: &Request) -> Option<Response> {
+    #[cfg(unix)]
+    {
+        request_at(&socket_path()?, request)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = request;
+        None
+    }
+}
+
+#[cfg(unix)]
+fn request_at(path: &std::path::Path, request: &Request) -> Option<Response> {
+    use std::io::{BufRead, BufReader, Write};
+    use zeroize::Zeroize;
+
+    let mut stream = std::os::unix::net::UnixStream::connect(path).ok()?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+    let mut body = serde_json::to_vec(request).ok()?;
+    body.push(b'\n');
+    let written = stream.write_all(&body);
+    body.zeroize();
+    written.ok()?;
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line).ok()?;
+    let response = serde_json::from_str(&line).ok();
+    line.zeroize();
+    response
+}
+
+#[cfg(not(unix))]
+pub async fn run(_ttl: Duration) -> Result<()> {
+    anyhow::bail!("The chainz agent requires Unix domain sockets")
+}
+
+/// Serve the agent until interrupted, then remove the socket. Secrets are
+/// wiped when the process exits.
+#[cfg(unix)]
+pub async fn run(ttl: Duration) -> Result<()> {
+    let path = socket_path().ok_or(anyhow!("Unable to find the agent socket path"))?;
+    if std::env::var_os(SOCKET_ENV).is_none()
+        && let Some(dir) = path.parent()
+    {
+        crate::config::ensure_private_dir(dir).await?;
+    }
+    if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+        anyhow::bail!("A chainz agent is already running at {}", path.display());
+    }
+    // Nothing answered, so any existing socket file is stale.
+    let _ = std::fs::remove_file(&path);
+    let listener = bind_private(&path)
+        .with_context(|| format!("Failed to bind agent socket at {}", path.display()))?;
+    locked::harden_process();
+
+    eprintln!(
+        "chainz agent listening on {} (keys expire after {}s); ctrl-c to stop",
+        path.display(),
+        ttl.as_secs()
+    );
+    let served = tokio::select! {
+        result = serve(listener, ttl) => result,
+        result = tokio::signal::ctrl_c() => result.map_err(Into::into),
+    };
+    let _ = std::fs::remove_file(&path);
+    served
+}
+
+/// Bind a socket that only its owner can reach. A `CHAINZ_AGENT_SOCK`
+/// override may live outside the private config directory, so the parent
+/// directory must already be owner-only; a chmod after `bind` alone would
+/// leave a window for other users to connect.
+#[cfg(unix)]
+fn bind_private(path: &std::path::Path) -> std::io::Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    let parent = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => std::path::Path::new("."),
+    };
+    let dir = std::fs::metadata(parent)?;
+    if dir.mode() & 0o077 != 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            format!(
+                "{} is accessible to other users; the agent socket needs an owner-only (0700) directory",
+                parent.display()
+            ),
+        ));
+    }
+    let listener = tokio::net::UnixListener::bind(path)?;
+    let socket = std::fs::metadata(path)?;
+    if socket.uid() != dir.uid() {
+        let _ = std::fs::remove_file(path);
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            format!(
+                "{} is owned by another user; the agent socket needs a directory you own",
+                parent.display()
+            ),
+        ));
+    }
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+#[cfg(unix)]
+async fn serve(listener: tokio::net::UnixListener, ttl: Duration) -> Result<()> {
+    use std::sync::{Arc, Mutex};
+
+    let store = Arc::new(Mutex::new(locked::Store::new(ttl)));
+    let sweeper = Arc::clone(&store);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            interval.tick().await;
+            sweeper.lock().expect("agent store lock").sweep();
+        }
+    });
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let store = Arc::clone(&store);
+        tokio::spawn(async move {
+            let _ = handle(stream, &store).await;
+        });
+    }
+}
+
+#[cfg(unix)]
+async fn handle(
+    stream: tokio::net::UnixStream,
+    store: &std::sync::Mutex<locked::Store>,
+) -> Result<()> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use zeroize::Zeroize;
+
+    let (reader, mut writer) = stream.into_split();
+    let mut line = String::new();
+    BufReader::new(reader).read_line(&mut line).await?;
+    let request = serde_json::from_str(&line);
+    line.zeroize();
+    let response = match request? {
+        Request::Get { id } => Response {
+            secret: store.lock().expect("agent store lock").get(&id),
+            wiped: 0,
+        },
+        Request::Put { id, mut secret } => {
+            store.lock().expect("agent store lock").put(id, &secret);
+            secret.zeroize();
+            Response::default()
+        }
+        Request::Lock => Response {
+            secret: None,
+            wiped: store.lock().expect("agent store lock").clear(),
+        },
+    };
+    let mut body = serde_json::to_vec(&response)?;
+    if let Some(mut secret) = response.secret {
+        secret.zeroize();
+    }
+    body.push(b'\n');
+    let written = writer.write_all(&body).await;
+    body.zeroize();
+    written?;
+    Ok(())
+}
+
+/// Memory-locked secret storage. Pages holding secrets are excluded from swap
+/// where the OS permits, and every buffer is zeroized before release.
+#[cfg(unix)]
+mod locked {
+    use std::{
+        collections::HashMap,
+        sync::Once,
+        time::{Duration, Instant},
+    };
+    use zeroize::Zeroize;
+
+    struct LockedSecret {
+        bytes: Box<[u8]>,
+        expires: Instant,
+    }
+
+    impl LockedSecret {
+        fn new(secret: &str, ttl: Duration) -> Self {
+            let bytes: Box<[u8]> = secret.as_bytes().into();
+            // SAFETY: the pointer and length describe a live allocation owned
+            // by `bytes`, which is unlocked in `Drop` before it is freed.
+            let locked = unsafe { libc::mlock(bytes.as_ptr().cast(), bytes.len()) } == 0;
+            if !locked {
+                static WARN: Once = Once::new();
+                WARN.call_once(|| {
+                    eprintln!("Warning: could not lock agent memory; secrets may be swapped")
+                });
+            }
+            Self {
+                bytes,
+                expires: Instant::now() + ttl,
+            }
+        }
+    }
+
+    impl Drop for LockedSecret {
+        fn drop(&mut self) {
+            self.bytes.zeroize();
+            // SAFETY: same live allocation that was passed to mlock.
+            unsafe { libc::munlock(self.bytes.as_ptr().cast(), self.bytes.len()) };
+        }
+    }
+
+    pub(super) struct Store {
+        ttl: Duration,
+        secrets: HashMap<String, LockedSecret>,
+    }
+
+    impl Store {
+        pub(super) fn new(ttl: Duration) -> Self {
+            Self {
+                ttl,
+                secrets: HashMap::new(),
+            }
+        }
+
+        /// Entries expire a fixed TTL after unlock; reads do not extend them.
+        pub(super) fn put(&mut self, id: String, secret: &str) {
+            self.secrets.insert(id, LockedSecret::new(secret, self.ttl));
+        }
+
+        pub(super) fn get(&mut self, id: &str) -> Option<String> {
+            self.sweep();
+            let secret = self.secrets.get(id)?;
+            String::from_utf8(secret.bytes.to_vec()).ok()
+        }
+
+        pub(super) fn sweep(&mut self) {
+            let now = Instant::now();
+            self.secrets.retain(|_, secret| secret.expires > now);
+        }
+
+        pub(super) fn clear(&mut self) -> usize {
+            let wiped = self.secrets.len();
+            self.secrets.clear();
+            wiped
+        }
+    }
+
+    /// Keep secrets out of core dumps and, on Linux, away from same-user
+    /// debuggers.
+    pub(super) fn harden_process() {
+        let no_core = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        // SAFETY: plain syscalls with a valid, initialized argument.
+        unsafe {
+            libc::setrlimit(libc::RLIMIT_CORE, &no_core);
+            #[cfg(target_os = "linux")]
+            libc::prctl(libc::PR_SET_DUMPABLE, 0);
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ttl_accepts_seconds_and_units() {
+        assert_eq!(parse_ttl("900"), Ok(Duration::from_secs(900)));
+        assert_eq!(parse_ttl("15m"), Ok(Duration::from_secs(900)));
+        assert_eq!(parse_ttl("2h"), Ok(Duration::from_secs(7200)));
+        assert!(parse_ttl("0").is_err());
+        assert!(parse_ttl("5d").is_err());
+        assert!(parse_ttl("soon").is_err());
+    }
+
+    #[tokio::test]
+    async fn agent_serves_until_locked_or_expired() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("agent.sock");
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let shared = std::fs::Permissions::from_mode(0o755);
+            std::fs::set_permissions(dir.path(), shared).unwrap();
+            assert!(bind_private(&path).is_err(), "shared directory is refused");
+            let private = std::fs::Permissions::from_mode(0o700);
+            std::fs::set_permissions(dir.path(), private).unwrap();
+        }
+        let listener = bind_private(&path).unwrap();
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600, "no group or other access");
+        }
+        tokio::spawn(serve(listener, Duration::from_millis(300)));
+
+        let client = path.clone();
+        tokio::task::spawn_blocking(move || {
+            let put = Request::Put {
+                id: "envelope".into(),
+                secret: "secret".into(),
+            };
+            request_at(&client, &put).unwrap();
+            let get = || Request::Get {
+                id: "envelope".into(),
+            };
+            assert_eq!(
+                request_at(&client, &get()).unwrap().secret.as_deref(),
+                Some("secret")
+            );
+            assert_eq!(request_at(&client, &Request::Lock).unwrap().wiped, 1);
+            assert!(request_at(&client, &get()).unwrap().secret.is_none());
+
+            request_at(&client, &put).unwrap();
+            std::thread::sleep(Duration::from_millis(400));
+            assert!(request_at(&client, &get()).unwrap().secret.is_none());
+        })
+        .await
+        .unwrap();
+    }
+}
@@ -0,0 +1,527 @@
+//! Extended RPC probes: what an endpoint can do beyond answering
+//! `eth_chainId`.
+//!
+//! Public endpoints often pass the basic health check while serving a stale
+//! head, pruned state, or a reduced method set. These probes speak raw
+//! JSON-RPC so unsupported methods and batches are observable as such,
+//! rather than being folded into provider errors.
+
+use super::{
+    history::History,
+    rpc::{CHECK_DEADLINE, ProbeError, ProbeResult, probe},
+};
+use crate::{config::Chainz, endpoint, ui};
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::{collections::BTreeSet, fmt, time::Duration};
+
+/// A head older than the newest endpoint's by more than this is stale.
+/// Measured in block time rather than blocks, so it holds for any chain.
+const FRESHNESS_LAG: Duration = Duration::from_secs(60);
+/// `eth_getLogs` spans tried, widest first.
+const LOG_RANGES: [u64; 3] = [100_000, 10_000, 1_000];
+/// The span an endpoint must accept to earn the `logs` tag.
+const LOGS_TAG_RANGE: u64 = 10_000;
+/// An address with no contract code, so log queries return quickly.
+const QUIET_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+const ZERO_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ValueEnum,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Head is within a minute of the newest probed endpoint
+    Fresh,
+    /// Serves state at old blocks
+    Archive,
+    /// Accepts `eth_getLogs` over at least 10,000 blocks
+    Logs,
+    /// Supports `debug_traceTransaction`
+    Debug,
+    /// Supports the `trace_*` namespace
+    Trace,
+    /// Answers JSON-RPC batch requests
+    Batch,
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.to_possible_value().expect("no capability is skipped");
+        f.write_str(name.get_name())
+    }
+}
+
+/// What one endpoint supports, as of one extended probe.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Capabilities {
+    pub tags: BTreeSet<Capability>,
+    /// Widest `eth_getLogs` span accepted, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_log_range: Option<u64>,
+}
+
+/// The result of an extended probe against one endpoint.
+pub(crate) struct DeepProbe {
+    /// `None` when the basic health probe failed; nothing else is checked.
+    pub capabilities: Option<Capabilities>,
+    pub latency: Duration,
+    pub error: Option<ProbeError>,
+    head: Option<Head>,
+}
+
+#[derive(Clone, Copy)]
+struct Head {
+    number: u64,
+    timestamp: u64,
+}
+
+/// Run the extended profile against every (already-expanded) URL
+/// concurrently. Results are in input order; freshness is judged against
+/// the newest head among them.
+pub(crate) async fn probe_capabilities(urls: &[String], expected_chain_id: u64) -> Vec<DeepProbe> {
+    let client = reqwest::Client::builder()
+        .timeout(CHECK_DEADLINE)
+        .build()
+        .expect("static reqwest configuration is valid");
+    let handles: Vec<_> = urls
+        .iter()
+        .cloned()
+        .map(|url| {
+            let client = client.clone();
+            tokio::spawn(async move { probe_one(&client, &url, expected_chain_id).await })
+        })
+        .collect();
+    let mut probes = Vec::with_capacity(handles.len());
+    for handle in handles {
+        probes.push(handle.await.expect("capability probe task panicked"));
+    }
+    mark_fresh(&mut probes);
+    probes
+}
+
+async fn probe_one(client: &reqwest::Client, url: &str, expected_chain_id: u64) -> DeepProbe {
+    let (outcome, latency) = probe(url, expected_chain_id).await;
+    if let Err(error) = outcome {
+        return DeepProbe {
+            capabilities: None,
+            latency,
+            error: Some(error),
+            head: None,
+        };
+    }
+    let rpc = Rpc { client, url };
+    let head = rpc.head().await;
+    let mut capabilities = Capabilities::default();
+    if rpc
+        .call("eth_getBalance", json!([QUIET_ADDRESS, "0x1"]))
+        .await
+        .is_ok()
+    {
+        capabilities.tags.insert(Capability::Archive);
+    }
+    if let Some(head) = head {
+        for range in LOG_RANGES {
+            let filter = json!([{
+                "address": QUIET_ADDRESS,
+                "fromBlock": format!("{:#x}", head.number.saturating_sub(range - 1)),
+                "toBlock": format!("{:#x}", head.number),
+            }]);
+            if rpc.call("eth_getLogs", filter).await.is_ok() {
+                capabilities.max_log_range = Some(range);
+                break;
+            }
+        }
+    }
+    if capabilities
+        .max_log_range
+        .is_some_and(|range| range >= LOGS_TAG_RANGE)
+    {
+        capabilities.tags.insert(Capability::Logs);
+    }
+    if rpc
+        .supports("debug_traceTransaction", json!([ZERO_HASH]))
+        .await
+    {
+        capabilities.tags.insert(Capability::Debug);
+    }
+    if rpc.supports("trace_transaction", json!([ZERO_HASH])).await {
+        capabilities.tags.insert(Capability::Trace);
+    }
+    if rpc.batch().await {
+        capabilities.tags.insert(Capability::Batch);
+    }
+    DeepProbe {
+        capabilities: Some(capabilities),
+        latency,
+        error: None,
+        head,
+    }
+}
+
+/// Tag endpoints whose head is close to the newest one seen.
+fn mark_fresh(probes: &mut [DeepProbe]) {
+    let Some(newest) = probes
+        .iter()
+        .filter_map(|probe| probe.head)
+        .map(|head| head.timestamp)
+        .max()
+    else {
+        return;
+    };
+    for probe in probes {
+        if let (Some(head), Some(capabilities)) = (probe.head, &mut probe.capabilities)
+            && head.timestamp + FRESHNESS_LAG.as_secs() >= newest
+        {
+            capabilities.tags.insert(Capability::Fresh);
+        }
+    }
+}
+
+enum CallError {
+    /// No usable HTTP response
+    Transport,
+    /// A JSON-RPC error object
+    Rpc { code: i64, message: String },
+}
+
+struct Rpc<'a> {
+    client: &'a reqwest::Client,
+    url: &'a str,
+}
+
+impl Rpc<'_> {
+    async fn post(&self, body: Value) -> Result<Value, CallError> {
+        let response = self
+            .client
+            .post(self.url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|_| CallError::Transport)?;
+        if !response.status().is_success() {
+            return Err(CallError::Transport);
+        }
+        response.json().await.map_err(|_| CallError::Transport)
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value, CallError> {
+        let body = json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params});
+        let response = self.post(body).await?;
+        if let Some(error) = response.get("error") {
+            return Err(CallError::Rpc {
+                code: error.get("code").and_then(Value::as_i64).unwrap_or(0),
+                message: error
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+            });
+        }
+        response.get("result").cloned().ok_or(CallError::Transport)
+    }
+
+    async fn head(&self) -> Option<Head> {
+        let block = self
+            .call("eth_getBlockByNumber", json!(["latest", false]))
+            .await
+            .ok()?;
+        Some(Head {
+            number: quantity(block.get("number")?)?,
+            timestamp: quantity(block.get("timestamp")?)?,
+        })
+    }
+
+    /// Whether `method` exists here. Lookup failures for the dummy
+    /// argument (e.g. "transaction not found") still prove support.
+    async fn supports(&self, method: &str, params: Value) -> bool {
+        match self.call(method, params).await {
+            Ok(_) => true,
+            Err(CallError::Transport) => false,
+            Err(CallError::Rpc { code, message }) => !is_unsupported(code, &message),
+        }
+    }
+
+    async fn batch(&self) -> bool {
+        let body = json!([
+            {"jsonrpc": "2.0", "id": 1, "method": "eth_chainId", "params": []},
+            {"jsonrpc": "2.0", "id": 2, "method": "eth_blockNumber", "params": []},
+        ]);
+        match self.post(body).await {
+            Ok(Value::Array(responses)) => {
+                responses.len() == 2 && responses.iter().all(|r| r.get("result").is_some())
+            }
+            _ => false,
+        }
+    }
+}
+
+fn is_unsupported(code: i64, message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    code == -32601
+        || (message.contains("method")
+            && [
+                "not found",
+                "not supported",
+                "unsupported",
+                "not available",
+                "disabled",
+            ]
+            .iter()
+            .any(|phrase| message.contains(phrase)))
+        || message.contains("not whitelisted")
+        || message.contains("not allowed")
+}
+
+/// `chainz rpc probe`: run the extended profile over a chain's RPCs, record
+/// the tags, and optionally select the best endpoint that has `require`.
+pub(crate) async fn run(
+    chainz: &mut Chainz,
+    name_or_id: &str,
+    require: &[Capability],
+    select: bool,
+    json: bool,
+) -> Result<()> {
+    let chain = chainz.get_profile_chain(name_or_id)?.definition;
+    let expanded: Vec<String> = chain
+        .rpc_urls
+        .iter()
+        .map(|url| chainz.variables_for(&chain).expand_rpc_url(url))
+        .collect();
+    let probes = probe_capabilities(&expanded, chain.chain_id).await;
+
+    let results: Vec<ProbeResult> = probes
+        .iter()
+        .enumerate()
+        .map(|(index, probe)| ProbeResult {
+            index,
+            healthy: probe.capabilities.is_some(),
+            latency: probe.latency,
+            error: probe.error,
+        })
+        .collect();
+    let mut history = History::load().await;
+    let order = history.rank(chain.chain_id, &chain.rpc_urls, &results);
+    for (result, probe) in results.iter().zip(&probes) {
+        let raw_url = &chain.rpc_urls[result.index];
+        history.record(chain.chain_id, raw_url, result);
+        if let Some(capabilities) = &probe.capabilities {
+            history.set_capabilities(chain.chain_id, raw_url, capabilities.clone());
+        }
+    }
+    history.save().await;
+
+    let missing = |index: usize| -> Option<Vec<Capability>> {
+        let tags = &probes[index].capabilities.as_ref()?.tags;
+        Some(
+            require
+                .iter()
+                .copied()
+                .filter(|capability| !tags.contains(capability))
+                .collect(),
+        )
+    };
+    let best = order
+        .iter()
+        .copied()
+        .find(|&index| missing(index).is_some_and(|missing| missing.is_empty()));
+
+    if json {
+        let endpoints: Vec<_> = probes
+            .iter()
+            .enumerate()
+            .map(|(index, probe)| {
+                json!({
+                    "rpc_url": endpoint::redact(&chain.rpc_urls[index]),
+                    "selected": chain.rpc_urls[index] == chain.selected_rpc,
+                    "healthy": probe.capabilities.is_some(),
+                    "latency_ms": probe.latency.as_millis() as u64,
+                    "error": probe.error,
+                    "capabilities": probe.capabilities,
+                    "matches": best.is_some() && missing(index).is_some_and(|m| m.is_empty()),
+                })
+            })
+            .collect();
+        let output = json!({
+            "name": chain.name,
+            "chain_id": chain.chain_id,
+            "require": require,
+            "best_rpc": best.map(|index| endpoint::redact(&chain.rpc_urls[index])),
+            "endpoints": endpoints,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        println!(
+            "{}",
+            ui::section(&format!("{} ({})", chain.name, chain.chain_id))
+        );
+        for (index, probe) in probes.iter().enumerate() {
+            let label = endpoint::redact(&chain.rpc_urls[index]);
+            let Some(capabilities) = &probe.capabilities else {
+                let error = probe.error.map_or("unhealthy", ProbeError::as_str);
+                println!("  {}", ui::fail(&format!("{}  {}", label, ui::dim(error))));
+                continue;
+            };
+            let line = format!(
+                "{}  {}ms  {}",
+                label,
+                probe.latency.as_millis(),
+                describe(capabilities)
+            );
+            match missing(index).filter(|missing| !missing.is_empty()) {
+                Some(missing) => println!(
+                    "  {}",
+                    ui::warn(&format!("{}  (missing: {})", line, join(&missing)))
+                ),
+                None => println!("  {}", ui::success(&line)),
+            }
+        }
+    }
+
+    if select {
+        let index = best.ok_or_else(|| {
+            anyhow::anyhow!(
+                "No healthy RPC for '{}' supports: {}",
+                chain.name,
+                join(require)
+            )
+        })?;
+        chainz.set_selected_rpc(&chain.name, chain.rpc_urls[index].clone())?;
+        chainz.save().await?;
+        if !json {
+            println!(
+                "Selected {} for {}",
+                endpoint::redact(&chain.rpc_urls[index]),
+                chain.name
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Human summary of one endpoint's tags, e.g. `fresh archive logs<=100000`.
+pub(crate) fn describe(capabilities: &Capabilities) -> String {
+    let mut parts: Vec<String> = capabilities.tags.iter().map(ToString::to_string).collect();
+    if let Some(range) = capabilities.max_log_range
+        && range < LOGS_TAG_RANGE
+    {
+        parts.push(format!("logs<={}", range));
+    }
+    if parts.is_empty() {
+        "no extended capabilities".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+fn join(capabilities: &[Capability]) -> String {
+    capabilities
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn quantity(value: &Value) -> Option<u64> {
+    u64::from_str_radix(value.as_str()?.strip_prefix("0x")?, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A JSON-RPC endpoint for chain 10 whose feature set is `full` or,
+    /// otherwise, a pruned non-archive node without traces or batches that
+    /// caps log queries at 1,000 blocks.
+    async fn endpoint(full: bool, head_timestamp: u64) -> String {
+        crate::serve::testing::mock_http(move |request| match request {
+            Value::Array(calls) if full => {
+                Value::Array(calls.iter().map(|c| answer(c, true, 0)).collect())
+            }
+            Value::Array(_) => json!({"jsonrpc": "2.0", "id": null,
+                "error": {"code": -32600, "message": "batch requests are disabled"}}),
+            call => answer(&call, full, head_timestamp),
+        })
+        .await
+    }
+
+    fn answer(call: &Value, full: bool, head_timestamp: u64) -> Value {
+        let id = call["id"].clone();
+        let ok = |result: Value| json!({"jsonrpc": "2.0", "id": id, "result": result});
+        let err = |code: i64, message: &str| json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}});
+        match call["method"].as_str().unwrap() {
+            "eth_chainId" => ok(json!("0xa")),
+            "eth_blockNumber" => ok(json!("0x1000000")),
+            "eth_getBlockByNumber" => ok(json!({
+                "number": "0x1000000",
+                "timestamp": format!("{:#x}", head_timestamp),
+            })),
+            "eth_getBalance" if full => ok(json!("0x0")),
+            "eth_getBalance" => err(-32000, "missing trie node"),
+            "eth_getLogs" => {
+                let filter = &call["params"][0];
+                let span = quantity(&filter["toBlock"]).unwrap()
+                    - quantity(&filter["fromBlock"]).unwrap()
+                    + 1;
+                if full || span <= 1_000 {
+                    ok(json!([]))
+                } else {
+                    err(-32005, "query exceeds max block range 1000")
+                }
+            }
+            "debug_traceTransaction" | "trace_transaction" if full => {
+                err(-32000, "transaction not found")
+            }
+            "debug_traceTransaction" => err(-32601, "the method does not exist"),
+            _ => err(-32000, "method trace_transaction is not whitelisted"),
+        }
+    }
+
+    #[tokio::test]
+    async fn full_and_limited_endpoints_get_distinct_tags() {
+        let urls = [
+            endpoint(true, 1_000_000).await,
+            endpoint(false, 1_000_000 - 600).await,
+        ];
+        let probes = probe_capabilities(&urls, 10).await;
+
+        let full = probes[0].capabilities.as_ref().unwrap();
+        assert_eq!(
+            full.tags.iter().copied().collect::<Vec<_>>(),
+            [
+                Capability::Fresh,
+                Capability::Archive,
+                Capability::Logs,
+                Capability::Debug,
+                Capability::Trace,
+                Capability::Batch,
+            ]
+        );
+        assert_eq!(full.max_log_range, Some(100_000));
+
+        let limited = probes[1].capabilities.as_ref().unwrap();
+        assert!(limited.tags.is_empty(), "{:?}", limited.tags);
+        assert_eq!(limited.max_log_range, Some(1_000));
+    }
+
+    #[tokio::test]
+    async fn wrong_chain_endpoints_are_not_probed_further() {
+        let url = endpoint(true, 0).await;
+        let probes = probe_capabilities(&[url], 1).await;
+        assert!(probes[0].capabilities.is_none());
+        assert_eq!(probes[0].error, Some(ProbeError::WrongChain));
+    }
+
+    #[test]
+    fn unsupported_method_errors_are_recognized() {
+        assert!(is_unsupported(-32601, "whatever"));
+        assert!(is_unsupported(
+            -32000,
+            "Method debug_traceTransaction not found"
+        ));
+        assert!(is_unsupported(-32000, "method is not whitelisted"));
+        assert!(!is_unsupported(-32000, "transaction not found"));
+    }
+}
@@ -0,0 +1,443 @@
+//! Persistent RPC health history, cached next to the chainlist.
+//!
+//! Every probe appends a sample for its endpoint. Ranking blends the live
+//! probe with past uptime and latency, so one lucky (or unlucky) sample does
+//! not decide which RPC gets selected. Endpoints are keyed by a hash of the
+//! raw URL: credentials embedded in URLs never reach the cache file.
+
+use super::{
+    capability::Capabilities,
+    rpc::{ProbeError, ProbeResult, rank_by_health},
+};
+use crate::{config::Chainz, endpoint, opt::RpcCommand, ui};
+use alloy::primitives::keccak256;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const HISTORY_FILE: &str = "rpc-history.json";
+/// Per endpoint; older samples are dropped first.
+const MAX_SAMPLES: usize = 200;
+/// Floor for the uptime divisor, so a flaky but fast endpoint still ranks.
+const MIN_UPTIME_WEIGHT: f64 = 0.1;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Sample {
+    /// Unix seconds
+    pub at: u64,
+    pub healthy: bool,
+    pub latency_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ProbeError>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub(crate) struct Stats {
+    pub samples: usize,
+    /// Fraction of healthy samples, 0.0 to 1.0
+    pub uptime: f64,
+    /// Nearest-rank percentiles over healthy samples
+    pub p50_ms: Option<u64>,
+    pub p95_ms: Option<u64>,
+    pub last_error: Option<ProbeError>,
+}
+
+/// The latest extended probe of one endpoint.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct CapabilityRecord {
+    /// Unix seconds
+    pub at: u64,
+    #[serde(flatten)]
+    pub capabilities: Capabilities,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct History {
+    /// Chain ID, then endpoint hash, to samples in probe order
+    chains: BTreeMap<u64, BTreeMap<String, Vec<Sample>>>,
+    /// Chain ID, then endpoint hash, to capability tags
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    capabilities: BTreeMap<u64, BTreeMap<String, CapabilityRecord>>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl History {
+    /// Load the cached history. A missing or corrupt cache is treated as
+    /// empty, like the chainlist cache.
+    pub(crate) async fn load() -> Self {
+        Self::load_from(cache_path()).await
+    }
+
+    async fn load_from(path: Option<PathBuf>) -> Self {
+        let mut history: Self = match &path {
+            Some(path) => tokio::fs::read_to_string(path)
+                .await
+                .ok()
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+            None => Self::default(),
+        };
+        history.path = path;
+        history
+    }
+
+    /// Best-effort: a failed cache write shouldn't fail the command. The
+    /// file is replaced atomically, so concurrent runs lose samples at worst.
+    pub(crate) async fn save(&self) {
+        let (Some(path), Ok(json)) = (self.path.clone(), serde_json::to_vec(self)) else {
+            return;
+        };
+        let _ = tokio::task::spawn_blocking(move || write_cache(&path, &json)).await;
+    }
+
+    /// Record one probe of `raw_url`.
+    pub(crate) fn record(&mut self, chain_id: u64, raw_url: &str, result: &ProbeResult) {
+        self.push(
+            chain_id,
+            raw_url,
+            Sample {
+                at: now(),
+                healthy: result.healthy,
+                latency_ms: result.latency.as_millis().try_into().unwrap_or(u64::MAX),
+                error: result.error,
+            },
+        );
+    }
+
+    /// Replace the capability tags of `raw_url`.
+    pub(crate) fn set_capabilities(
+        &mut self,
+        chain_id: u64,
+        raw_url: &str,
+        capabilities: Capabilities,
+    ) {
+        self.capabilities.entry(chain_id).or_default().insert(
+            endpoint_key(raw_url),
+            CapabilityRecord {
+                at: now(),
+                capabilities,
+            },
+        );
+    }
+
+    pub(crate) fn capabilities(&self, chain_id: u64, raw_url: &str) -> Option<&CapabilityRecord> {
+        self.capabilities
+            .get(&chain_id)?
+            .get(&endpoint_key(raw_url))
+    }
+
+    fn push(&mut self, chain_id: u64, raw_url: &str, sample: Sample) {
+        let samples = self
+            .chains
+            .entry(chain_id)
+            .or_default()
+            .entry(endpoint_key(raw_url))
+            .or_default();
+        samples.push(sample);
+        if samples.len() > MAX_SAMPLES {
+            samples.drain(..samples.len() - MAX_SAMPLES);
+        }
+    }
+
+    fn samples(&self, chain_id: u64, raw_url: &str) -> &[Sample] {
+        self.chains
+            .get(&chain_id)
+            .and_then(|endpoints| endpoints.get(&endpoint_key(raw_url)))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    pub(crate) fn stats(&self, chain_id: u64, raw_url: &str) -> Option<Stats> {
+        let samples = self.samples(chain_id, raw_url);
+        if samples.is_empty() {
+            return None;
+        }
+        let mut latencies: Vec<u64> = samples
+            .iter()
+            .filter(|sample| sample.healthy)
+            .map(|sample| sample.latency_ms)
+            .collect();
+        latencies.sort_unstable();
+        Some(Stats {
+            samples: samples.len(),
+            uptime: latencies.len() as f64 / samples.len() as f64,
+            p50_ms: percentile(&latencies, 50),
+            p95_ms: percentile(&latencies, 95),
+            last_error: samples.iter().rev().find_map(|sample| sample.error),
+        })
+    }
+
+    /// Order `results` (probes of `raw_urls`) best first. Endpoints healthy
+    /// now come first, by their live latency averaged with the historical
+    /// median and scaled down by historical uptime; the rest follow by
+    /// uptime. Without history for the chain this is `rank_by_health`.
+    pub(crate) fn rank(
+        &self,
+        chain_id: u64,
+        raw_urls: &[String],
+        results: &[ProbeResult],
+    ) -> Vec<usize> {
+        if !self.chains.contains_key(&chain_id) {
+            return rank_by_health(results);
+        }
+        let scored: Vec<(&ProbeResult, Option<Stats>)> = results
+            .iter()
+            .map(|result| (result, self.stats(chain_id, &raw_urls[result.index])))
+            .collect();
+        let score = |result: &ProbeResult, stats: &Option<Stats>| {
+            let live = result.latency.as_secs_f64() * 1000.0;
+            match stats {
+                Some(stats) => {
+                    let typical = stats.p50_ms.map_or(live, |p50| (live + p50 as f64) / 2.0);
+                    typical / stats.uptime.max(MIN_UPTIME_WEIGHT)
+                }
+                None => live,
+            }
+        };
+        let uptime = |stats: &Option<Stats>| stats.as_ref().map_or(0.0, |stats| stats.uptime);
+
+        let (mut healthy, mut unhealthy): (Vec<_>, Vec<_>) =
+            scored.into_iter().partition(|(result, _)| result.healthy);
+        healthy.sort_by(|(a, a_stats), (b, b_stats)| {
+            score(a, a_stats)
+                .total_cmp(&score(b, b_stats))
+                .then(a.index.cmp(&b.index))
+        });
+        unhealthy.sort_by(|(a, a_stats), (b, b_stats)| {
+            uptime(b_stats)
+                .total_cmp(&uptime(a_stats))
+                .then(a.index.cmp(&b.index))
+        });
+        healthy
+            .into_iter()
+            .chain(unhealthy)
+            .map(|(result, _)| result.index)
+            .collect()
+    }
+}
+
+/// Probe `raw_urls` (expanded with `expanded`), record every result, and
+/// return the results with the history-aware ranking.
+pub(crate) async fn probe_and_rank(
+    chain_id: u64,
+    raw_urls: &[String],
+    expanded: &[String],
+) -> (Vec<ProbeResult>, Vec<usize>) {
+    let results = super::rpc::collect_probes(expanded, chain_id).await;
+    let mut history = History::load().await;
+    let order = history.rank(chain_id, raw_urls, &results);
+    for result in &results {
+        history.record(chain_id, &raw_urls[result.index], result);
+    }
+    history.save().await;
+    (results, order)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn cache_path() -> Option<PathBuf> {
+    Some(dirs::cache_dir()?.join("chainz").join(HISTORY_FILE))
+}
+
+fn write_cache(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    std::fs::create_dir_all(dir)?;
+    let mut temp = tempfile::Builder::new()
+        .prefix(".rpc-history.")
+        .tempfile_in(dir)?;
+    temp.write_all(contents)?;
+    temp.persist(path).map_err(|error| error.error)?;
+    Ok(())
+}
+
+/// A stable, credential-free identifier for a raw (unexpanded) URL.
+fn endpoint_key(raw_url: &str) -> String {
+    let hash = keccak256(raw_url.as_bytes());
+    alloy::hex::encode(&hash[..16])
+}
+
+fn percentile(sorted: &[u64], percent: usize) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (sorted.len() * percent).div_ceil(100).max(1);
+    Some(sorted[rank - 1])
+}
+
+impl RpcCommand {
+    pub async fn handle(self, chainz: &mut Chainz) -> Result<()> {
+        match self {
+            RpcCommand::Probe {
+                name_or_id,
+                require,
+                select,
+                json,
+            } => super::capability::run(chainz, &name_or_id, &require, select, json).await?,
+            RpcCommand::Stats { name_or_id, json } => {
+                let chain = chainz.get_profile_chain(&name_or_id)?.definition;
+                let history = History::load().await;
+                if json {
+                    let endpoints: Vec<_> = chain
+                        .rpc_urls
+                        .iter()
+                        .map(|url| {
+                            serde_json::json!({
+                                "rpc_url": endpoint::redact(url),
+                                "selected": *url == chain.selected_rpc,
+                                "stats": history.stats(chain.chain_id, url),
+                                "capabilities": history.capabilities(chain.chain_id, url),
+                            })
+                        })
+                        .collect();
+                    let output = serde_json::json!({
+                        "name": chain.name,
+                        "chain_id": chain.chain_id,
+                        "endpoints": endpoints,
+                    });
+                    println!("{}", serde_json::to_string_pretty(&output)?);
+                    return Ok(());
+                }
+
+                println!(
+                    "{}",
+                    ui::section(&format!("{} ({})", chain.name, chain.chain_id))
+                );
+                for url in &chain.rpc_urls {
+                    let marker = if *url == chain.selected_rpc { "*" } else { " " };
+                    let label = endpoint::redact(url);
+                    match history.stats(chain.chain_id, url) {
+                        Some(stats) => println!(
+                            "{} {}\n    uptime {:.1}%  p50 {}  p95 {}  ({} sample(s){})",
+                            marker,
+                            label,
+                            stats.uptime * 100.0,
+                            format_ms(stats.p50_ms),
+                            format_ms(stats.p95_ms),
+                            stats.samples,
+                            stats
+                                .last_error
+                                .map(|error| format!(", last error: {}", error.as_str()))
+                                .unwrap_or_default()
+                        ),
+                        None => println!("{} {}\n    {}", marker, label, ui::dim("no samples")),
+                    }
+                    if let Some(record) = history.capabilities(chain.chain_id, url) {
+                        println!(
+                            "    capabilities: {}",
+                            super::capability::describe(&record.capabilities)
+                        );
+                    }
+                }
+                if !history.chains.contains_key(&chain.chain_id) {
+                    println!(
+                        "{}",
+                        ui::dim(
+                            "Samples are recorded whenever chainz probes RPCs, e.g. `chainz doctor`."
+                        )
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn format_ms(ms: Option<u64>) -> String {
+    ms.map(|ms| format!("{}ms", ms))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn sample(healthy: bool, latency_ms: u64) -> Sample {
+        Sample {
+            at: 0,
+            healthy,
+            latency_ms,
+            error: (!healthy).then_some(ProbeError::Timeout),
+        }
+    }
+
+    fn result(index: usize, healthy: bool, ms: u64) -> ProbeResult {
+        ProbeResult {
+            index,
+            healthy,
+            latency: Duration::from_millis(ms),
+            error: (!healthy).then_some(ProbeError::Unreachable),
+        }
+    }
+
+    #[test]
+    fn stats_report_uptime_and_nearest_rank_percentiles() {
+        let mut history = History::default();
+        for ms in 1..=19 {
+            history.push(1, "https://a.example", sample(true, ms * 10));
+        }
+        history.push(1, "https://a.example", sample(false, 10_000));
+        let stats = history.stats(1, "https://a.example").unwrap();
+        assert_eq!(stats.samples, 20);
+        assert!((stats.uptime - 0.95).abs() < 1e-9);
+        assert_eq!(stats.p50_ms, Some(100));
+        assert_eq!(stats.p95_ms, Some(190));
+        assert_eq!(stats.last_error, Some(ProbeError::Timeout));
+        assert_eq!(history.stats(2, "https://a.example"), None);
+    }
+
+    #[test]
+    fn samples_are_capped_and_keyed_without_the_url() {
+        let mut history = History::default();
+        let secret_url = "https://rpc.example/v1/supersecret";
+        for _ in 0..MAX_SAMPLES + 5 {
+            history.push(1, secret_url, sample(true, 5));
+        }
+        assert_eq!(history.samples(1, secret_url).len(), MAX_SAMPLES);
+        let json = serde_json::to_string(&history).unwrap();
+        assert!(!json.contains("supersecret"));
+    }
+
+    #[test]
+    fn ranking_prefers_reliable_history_over_one_fast_sample() {
+        let urls = vec![
+            "https://flaky.example".to_string(),
+            "https://steady.example".to_string(),
+        ];
+        let live = [result(0, true, 40), result(1, true, 60)];
+        let mut history = History::default();
+        assert_eq!(history.rank(1, &urls, &live), [0, 1]);
+
+        for healthy in [false, false, true, false] {
+            history.push(1, &urls[0], sample(healthy, 40));
+            history.push(1, &urls[1], sample(true, 60));
+        }
+        assert_eq!(history.rank(1, &urls, &live), [1, 0]);
+
+        let down = [result(0, false, 10), result(1, false, 10)];
+        assert_eq!(history.rank(1, &urls, &down), [1, 0]);
+    }
+
+    #[tokio::test]
+    async fn history_round_trips_through_the_cache_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("nested").join(HISTORY_FILE);
+        let mut history = History::load_from(Some(path.clone())).await;
+        history.record(10, "https://a.example", &result(0, true, 25));
+        history.save().await;
+
+        let reloaded = History::load_from(Some(path)).await;
+        let stats = reloaded.stats(10, "https://a.example").unwrap();
+        assert_eq!((stats.samples, stats.p50_ms), (1, Some(25)));
+    }
+}
@@ -1,5 +1,9 @@
-use alloy::providers::{DynProvider, Provider, ProviderBuilder};
+use alloy::{
+    providers::{DynProvider, Provider, ProviderBuilder},
+    transports::RpcError,
+};
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use std::time::{Duration, Instant};
 
 /// Global deadline for a single RPC health probe in interactive sweeps.
@@ -13,13 +17,16 @@ pub const CHECK_DEADLINE: Duration = Duration::from_secs(4);
 /// that report failures to the user should attach the *raw* (unexpanded)
 /// URL as context themselves.
 pub async fn check_url(rpc_url: &str, expected_chain_id: u64) -> Result<()> {
-    let provider = create_provider(rpc_url).await?;
-    let chain_id = provider
-        .get_chain_id()
-        .await
-        // Provider errors may repeat credential-bearing URL paths. Keep the
-        // diagnostic at this seam URL-free; callers attach a redacted URL.
-        .map_err(|_| anyhow::anyhow!("Failed to query the RPC chain ID"))?;
+    let chain_id = query_chain_id(rpc_url).await.map_err(|error| {
+        anyhow::anyhow!(match error {
+            ProbeError::Timeout => "RPC connection timed out",
+            ProbeError::Unreachable => "Failed to initialize the RPC connection",
+            // Provider errors may repeat credential-bearing URL paths. Keep
+            // the diagnostic at this seam URL-free; callers attach a
+            // redacted URL.
+            _ => "Failed to query the RPC chain ID",
+        })
+    })?;
     if chain_id != expected_chain_id {
         anyhow::bail!(
             "Chain ID mismatch: expected {}, got {}",
@@ -30,20 +37,108 @@ pub async fn check_url(rpc_url: &str, expected_chain_id: u64) -> Result<()> {
     Ok(())
 }
 
+/// Test whether an (already-expanded) WebSocket URL serves the expected
+/// chain and completes an `eth_subscribe` handshake for new heads. Same
+/// error policy as `check_url`.
+pub async fn check_ws_url(ws_url: &str, expected_chain_id: u64) -> Result<()> {
+    if !super::is_ws_url(ws_url) {
+        anyhow::bail!("WebSocket URLs must start with ws:// or wss://");
+    }
+    let (outcome, _) = probe_ws(ws_url, expected_chain_id).await;
+    outcome.map_err(|error| {
+        anyhow::anyhow!(match error {
+            ProbeError::Timeout => "WebSocket handshake timed out",
+            ProbeError::Unreachable => "Failed to open the WebSocket connection",
+            ProbeError::BadResponse => "WebSocket endpoint rejected eth_subscribe",
+            ProbeError::WrongChain => "WebSocket endpoint serves a different chain",
+        })
+    })
+}
+
+/// Why a probe failed, coarse enough to record without leaking URLs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeError {
+    /// No answer within the deadline
+    Timeout,
+    /// Connection refused, DNS failure, TLS error, or an HTTP error status
+    Unreachable,
+    /// Answered, but not with a usable chain ID
+    BadResponse,
+    /// Serves a different chain
+    WrongChain,
+}
+
+impl ProbeError {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProbeError::Timeout => "timeout",
+            ProbeError::Unreachable => "unreachable",
+            ProbeError::BadResponse => "bad response",
+            ProbeError::WrongChain => "wrong chain",
+        }
+    }
+}
+
+async fn query_chain_id(rpc_url: &str) -> Result<u64, ProbeError> {
+    let provider = create_provider(rpc_url)
+        .await
+        .map_err(|_| ProbeError::Unreachable)?;
+    chain_id_of(&provider).await
+}
+
+async fn chain_id_of(provider: &DynProvider) -> Result<u64, ProbeError> {
+    provider.get_chain_id().await.map_err(|error| match error {
+        RpcError::Transport(_) => ProbeError::Unreachable,
+        _ => ProbeError::BadResponse,
+    })
+}
+
+/// Open a WebSocket, read its chain ID, and subscribe to new heads. The
+/// subscription is dropped, and the connection closed, on return.
+async fn ws_handshake(ws_url: &str) -> Result<u64, ProbeError> {
+    let provider = create_provider(ws_url)
+        .await
+        .map_err(|_| ProbeError::Unreachable)?;
+    let chain_id = chain_id_of(&provider).await?;
+    let subscription = provider
+        .subscribe_blocks()
+        .await
+        .map_err(|_| ProbeError::BadResponse)?;
+    drop(subscription);
+    Ok(chain_id)
+}
+
 /// One health probe under CHECK_DEADLINE, with measured latency.
-pub async fn probe(url: &str, expected_chain_id: u64) -> (bool, Duration) {
+pub async fn probe(url: &str, expected_chain_id: u64) -> (Result<(), ProbeError>, Duration) {
+    measure(query_chain_id(url), expected_chain_id).await
+}
+
+/// One WebSocket handshake probe under CHECK_DEADLINE, with measured
+/// latency.
+pub async fn probe_ws(url: &str, expected_chain_id: u64) -> (Result<(), ProbeError>, Duration) {
+    measure(ws_handshake(url), expected_chain_id).await
+}
+
+async fn measure(
+    query: impl Future<Output = Result<u64, ProbeError>>,
+    expected_chain_id: u64,
+) -> (Result<(), ProbeError>, Duration) {
     let start = Instant::now();
-    let healthy = tokio::time::timeout(CHECK_DEADLINE, check_url(url, expected_chain_id))
-        .await
-        .map(|r| r.is_ok())
-        .unwrap_or(false);
-    (healthy, start.elapsed())
+    let outcome = match tokio::time::timeout(CHECK_DEADLINE, query).await {
+        Ok(Ok(chain_id)) if chain_id == expected_chain_id => Ok(()),
+        Ok(Ok(_)) => Err(ProbeError::WrongChain),
+        Ok(Err(error)) => Err(error),
+        Err(_) => Err(ProbeError::Timeout),
+    };
+    (outcome, start.elapsed())
 }
 
 pub struct ProbeResult {
     pub index: usize,
     pub healthy: bool,
     pub latency: Duration,
+    pub error: Option<ProbeError>,
 }
 
 /// Probe URLs concurrently, yielding each result as it lands (completion
@@ -56,12 +151,13 @@ pub fn probe_urls(
     for (index, url) in urls.iter().cloned().enumerate() {
         let tx = tx.clone();
         tokio::spawn(async move {
-            let (healthy, latency) = probe(&url, expected_chain_id).await;
+            let (outcome, latency) = probe(&url, expected_chain_id).await;
             let _ = tx
                 .send(ProbeResult {
                     index,
-                    healthy,
+                    healthy: outcome.is_ok(),
                     latency,
+                    error: outcome.err(),
                 })
                 .await;
         });
@@ -83,13 +179,14 @@ pub fn rank_by_health(results: &[ProbeResult]) -> Vec<usize> {
         .collect()
 }
 
-/// Collecting wrapper: one health flag per input URL, in input order.
-pub async fn check_urls(urls: &[String], expected_chain_id: u64) -> Vec<bool> {
-    let mut results = vec![false; urls.len()];
+/// Collecting wrapper: every probe result, in input order.
+pub async fn collect_probes(urls: &[String], expected_chain_id: u64) -> Vec<ProbeResult> {
+    let mut results = Vec::with_capacity(urls.len());
     let mut rx = probe_urls(urls, expected_chain_id);
     while let Some(result) = rx.recv().await {
-        results[result.index] = result.healthy;
+        results.push(result);
     }
+    results.sort_by_key(|result| result.index);
     results
 }
 
@@ -114,9 +211,18 @@ mod tests {
             index,
             healthy,
             latency: Duration::from_millis(ms),
+            error: (!healthy).then_some(ProbeError::Timeout),
         }
     }
 
+    #[tokio::test]
+    async fn ws_check_rejects_http_urls_and_dead_sockets() {
+        let error = check_ws_url("http://127.0.0.1:1", 1).await.unwrap_err();
+        assert!(error.to_string().contains("ws://"), "{error}");
+        let (outcome, _) = probe_ws("ws://127.0.0.1:1", 1).await;
+        assert_eq!(outcome, Err(ProbeError::Unreachable));
+    }
+
     #[test]
     fn rank_healthy_fastest_first_then_unhealthy_in_order() {
         let results = vec![
@@ -139,6 +245,7 @@ mod tests {
         let mut seen = Vec::new();
         while let Some(result) = rx.recv().await {
             assert!(!result.healthy);
+            assert_eq!(result.error, Some(ProbeError::Unreachable));
             seen.push(result.index);
         }
         seen.sort();
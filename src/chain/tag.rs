@@ -0,0 +1,81 @@
+//! `chainz tag`: free-form labels that narrow `list`, `doctor`, `exec`, and
+//! `use` to a group of chains.
+
+use super::normalize_tag;
+use crate::{config::Chainz, opt::TagCommand};
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+impl TagCommand {
+    pub async fn handle(self, chainz: &mut Chainz) -> Result<()> {
+        match self {
+            TagCommand::Add { name_or_id, tags } => {
+                let mut chain = chainz.config.get_chain(&name_or_id)?.clone();
+                for tag in &tags {
+                    let tag = normalize_tag(tag)?;
+                    if !chain.tags.contains(&tag) {
+                        chain.tags.push(tag);
+                    }
+                }
+                chain.tags.sort();
+                let summary = chain.tags.join(", ");
+                let name = chain.name.clone();
+                chainz.replace_chain(&name, chain)?;
+                chainz.save().await?;
+                println!("Tags for '{}': {}", name, summary);
+            }
+            TagCommand::Remove { name_or_id, tags } => {
+                let mut chain = chainz.config.get_chain(&name_or_id)?.clone();
+                let tags = tags
+                    .iter()
+                    .map(|tag| normalize_tag(tag))
+                    .collect::<Result<Vec<_>>>()?;
+                if let Some(missing) = tags
+                    .iter()
+                    .find(|tag| !chain.tags.iter().any(|own| own.eq_ignore_ascii_case(tag)))
+                {
+                    anyhow::bail!("Chain '{}' is not tagged '{}'", chain.name, missing);
+                }
+                chain
+                    .tags
+                    .retain(|own| !tags.iter().any(|tag| own.eq_ignore_ascii_case(tag)));
+                let name = chain.name.clone();
+                let untagged = chain.tags.is_empty();
+                let summary = chain.tags.join(", ");
+                chainz.replace_chain(&name, chain)?;
+                chainz.save().await?;
+                if untagged {
+                    println!("Chain '{}' has no tags", name);
+                } else {
+                    println!("Tags for '{}': {}", name, summary);
+                }
+            }
+            TagCommand::List => {
+                let chains = chainz.profile_chains();
+                let groups = groups(&chains);
+                if groups.is_empty() {
+                    println!("No tags. Add one with 'chainz tag add <chain> <tag>'.");
+                }
+                for (tag, chains) in groups {
+                    println!("{}: {}", tag, chains.join(", "));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Chain names per tag, in tag order; hand-edited tags are folded to their
+/// canonical spelling.
+fn groups(chains: &[super::ChainDefinition]) -> BTreeMap<String, Vec<&str>> {
+    let mut groups = BTreeMap::<String, Vec<&str>>::new();
+    for chain in chains {
+        for tag in &chain.tags {
+            groups
+                .entry(tag.to_lowercase())
+                .or_default()
+                .push(&chain.name);
+        }
+    }
+    groups
+}
@@ -1,15 +1,18 @@
 use super::{
-    ChainDefinition,
-    rpc::{check_url, probe_urls, rank_by_health},
+    ChainDefinition, ChainMetadata,
+    history::History,
+    is_ws_url,
+    rpc::{check_url, check_ws_url, probe_urls, probe_ws},
 };
 use crate::ui;
 use crate::{
     chainlist::{ChainlistEntry, fetch_all_chains, fetch_chain_by_id},
-    config::Chainz,
+    config::{Chainz, ConfigLayer},
     key::{Key, KeyType, save_with_safe_new_keys},
     opt::{AddArgs, UpdateArgs},
     prompt::{Prompt, SystemPrompt},
     variables::GlobalVariables,
+    verifier::{KnownVerifier, VerifierKind, registry},
 };
 use anyhow::{Context, Result};
 use console::style;
@@ -58,6 +61,7 @@ async fn manual_chain_entry(
         name,
         chain_id,
         rpc: vec![],
+        ..Default::default()
     })
 }
 
@@ -113,8 +117,13 @@ async fn select_rpc(
         println!("{}", ui::success(&probe_summary(&results)));
     }
 
-    // Healthy-first, fastest-first picker over RAW urls
-    let order = rank_by_health(&results);
+    // Healthy-first picker over RAW urls, ordered by history and this probe
+    let mut history = History::load().await;
+    let order = history.rank(chain_id, &urls, &results);
+    for result in &results {
+        history.record(chain_id, &urls[result.index], result);
+    }
+    history.save().await;
     // Index results by url position once, rather than a linear scan per item.
     let mut by_index: Vec<Option<&_>> = vec![None; urls.len()];
     for r in &results {
@@ -182,6 +191,39 @@ async fn select_manual_rpc(
     }
 }
 
+/// Pick the fastest healthy WebSocket endpoint among `urls` (raw), if any.
+/// Unlike HTTP RPCs this is not prompted for: a WebSocket is optional.
+async fn pick_ws(chain_id: u64, urls: &[String], globals: &GlobalVariables) -> Option<String> {
+    let probes: Vec<_> = urls
+        .iter()
+        .map(|url| {
+            let expanded = globals.expand_rpc_url(url);
+            tokio::spawn(async move { probe_ws(&expanded, chain_id).await })
+        })
+        .collect();
+    let mut best: Option<(std::time::Duration, &String)> = None;
+    for (url, handle) in urls.iter().zip(probes) {
+        if let Ok((Ok(()), latency)) = handle.await
+            && best.is_none_or(|(fastest, _)| latency < fastest)
+        {
+            best = Some((latency, url));
+        }
+    }
+    best.map(|(_, url)| url.clone())
+}
+
+/// Validate a user-supplied WebSocket URL with a live handshake.
+async fn check_ws_arg(ws_url: &str, chain_id: u64, globals: &GlobalVariables) -> Result<()> {
+    check_ws_url(&globals.expand_rpc_url(ws_url), chain_id)
+        .await
+        .with_context(|| {
+            format!(
+                "WebSocket check failed for {}",
+                crate::endpoint::redact(ws_url)
+            )
+        })
+}
+
 /// Helper function to select or create a key
 fn select_key(terminal: &mut impl Prompt, chainz: &mut Chainz) -> Result<Option<String>> {
     let keys = chainz.list_keys();
@@ -224,9 +266,29 @@ fn select_key(terminal: &mut impl Prompt, chainz: &mut Chainz) -> Result<Option<
     }
 }
 
-/// Helper function to select or create a verifier
-fn select_verifier(prompt: &mut impl Prompt) -> Result<(Option<String>, Option<String>)> {
-    let new_url = prompt.text("Enter verifier URL (empty to remove)", None, true)?;
+/// Helper function to select or create a verifier, offering the registry's
+/// verifier for the chain first when there is one
+fn select_verifier(
+    prompt: &mut impl Prompt,
+    known: Option<&KnownVerifier>,
+) -> Result<(Option<String>, Option<String>)> {
+    let known = match known {
+        Some(known) => prompt
+            .confirm(
+                &format!("Use the {} verifier at {}?", known.verifier, known.url),
+                true,
+            )?
+            .then_some(known),
+        None => None,
+    };
+    let new_url = match known {
+        Some(known) => known.url.clone(),
+        None => prompt.text("Enter verifier URL (empty to remove)", None, true)?,
+    };
+    // Sourcify takes no API key.
+    if known.is_some_and(|known| known.verifier == VerifierKind::Sourcify) {
+        return Ok((Some(new_url), None));
+    }
     let new_key = prompt.secret("Enter verification API key (empty to remove): ")?;
 
     match (new_url.is_empty(), new_key.is_empty()) {
@@ -270,6 +332,18 @@ impl UpdateArgs {
                 chainz.list_chains()[selection].clone()
             }
         };
+        // Project chains are read-only, and probing their URLs here would
+        // expand them with global variables.
+        if chainz.chain_layer(&original.name) == ConfigLayer::Project {
+            anyhow::bail!(
+                "Chain '{}' is defined in {}; edit that file instead",
+                original.name,
+                chainz
+                    .layer_path(ConfigLayer::Project)
+                    .unwrap_or_default()
+                    .display()
+            );
+        }
         let original_name = original.name.clone();
         let mut chain = original;
 
@@ -296,6 +370,9 @@ impl UpdateArgs {
     fn has_direct_changes(&self) -> bool {
         self.name.is_some()
             || self.rpc_url.is_some()
+            || self.ws_url.is_some()
+            || self.no_ws
+            || self.testnet.is_some()
             || self.key.is_some()
             || self.no_key
             || self.verification_url.is_some()
@@ -329,6 +406,15 @@ impl UpdateArgs {
             })?;
             chain.select_rpc(rpc_url.clone());
         }
+        if let Some(ws_url) = &self.ws_url {
+            check_ws_arg(ws_url, chain.chain_id, &chainz.config.globals).await?;
+            chain.select_ws(ws_url.clone());
+        } else if self.no_ws {
+            chain.selected_ws = None;
+        }
+        if let Some(testnet) = self.testnet {
+            chain.metadata.testnet = testnet;
+        }
         if let Some(key) = &self.key {
             chainz.get_key(key)?;
             chain.key_name = Some(key.clone());
@@ -344,6 +430,9 @@ impl UpdateArgs {
             }
             if self.verification_api_key.is_some() || self.verification_api_key_stdin {
                 chain.verification_api_key = self.read_verification_api_key()?;
+                if chain.verification_url.is_none() {
+                    chain.verification_url = known_verifier_url(chain.chain_id);
+                }
             }
         }
         Ok(())
@@ -368,6 +457,7 @@ impl UpdateArgs {
             );
             let options = [
                 "RPC URL",
+                "WebSocket URL",
                 "Key",
                 "Verification",
                 "Rename",
@@ -376,10 +466,19 @@ impl UpdateArgs {
             match fuzzy_select(terminal, "What would you like to update?", &options, 0)? {
                 0 => {
                     println!("{}", ui::header("RPC Configuration"));
-                    let available_rpcs = fetch_chain_by_id(chain.chain_id, self.refresh)
-                        .await
-                        .map(|entry| entry.rpc)
-                        .unwrap_or_else(|_| chain.rpc_urls.clone());
+                    let entry = fetch_chain_by_id(chain.chain_id, self.refresh).await.ok();
+                    if let Some(entry) = &entry {
+                        chain.metadata = entry.metadata();
+                    }
+                    let available_rpcs = entry
+                        .map(|entry| {
+                            entry
+                                .rpc
+                                .into_iter()
+                                .filter(|url| !is_ws_url(url))
+                                .collect()
+                        })
+                        .unwrap_or_else(|| chain.rpc_urls.clone());
                     let new_rpc = select_rpc(
                         terminal,
                         &chain.name,
@@ -392,16 +491,29 @@ impl UpdateArgs {
                     chain.select_rpc(new_rpc);
                 }
                 1 => {
+                    println!("{}", ui::header("WebSocket Configuration"));
+                    let current = chain.selected_ws.clone().unwrap_or_default();
+                    let ws_url =
+                        terminal.text("WebSocket URL (empty to clear)", Some(&current), true)?;
+                    if ws_url.trim().is_empty() {
+                        chain.selected_ws = None;
+                    } else {
+                        check_ws_arg(&ws_url, chain.chain_id, &chainz.config.globals).await?;
+                        println!("{}", ui::success("WebSocket working"));
+                        chain.select_ws(ws_url);
+                    }
+                }
+                2 => {
                     println!("{}", ui::header("Key Configuration"));
                     chain.key_name = select_key(terminal, chainz)?;
                 }
-                2 => {
+                3 => {
                     println!("{}", ui::header("Verification Configuration"));
-                    let (url, key) = select_verifier(terminal)?;
+                    let (url, key) = select_verifier(terminal, registry().lookup(chain.chain_id))?;
                     chain.verification_url = url;
                     chain.verification_api_key = key;
                 }
-                3 => {
+                4 => {
                     let name = terminal.text("Chain name", Some(&chain.name), false)?;
                     if !chain.matches_exact(&name) && chainz.chain_exists(&name) {
                         anyhow::bail!("Chain '{}' already exists", name);
@@ -411,7 +523,7 @@ impl UpdateArgs {
                         .retain(|alias| !alias.eq_ignore_ascii_case(&name));
                     chain.name = name;
                 }
-                4 => break,
+                5 => break,
                 _ => unreachable!(),
             }
         }
@@ -481,16 +593,28 @@ impl AddArgs {
             .with_context(|| {
                 format!("RPC check failed for {}", crate::endpoint::redact(&rpc_url))
             })?;
+        if let Some(ws_url) = &self.ws_url {
+            check_ws_arg(ws_url, chain_id, &chainz.config.globals).await?;
+        }
 
+        let verification_api_key = self.read_verification_api_key()?;
         let chain_def = ChainDefinition {
             name: name.clone(),
             aliases: vec![],
+            tags: vec![],
             chain_id,
             rpc_urls: vec![rpc_url.clone()],
             selected_rpc: rpc_url,
-            verification_api_key: self.read_verification_api_key()?,
-            verification_url: self.verification_url.clone(),
+            ws_urls: self.ws_url.iter().cloned().collect(),
+            selected_ws: self.ws_url.clone(),
+            verification_url: match (&self.verification_url, &verification_api_key) {
+                (None, Some(_)) => known_verifier_url(chain_id),
+                (url, _) => url.clone(),
+            },
+            verification_api_key,
             key_name,
+            env: Default::default(),
+            metadata: ChainMetadata::default(),
         };
 
         // Check for existing chain (by name or alias)
@@ -551,6 +675,13 @@ impl AddArgs {
             (selected_chain.name.clone(), vec![])
         };
 
+        // Chainlist mixes WebSocket endpoints into its RPC list.
+        let (ws_urls, http_urls): (Vec<String>, Vec<String>) = selected_chain
+            .rpc
+            .iter()
+            .cloned()
+            .partition(|url| is_ws_url(url));
+
         let selected_rpc = if let Some(rpc_url) = &self.rpc_url {
             // Use provided RPC URL directly
             println!("Testing RPC...");
@@ -571,12 +702,25 @@ impl AddArgs {
                 terminal,
                 &selected_chain.name,
                 selected_chain.chain_id,
-                selected_chain.rpc.clone(),
+                http_urls.clone(),
                 &chainz.config.globals,
             )
             .await?
         };
 
+        let selected_ws = if let Some(ws_url) = &self.ws_url {
+            check_ws_arg(ws_url, selected_chain.chain_id, &chainz.config.globals).await?;
+            Some(ws_url.clone())
+        } else {
+            pick_ws(selected_chain.chain_id, &ws_urls, &chainz.config.globals).await
+        };
+        if let Some(ws_url) = &selected_ws {
+            println!(
+                "{}",
+                ui::success(&format!("WebSocket: {}", crate::endpoint::redact(ws_url)))
+            );
+        }
+
         let key_name = if let Some(key) = &self.key {
             chainz.get_key(key).map_err(|_| {
                 anyhow::anyhow!(
@@ -594,26 +738,37 @@ impl AddArgs {
             || self.verification_api_key.is_some()
             || self.verification_api_key_stdin
         {
-            (
-                self.verification_url.clone(),
-                self.read_verification_api_key()?,
-            )
+            let verification_api_key = self.read_verification_api_key()?;
+            let verification_url = match (&self.verification_url, &verification_api_key) {
+                (None, Some(_)) => known_verifier_url(selected_chain.chain_id),
+                (url, _) => url.clone(),
+            };
+            (verification_url, verification_api_key)
         } else {
-            select_verifier(terminal)?
+            println!("{}", ui::header("Verification Configuration"));
+            select_verifier(terminal, registry().lookup(selected_chain.chain_id))?
         };
 
         // Create and add the chain
         let mut chain_def = ChainDefinition {
             name,
             aliases,
+            tags: vec![],
             chain_id: selected_chain.chain_id,
-            rpc_urls: selected_chain.rpc,
+            rpc_urls: http_urls,
             selected_rpc: String::new(),
+            ws_urls,
+            selected_ws: None,
             verification_api_key,
             verification_url,
             key_name,
+            env: Default::default(),
+            metadata: selected_chain.metadata(),
         };
         chain_def.select_rpc(selected_rpc);
+        if let Some(ws_url) = selected_ws {
+            chain_def.select_ws(ws_url);
+        }
 
         // Confirm before replacing an existing chain (matched by name or alias)
         if chainz.chain_exists(&chain_def.name) {
@@ -652,6 +807,20 @@ impl AddArgs {
     }
 }
 
+/// The registry's verifier URL for a chain given only an API key, announced
+/// so the pre-filled value is never a surprise.
+fn known_verifier_url(chain_id: u64) -> Option<String> {
+    let known = registry().lookup(chain_id)?;
+    println!(
+        "{}",
+        ui::dim(&format!(
+            "Using the {} verifier at {}",
+            known.verifier, known.url
+        ))
+    );
+    Some(known.url.clone())
+}
+
 fn read_verification_api_key(stdin: bool, value: Option<String>) -> Result<Option<String>> {
     if stdin {
         use std::io::Read;
@@ -1,5 +1,8 @@
 use super::{manual_chain_entry, probe_summary, select_key, select_verifier, suggest_short_name};
-use crate::chain::{ChainDefinition, rpc::ProbeResult};
+use crate::chain::{
+    ChainDefinition,
+    rpc::{ProbeError, ProbeResult},
+};
 use crate::config::Chainz;
 use crate::opt::UpdateArgs;
 use crate::prompt::testing::{Answer, ScriptedPrompt};
@@ -20,11 +23,13 @@ fn probe_summary_collapses_endpoint_results() {
             index: 0,
             healthy: true,
             latency: Duration::from_millis(20),
+            error: None,
         },
         ProbeResult {
             index: 1,
             healthy: false,
             latency: Duration::from_millis(40),
+            error: Some(ProbeError::Timeout),
         },
     ];
     assert_eq!(probe_summary(&results), "1 of 2 RPCs healthy");
@@ -49,12 +54,47 @@ fn verifier_prompt_covers_set_partial_and_clear_states() {
     for (url, key, expected) in cases {
         let mut prompt =
             ScriptedPrompt::new([Answer::Text(url.into()), Answer::Secret(key.into())]);
-        let actual = select_verifier(&mut prompt).unwrap();
+        let actual = select_verifier(&mut prompt, None).unwrap();
         assert_eq!(actual.0.as_deref(), expected.0);
         assert_eq!(actual.1.as_deref(), expected.1);
     }
 }
 
+#[test]
+fn verifier_prompt_offers_the_known_verifier_first() {
+    let known = |verifier, url: &str| crate::verifier::KnownVerifier {
+        chain_id: 1,
+        verifier,
+        url: url.into(),
+    };
+    let etherscan = known(
+        crate::verifier::VerifierKind::Etherscan,
+        "https://api.etherscan.io/v2/api?chainid=1",
+    );
+    let mut prompt = ScriptedPrompt::new([Answer::Confirm(true), Answer::Secret("token".into())]);
+    let (url, key) = select_verifier(&mut prompt, Some(&etherscan)).unwrap();
+    assert_eq!(url.as_deref(), Some(etherscan.url.as_str()));
+    assert_eq!(key.as_deref(), Some("token"));
+
+    let mut prompt = ScriptedPrompt::new([
+        Answer::Confirm(false),
+        Answer::Text("https://verify.example".into()),
+        Answer::Secret(String::new()),
+    ]);
+    let (url, key) = select_verifier(&mut prompt, Some(&etherscan)).unwrap();
+    assert_eq!(url.as_deref(), Some("https://verify.example"));
+    assert_eq!(key, None);
+
+    let sourcify = known(
+        crate::verifier::VerifierKind::Sourcify,
+        "https://sourcify.dev/server",
+    );
+    let mut prompt = ScriptedPrompt::new([Answer::Confirm(true)]);
+    let (url, key) = select_verifier(&mut prompt, Some(&sourcify)).unwrap();
+    assert_eq!(url.as_deref(), Some("https://sourcify.dev/server"));
+    assert_eq!(key, None);
+}
+
 #[tokio::test]
 async fn scripted_prompt_drives_manual_entry_and_update_menu() {
     let mut entry_prompt =
@@ -68,18 +108,26 @@ async fn scripted_prompt_drives_manual_entry_and_update_menu() {
     let mut chain = ChainDefinition {
         name: entry.name,
         aliases: vec![],
+        tags: vec![],
         chain_id: entry.chain_id,
         rpc_urls: vec!["http://127.0.0.1:8545".into()],
         selected_rpc: "http://127.0.0.1:8545".into(),
+        ws_urls: vec![],
+        selected_ws: None,
         verification_api_key: None,
         verification_url: None,
         key_name: None,
+        env: Default::default(),
+        metadata: Default::default(),
     };
     let args = UpdateArgs {
         name_or_id: None,
         refresh: false,
         name: None,
         rpc_url: None,
+        ws_url: None,
+        no_ws: false,
+        testnet: None,
         key: None,
         no_key: false,
         verification_url: None,
@@ -88,9 +136,9 @@ async fn scripted_prompt_drives_manual_entry_and_update_menu() {
         clear_verification: false,
     };
     let mut update_prompt = ScriptedPrompt::new([
-        Answer::Select(3),
-        Answer::Text("local-renamed".into()),
         Answer::Select(4),
+        Answer::Text("local-renamed".into()),
+        Answer::Select(5),
     ]);
     let mut chainz = Chainz::new();
     args.edit_interactively(&mut update_prompt, &mut chainz, &mut chain)
@@ -1,3 +1,4 @@
+use crate::chain::{ChainMetadata, Explorer, NativeCurrency, ParentChain};
 use anyhow::{Result, anyhow};
 use serde::Deserialize;
 use std::path::PathBuf;
@@ -7,12 +8,47 @@ const CHAINLIST_URL: &str = "https://chainid.network/chains.json";
 /// chains.json is several MB and changes rarely; re-download at most daily.
 const CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
 
-#[derive(Deserialize, Debug, Clone)]
+/// Name fragments that mark a test network; chainlist has no explicit flag.
+const TESTNET_MARKERS: [&str; 7] = [
+    "testnet", "sepolia", "goerli", "holesky", "hoodi", "devnet", "test",
+];
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct ChainlistEntry {
     pub name: String,
-    #[serde(rename = "chainId")]
     pub chain_id: u64,
     pub rpc: Vec<String>,
+    #[serde(default)]
+    pub short_name: Option<String>,
+    #[serde(default)]
+    pub native_currency: Option<NativeCurrency>,
+    #[serde(default)]
+    pub explorers: Vec<Explorer>,
+    #[serde(default, rename = "infoURL")]
+    pub info_url: Option<String>,
+    #[serde(default)]
+    pub parent: Option<ParentChain>,
+}
+
+impl ChainlistEntry {
+    /// The descriptive data persisted on a chain added from this entry.
+    pub fn metadata(&self) -> ChainMetadata {
+        ChainMetadata {
+            short_name: self.short_name.clone(),
+            native_currency: self.native_currency.clone(),
+            explorers: self.explorers.clone(),
+            info_url: self.info_url.clone(),
+            parent: self.parent.clone(),
+            testnet: self.is_testnet(),
+        }
+    }
+
+    fn is_testnet(&self) -> bool {
+        let name = self.name.to_lowercase();
+        name.split(|c: char| !c.is_ascii_alphanumeric())
+            .any(|word| TESTNET_MARKERS.contains(&word))
+    }
 }
 
 /// Fetch the chainlist, served from a local cache unless it is stale or
@@ -158,4 +194,26 @@ mod tests {
         assert_eq!(entry.chain_id, 1);
         assert_eq!(entry.rpc, vec!["https://rpc.example.com"]);
     }
+
+    #[test]
+    fn metadata_keeps_currency_explorers_and_parent() {
+        let json = r#"{"name":"Base Sepolia Testnet","chainId":84532,"shortName":"basesep",
+            "rpc":[],"infoURL":"https://base.org","icon":"base",
+            "nativeCurrency":{"name":"Sepolia Ether","symbol":"ETH","decimals":18},
+            "explorers":[{"name":"basescan","url":"https://sepolia.basescan.org/","standard":"EIP3091"}],
+            "parent":{"type":"L2","chain":"eip155-11155111","bridges":[{"url":"https://bridge.base.org"}]}}"#;
+        let metadata = serde_json::from_str::<ChainlistEntry>(json)
+            .unwrap()
+            .metadata();
+        assert_eq!(metadata.short_name.as_deref(), Some("basesep"));
+        assert_eq!(metadata.native_currency.unwrap().symbol, "ETH");
+        assert_eq!(metadata.explorers[0].url, "https://sepolia.basescan.org/");
+        assert_eq!(metadata.info_url.as_deref(), Some("https://base.org"));
+        assert_eq!(metadata.parent.unwrap().chain_id(), Some(11155111));
+        assert!(metadata.testnet);
+
+        let mainnet: ChainlistEntry =
+            serde_json::from_str(r#"{"name":"Testudo Mainnet","chainId":1,"rpc":[]}"#).unwrap();
+        assert!(!mainnet.metadata().testnet);
+    }
 }
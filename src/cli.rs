@@ -4,13 +4,16 @@
 //! adapter and lets the command implementation modules remain private.
 
 use crate::{
+    agent,
     config::Chainz,
-    doctor, init, listing,
+    doctor, env, exec,
+    failover::Failover,
+    init, listing,
     listing::SecretVisibility,
     opt,
     opt::Opt,
     prompt::{Prompt, SystemPrompt},
-    ui,
+    serve, ui,
     variables::ChainVariables,
 };
 use anyhow::Result;
@@ -40,13 +43,37 @@ async fn dispatch() -> Result<()> {
             return Ok(());
         }
         opt::Command::Init {} => return init::handle_init().await,
-        opt::Command::Doctor { fix } => {
+        opt::Command::Agent { ttl } => return agent::run(ttl).await,
+        opt::Command::Doctor {
+            fix,
+            json,
+            format,
+            only,
+            skip,
+            chains,
+            tags,
+            list_checks,
+        } => {
+            if list_checks {
+                doctor::list_checks();
+                return Ok(());
+            }
+            let format = if json {
+                opt::DoctorFormat::Json
+            } else {
+                format
+            };
             let mut chainz = Chainz::load_for_doctor().await?;
             if !fix {
                 chainz.release_config_lock();
             }
-            let report = doctor::run(&mut chainz, fix).await?;
-            if report.failures > 0 {
+            let selection = doctor::Selection::new(&chainz, only, skip, chains, tags)?;
+            let human = format == opt::DoctorFormat::Text;
+            let report = doctor::run(&mut chainz, fix, human, selection).await?;
+            if !human {
+                print!("{}", doctor::render(&report, format)?);
+            }
+            if report.failures() > 0 {
                 std::process::exit(1);
             }
             return Ok(());
@@ -57,11 +84,23 @@ async fn dispatch() -> Result<()> {
     let mut chainz = Chainz::load().await?;
 
     match opts.cmd {
-        opt::Command::Init {} | opt::Command::Completions { .. } | opt::Command::Doctor { .. } => {
+        opt::Command::Init {}
+        | opt::Command::Agent { .. }
+        | opt::Command::Completions { .. }
+        | opt::Command::Doctor { .. } => {
             unreachable!("handled above")
         }
         opt::Command::Key { cmd } => cmd.handle(&mut chainz).await?,
         opt::Command::Var { cmd } => cmd.handle(&mut chainz).await?,
+        opt::Command::Profile { cmd } => cmd.handle(&mut chainz).await?,
+        opt::Command::Tag { cmd } => cmd.handle(&mut chainz).await?,
+        opt::Command::Rpc { cmd } => {
+            // Probing is slow; only a selection needs to write the config.
+            if !matches!(cmd, opt::RpcCommand::Probe { select: true, .. }) {
+                chainz.release_config_lock();
+            }
+            cmd.handle(&mut chainz).await?
+        }
         opt::Command::Add { args } => {
             let chain = args.handle(&mut chainz).await?;
             println!("Added chain {}", chain.name);
@@ -74,37 +113,41 @@ async fn dispatch() -> Result<()> {
             chainz.save().await?;
             println!("Removed chain '{}'", removed.name);
         }
-        opt::Command::Use { name_or_id } => {
+        opt::Command::Use { name_or_id, tags } => {
             let target = match name_or_id {
                 Some(id) => id,
-                None => select_chain(&chainz)?,
+                None => select_chain(&chainz, &tags)?,
             };
             let name = chainz.set_default_chain(&target)?;
             chainz.save().await?;
-            println!("Default chain set to '{}'", name);
+            match chainz.config.active_profile() {
+                Some((profile, _)) => {
+                    println!("Default chain for profile '{}' set to '{}'", profile, name)
+                }
+                None => println!("Default chain set to '{}'", name),
+            }
         }
         opt::Command::List {
             json,
             show_secrets,
             verbose,
+            testnets,
+            mainnets,
+            tags,
         } => {
-            let chains = chainz.list_chains();
+            let mut chains = chainz.profile_chains();
+            if testnets || mainnets {
+                chains.retain(|chain| chain.metadata.testnet == testnets);
+            }
+            chains.retain(|chain| chain.has_tags(&tags));
+            let default = chainz.config.effective_default();
             let visibility = SecretVisibility::from(show_secrets);
             if json {
-                println!(
-                    "{}",
-                    listing::json(chains, chainz.config.default_chain.as_deref(), visibility)?
-                );
+                println!("{}", listing::json(&chains, default, visibility)?);
             } else if show_secrets || verbose {
-                print!(
-                    "{}",
-                    listing::verbose(chains, chainz.config.default_chain.as_deref(), visibility)
-                );
+                print!("{}", listing::verbose(&chains, default, visibility));
             } else {
-                print!(
-                    "{}",
-                    listing::compact(chains, chainz.config.default_chain.as_deref())
-                );
+                print!("{}", listing::compact(&chains, default));
             }
         }
         opt::Command::Show {
@@ -112,26 +155,35 @@ async fn dispatch() -> Result<()> {
             json,
             show_secrets,
         } => {
-            let chain = chainz.config.get_chain(&name_or_id)?;
+            // Show the active profile's key mapping, but never expand URLs:
+            // viewing a chain must not resolve secrets or fail on them.
+            let mut chain = chainz.config.get_chain(&name_or_id)?.clone();
+            chain.key_name = chainz.config.effective_key_name(&chain).map(str::to_string);
             let visibility = SecretVisibility::from(show_secrets);
+            let layer = chainz.chain_layer(&chain.name);
+            let sources = listing::Sources {
+                chain: layer,
+                chain_path: chainz.layer_path(layer),
+                default: chainz.default_layer(),
+            };
+            let default = chainz.config.effective_default();
             if json {
                 println!(
                     "{}",
-                    listing::show_json(chain, chainz.config.default_chain.as_deref(), visibility)?
+                    listing::show_json(&chain, default, visibility, &sources)?
                 );
             } else {
-                print!(
-                    "{}",
-                    listing::show(chain, chainz.config.default_chain.as_deref(), visibility)
-                );
+                print!("{}", listing::show(&chain, default, visibility, &sources));
             }
         }
         opt::Command::Shell { name_or_id } => {
-            let name_or_id = match name_or_id.or_else(|| chainz.config.default_chain.clone()) {
+            let name_or_id = match name_or_id
+                .or_else(|| chainz.config.effective_default().map(str::to_string))
+            {
                 Some(id) => id,
-                None => select_chain(&chainz)?,
+                None => select_chain(&chainz, &[])?,
             };
-            let chain = chainz.get_chain(&name_or_id)?;
+            let chain = chainz.get_profile_chain(&name_or_id)?;
             // Empty command args → lazy rule: key backends are never touched.
             let variables = ChainVariables::new(&chain, &[], false)?;
             let chain_name = chain.definition.name.clone();
@@ -157,29 +209,169 @@ async fn dispatch() -> Result<()> {
                 std::process::exit(status.code().unwrap_or(1));
             }
         }
+        opt::Command::Sign { chain, key, cmd } => {
+            let name_or_id =
+                match chain.or_else(|| chainz.config.effective_default().map(str::to_string)) {
+                    Some(id) => id,
+                    None => select_chain(&chainz, &[])?,
+                };
+            let mut chain = chainz.get_profile_chain(&name_or_id)?;
+            if let Some(key_name) = key {
+                chain = chain.with_key(chainz.get_key(&key_name)?);
+            }
+            // Signing may prompt for a key password; don't hold the lock.
+            chainz.release_config_lock();
+            cmd.handle(&chain)?;
+        }
+        opt::Command::Serve {
+            name_or_id,
+            port,
+            key,
+        } => {
+            let name_or_id = match name_or_id
+                .or_else(|| chainz.config.effective_default().map(str::to_string))
+            {
+                Some(id) => id,
+                None => select_chain(&chainz, &[])?,
+            };
+            let mut chain = chainz.get_profile_chain(&name_or_id)?;
+            if let Some(key_name) = key {
+                chain = chain.with_key(chainz.get_key(&key_name)?);
+            }
+            chainz.release_config_lock();
+            let key = chain.key.as_ref().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Chain '{}' has no key attached; use `chainz update {} --key <name>` or --key",
+                    chain.definition.name,
+                    chain.definition.name
+                )
+            })?;
+            let proxy = serve::Proxy::new(
+                key.private_key()?.parse()?,
+                chain.definition.chain_id,
+                chain.rpc_url.clone(),
+            );
+            serve::run(proxy, &chain.definition.name, port).await?;
+        }
+        opt::Command::Env {
+            name_or_id,
+            format: opt::EnvFormat::FoundryToml,
+            key,
+            expose_key,
+        } => {
+            if name_or_id.is_some() {
+                anyhow::bail!("--format foundry-toml covers every chain; omit the chain");
+            }
+            if key.is_some() || expose_key {
+                anyhow::bail!(
+                    "--format foundry-toml never includes keys; omit --key and --expose-key"
+                );
+            }
+            print!(
+                "{}",
+                env::foundry_toml(&chainz.profile_chains(), |chain| {
+                    chainz
+                        .variables_for(chain)
+                        .expand_rpc_url(&chain.selected_rpc)
+                })
+            );
+        }
+        opt::Command::Env {
+            name_or_id,
+            format,
+            key,
+            expose_key,
+        } => {
+            let name_or_id = match name_or_id
+                .or_else(|| chainz.config.effective_default().map(str::to_string))
+            {
+                Some(id) => id,
+                None => select_chain(&chainz, &[])?,
+            };
+            let mut chain = chainz.get_profile_chain(&name_or_id)?;
+            if let Some(key_name) = key {
+                chain = chain.with_key(chainz.get_key(&key_name)?);
+            }
+            chainz.release_config_lock();
+            let variables = ChainVariables::new(&chain, &[], expose_key)?;
+            print!("{}", env::render(variables.as_map(), format)?);
+        }
+        opt::Command::Exec {
+            name_or_id: None,
+            chains,
+            all,
+            tags,
+            jobs,
+            command,
+            key,
+            expose_key,
+            ..
+        } if all || !chains.is_empty() || !tags.is_empty() => {
+            let names: Vec<String> = if chains.is_empty() {
+                chainz
+                    .profile_chains()
+                    .into_iter()
+                    .filter(|c| c.has_tags(&tags))
+                    .map(|c| c.name)
+                    .collect()
+            } else {
+                chains
+            };
+            let mut instances = Vec::with_capacity(names.len());
+            for name in &names {
+                let mut chain = chainz.get_profile_chain(name)?;
+                if let Some(key_name) = &key {
+                    chain = chain.with_key(chainz.get_key(key_name)?);
+                }
+                instances.push(chain);
+            }
+            if instances.is_empty() && !tags.is_empty() {
+                anyhow::bail!("No chains tagged {}", tags.join(", "));
+            }
+            if instances.is_empty() {
+                anyhow::bail!("No chains configured. Use 'chainz add' to add a chain first.");
+            }
+            chainz.release_config_lock();
+            let targets = exec::prepare(instances, &command, expose_key)?;
+            let outcomes = exec::run(&targets, jobs.into());
+            if !exec::summarize(&outcomes) {
+                std::process::exit(1);
+            }
+        }
         opt::Command::Exec {
             name_or_id,
             command,
             key,
             expose_key,
+            failover,
+            ..
         } => {
             // Explicit chain > configured default > interactive picker.
-            let name_or_id = match name_or_id.or_else(|| chainz.config.default_chain.clone()) {
+            let name_or_id = match name_or_id
+                .or_else(|| chainz.config.effective_default().map(str::to_string))
+            {
                 Some(id) => id,
-                None => select_chain(&chainz)?,
+                None => select_chain(&chainz, &[])?,
             };
-            let mut chain = chainz.get_chain(&name_or_id)?;
+            let mut chain = chainz.get_profile_chain(&name_or_id)?;
             if let Some(key_name) = key {
                 chain = chain.with_key(chainz.get_key(&key_name)?);
             }
+            if failover {
+                chain.rpc_url =
+                    Failover::ranked(&chain.definition, chainz.variables_for(&chain.definition))
+                        .await
+                        .spawn()
+                        .await?;
+            }
             let variables = ChainVariables::new(&chain, &command, expose_key)?;
-            let expanded_command = variables.expand(command);
+            let expanded_command = variables.expand(command)?;
 
             chainz.release_config_lock();
-            let status = ProcessCommand::new(&expanded_command[0])
-                .args(&expanded_command[1..])
-                .envs(variables.as_map())
-                .status()?;
+            let mut child = ProcessCommand::new(&expanded_command[0]);
+            child.args(&expanded_command[1..]).envs(variables.as_map());
+            // Off the runtime's workers: a --failover proxy serves meanwhile.
+            let status = tokio::task::spawn_blocking(move || child.status()).await??;
 
             if !status.success() {
                 std::process::exit(status.code().unwrap_or(1));
@@ -189,11 +381,16 @@ async fn dispatch() -> Result<()> {
     Ok(())
 }
 
-fn select_chain(chainz: &Chainz) -> Result<String> {
-    let chains = chainz.list_chains();
+/// Pick among the profile's chains that carry every one of `tags`.
+fn select_chain(chainz: &Chainz, tags: &[String]) -> Result<String> {
+    let mut chains = chainz.profile_chains();
     if chains.is_empty() {
         anyhow::bail!("No chains configured. Use 'chainz add' to add a chain first.");
     }
+    chains.retain(|chain| chain.has_tags(tags));
+    if chains.is_empty() {
+        anyhow::bail!("No chains tagged {}", tags.join(", "));
+    }
     let items: Vec<String> = chains
         .iter()
         .map(|chain| format!("{} ({})", chain.name, chain.chain_id))
@@ -1,23 +1,30 @@
 use crate::{
-    chain::{ChainDefinition, ChainInstance},
+    chain::{ChainDefinition, ChainInstance, is_ws_url},
+    doctor::CheckLevel,
     key::Key,
+    ui,
     variables::GlobalVariables,
 };
 use anyhow::{Context, Result, anyhow};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+mod profile;
+mod project;
 mod store;
-pub(crate) use store::config_exists;
+pub use profile::Profile;
+use project::ProjectConfig;
 use store::{
-    ConfigLock, ensure_private_dir, get_config_path, migrate_legacy_config, restrict_permissions,
-    write_atomically,
+    ConfigLock, get_config_path, migrate_legacy_config, restrict_permissions, write_atomically,
 };
+pub(crate) use store::{config_dir, config_exists, ensure_private_dir};
 
 /// Pre-0.3 config location, relative to $HOME. Migrated on first load.
 pub const LEGACY_CONFIG_FILE: &str = ".chainz.json";
 /// Config location relative to $HOME (when XDG_CONFIG_HOME is unset).
 const DEFAULT_CONFIG_RELATIVE: &str = ".config/chainz/config.json";
+/// Turns on `strict_variables` for one process.
+const STRICT_VARIABLES_ENV: &str = "CHAINZ_STRICT_VARIABLES";
 
 #[derive(Default, Debug, Serialize, Deserialize)]
 pub struct Config {
@@ -28,16 +35,59 @@ pub struct Config {
     /// Chain used by `exec` when none is specified; set via `chainz use`
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub default_chain: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    /// Profile selected by `chainz profile use`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<String>,
+    /// Severity overrides for `chainz doctor`, keyed by check ID or section
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub doctor: BTreeMap<String, CheckLevel>,
+    /// Fail instead of leaving `${VAR}` in a URL when VAR does not resolve;
+    /// `CHAINZ_STRICT_VARIABLES=1` enables it for one process
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub strict_variables: bool,
+    /// Profile selected for this process by `CHAINZ_PROFILE`; never saved
+    #[serde(skip)]
+    pub session_profile: Option<String>,
 }
 
 #[derive(Default)]
 pub struct Chainz {
+    /// The effective config: the global file with any project overlay
+    /// merged over it.
     pub config: Config,
+    overlay: Option<Overlay>,
     // Commands hold this lock from load through save, making the complete
     // read-modify-write operation serial across chainz processes.
     _config_lock: Option<ConfigLock>,
 }
 
+/// Where an effective config value came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ConfigLayer {
+    Global,
+    Project,
+}
+
+impl ConfigLayer {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            ConfigLayer::Global => "global",
+            ConfigLayer::Project => "project",
+        }
+    }
+}
+
+/// A merged project overlay and the global values it shadows, so saves can
+/// write back exactly the global layer.
+struct Overlay {
+    project: ProjectConfig,
+    shadowed_chains: Vec<ChainDefinition>,
+    shadowed_variables: HashMap<String, String>,
+    global_default: Option<String>,
+}
+
 impl Chainz {
     pub fn new() -> Self {
         Self::default()
@@ -48,10 +98,7 @@ impl Chainz {
         // propagated so the next save can never wipe a broken real config.
         let config_lock = ConfigLock::acquire().await?;
         let config = Config::load_locked(true).await?.unwrap_or_default();
-        Ok(Self {
-            config,
-            _config_lock: Some(config_lock),
-        })
+        Self::with_project(config, config_lock, true).await
     }
 
     /// Load deserializable config without enforcing semantic invariants so
@@ -59,15 +106,121 @@ impl Chainz {
     pub async fn load_for_doctor() -> Result<Self> {
         let config_lock = ConfigLock::acquire().await?;
         let config = Config::load_locked(false).await?.unwrap_or_default();
+        Self::with_project(config, config_lock, false).await
+    }
+
+    /// Merge the nearest project overlay, if any, over the loaded config.
+    async fn with_project(
+        mut config: Config,
+        config_lock: ConfigLock,
+        validate: bool,
+    ) -> Result<Self> {
+        if let Ok(name) = std::env::var(profile::PROFILE_ENV)
+            && !name.is_empty()
+        {
+            if validate && !config.profiles.contains_key(&name) {
+                anyhow::bail!(
+                    "Profile '{}' (from {}) is not configured",
+                    name,
+                    profile::PROFILE_ENV
+                );
+            }
+            config.session_profile = Some(name);
+        }
+        let home = dirs::home_dir();
+        let found = match std::env::current_dir() {
+            Ok(cwd) => project::discover(&cwd, home.as_deref())?,
+            Err(_) => None,
+        };
+        let overlay = match found {
+            Some(path) => {
+                let project = project::load(&path).await?;
+                let overlay = config.merge_project(project);
+                if !overlay.shadowed_chains.is_empty() {
+                    let names: Vec<_> = overlay
+                        .shadowed_chains
+                        .iter()
+                        .map(|chain| format!("{} ({})", chain.name, chain.chain_id))
+                        .collect();
+                    eprintln!(
+                        "{}",
+                        ui::warn(&format!(
+                            "{} replaces global chain(s) {}; their RPC URLs now come from that file",
+                            path.display(),
+                            names.join(", ")
+                        ))
+                    );
+                }
+                if validate {
+                    config
+                        .validate()
+                        .with_context(|| format!("Invalid project config at {}", path.display()))?;
+                }
+                Some(overlay)
+            }
+            None => None,
+        };
         Ok(Self {
             config,
+            overlay,
             _config_lock: Some(config_lock),
         })
     }
 
+    /// The layer that defines `chain_name`.
+    pub(crate) fn chain_layer(&self, chain_name: &str) -> ConfigLayer {
+        match &self.overlay {
+            Some(overlay) if overlay.project.chains.iter().any(|c| c.name == chain_name) => {
+                ConfigLayer::Project
+            }
+            _ => ConfigLayer::Global,
+        }
+    }
+
+    /// The variables `chain`'s URLs expand from. Project chains see only
+    /// their file's variables, so a cloned repository cannot send global
+    /// secrets or the environment to a URL it chose.
+    pub(crate) fn variables_for(&self, chain: &ChainDefinition) -> &GlobalVariables {
+        match &self.overlay {
+            Some(overlay) if self.chain_layer(&chain.name) == ConfigLayer::Project => {
+                &overlay.project.variables
+            }
+            _ => &self.config.globals,
+        }
+    }
+
+    /// The layer that selects the default chain.
+    pub(crate) fn default_layer(&self) -> ConfigLayer {
+        match &self.overlay {
+            Some(overlay) if overlay.project.default_chain.is_some() => ConfigLayer::Project,
+            _ => ConfigLayer::Global,
+        }
+    }
+
+    /// The file backing `layer`.
+    pub(crate) fn layer_path(&self, layer: ConfigLayer) -> Option<std::path::PathBuf> {
+        match layer {
+            ConfigLayer::Global => get_config_path(),
+            ConfigLayer::Project => self.overlay.as_ref().map(|o| o.project.path.clone()),
+        }
+    }
+
     pub fn get_chain(&self, name_or_id: &str) -> Result<ChainInstance> {
-        let definition = self.config.get_chain(name_or_id)?.clone();
-        let rpc_url = self.config.globals.expand_rpc_url(&definition.selected_rpc);
+        let mut definition = self.config.get_chain(name_or_id)?.clone();
+        definition.key_name = self
+            .config
+            .effective_key_name(&definition)
+            .map(str::to_string);
+        let strict = self.config.strict_variables
+            || std::env::var_os(STRICT_VARIABLES_ENV).is_some_and(|value| value != "0");
+        let variables = self.variables_for(&definition);
+        let expand = |url: &str| {
+            variables
+                .expand_checked(url, strict)
+                .with_context(|| format!("Chain '{}' cannot be used", definition.name))
+        };
+        let rpc_url = expand(&definition.selected_rpc)?;
+        let ws_url = definition.selected_ws.as_deref().map(expand).transpose()?;
         let key = definition
             .key_name
             .as_deref()
@@ -76,6 +229,7 @@ impl Chainz {
         Ok(ChainInstance {
             definition,
             rpc_url,
+            ws_url,
             key,
         })
     }
@@ -131,6 +285,14 @@ impl Chainz {
                 referenced_by.join(", ")
             );
         }
+        if let Some((profile, _)) = self
+            .config
+            .profiles
+            .iter()
+            .find(|(_, profile)| profile.keys.values().any(|key| key == name))
+        {
+            anyhow::bail!("Key '{}' is still mapped by profile '{}'", name, profile);
+        }
         self.config.keys.remove(name);
         Ok(())
     }
@@ -152,6 +314,9 @@ impl Chainz {
                 detached += 1;
             }
         }
+        for profile in self.config.profiles.values_mut() {
+            profile.keys.retain(|_, key| key != name);
+        }
         detached
     }
 
@@ -205,18 +370,22 @@ impl Chainz {
     /// semantic validation either commit together or are both rolled back.
     fn commit_chain(&mut self, replacement: Option<usize>, chain: ChainDefinition) -> Result<()> {
         let previous_default = self.config.default_chain.clone();
+        let previous_profiles = self.config.profiles.clone();
         let previous_chain = replacement.map(|index| self.config.chains[index].clone());
         if let Some(index) = replacement {
             if self.config.default_chain.as_deref() == Some(self.config.chains[index].name.as_str())
             {
                 self.config.default_chain = Some(chain.name.clone());
             }
+            let old_name = self.config.chains[index].name.clone();
+            self.config.rename_in_profiles(&old_name, &chain.name);
             self.config.chains[index] = chain;
         } else {
             self.config.chains.push(chain);
         }
         if let Err(error) = self.config.validate() {
             self.config.default_chain = previous_default;
+            self.config.profiles = previous_profiles;
             match (replacement, previous_chain) {
                 (Some(index), Some(previous)) => self.config.chains[index] = previous,
                 (None, None) => {
@@ -230,10 +399,29 @@ impl Chainz {
     }
 
     /// Resolve and select a default chain while preserving the canonical
-    /// primary name in the persisted config.
+    /// primary name in the persisted config. With a profile active, this
+    /// sets the profile's default instead.
     pub fn set_default_chain(&mut self, name_or_id: &str) -> Result<String> {
         let name = self.config.get_chain(name_or_id)?.name.clone();
-        self.config.default_chain = Some(name.clone());
+        let profile_name = self
+            .config
+            .active_profile()
+            .map(|(name, _)| name.to_string());
+        match profile_name
+            .and_then(|p| self.config.profiles.get_mut(&p).map(|profile| (p, profile)))
+        {
+            Some((profile_name, profile)) => {
+                if !profile.contains(&name) {
+                    anyhow::bail!(
+                        "Chain '{}' is not in the active profile '{}'",
+                        name,
+                        profile_name
+                    );
+                }
+                profile.default_chain = Some(name.clone());
+            }
+            None => self.config.default_chain = Some(name.clone()),
+        }
         Ok(name)
     }
 
@@ -258,6 +446,7 @@ impl Chainz {
         if self.config.default_chain.as_deref() == Some(removed.name.as_str()) {
             self.config.default_chain = None;
         }
+        self.config.forget_in_profiles(&removed.name);
         Ok(removed)
     }
 
@@ -268,12 +457,102 @@ impl Chainz {
     }
 
     pub async fn save(&self) -> Result<()> {
+        let global = self.global_layer()?;
+        let config = global.as_ref().unwrap_or(&self.config);
         if self._config_lock.is_some() {
-            self.config.write_locked().await
+            config.write_locked().await
         } else {
             let _config_lock = ConfigLock::acquire().await?;
-            self.config.write_locked().await
+            config.write_locked().await
+        }
+    }
+
+    /// Separate the global layer from the effective config, or `None` when
+    /// no overlay is active. Project values are read-only here: changing or
+    /// removing one is an error pointing at the project file.
+    fn global_layer(&self) -> Result<Option<Config>> {
+        let Some(overlay) = &self.overlay else {
+            return Ok(None);
+        };
+        let project = &overlay.project;
+        let read_only = |what: String| {
+            anyhow!(
+                "{} is defined in {}; edit that file instead",
+                what,
+                project.path.display()
+            )
+        };
+
+        let mut chains = Vec::new();
+        for chain in &self.config.chains {
+            match project.chains.iter().find(|p| p.name == chain.name) {
+                Some(defined) if defined != chain => {
+                    return Err(read_only(format!("Chain '{}'", chain.name)));
+                }
+                Some(_) => {}
+                None => chains.push(chain.clone()),
+            }
         }
+        if let Some(removed) = project
+            .chains
+            .iter()
+            .find(|p| !self.config.chains.iter().any(|c| c.name == p.name))
+        {
+            return Err(read_only(format!("Chain '{}'", removed.name)));
+        }
+        chains.extend(overlay.shadowed_chains.iter().cloned());
+
+        let mut globals = GlobalVariables::default();
+        let effective = self.config.globals.list_rpc_expansions();
+        for (name, value) in effective {
+            match project.variables.get_rpc_expansion(name) {
+                Some(defined) if defined != value => {
+                    return Err(read_only(format!("Variable '{}'", name)));
+                }
+                Some(_) => {}
+                None => globals.add_rpc_expansion(name, value),
+            }
+        }
+        if let Some(removed) = project
+            .variables
+            .list_rpc_expansions()
+            .keys()
+            .find(|name| !effective.contains_key(*name))
+        {
+            return Err(read_only(format!("Variable '{}'", removed)));
+        }
+        for (name, value) in &overlay.shadowed_variables {
+            globals.add_rpc_expansion(name, value);
+        }
+
+        let default_chain = if project.default_chain.is_some() {
+            if self.config.default_chain != project.default_chain {
+                return Err(read_only("The default chain".to_string()));
+            }
+            overlay.global_default.clone()
+        } else {
+            match &self.config.default_chain {
+                Some(name) if self.chain_layer(name) == ConfigLayer::Project => {
+                    return Err(read_only(format!(
+                        "Chain '{}' (set default_chain there to use it by default)",
+                        name
+                    )));
+                }
+                default => default.clone(),
+            }
+        };
+
+        Ok(Some(Config {
+            chains,
+            globals,
+            keys: self.config.keys.clone(),
+            default_chain,
+            profiles: self.config.profiles.clone(),
+            doctor: self.config.doctor.clone(),
+            strict_variables: self.config.strict_variables,
+            active_profile: self.config.active_profile.clone(),
+            session_profile: None,
+        }))
     }
 
     /// Release the process-wide config transaction before starting work that
@@ -314,25 +593,97 @@ impl Config {
         Ok(Some(config))
     }
 
+    /// Merge a project overlay over this (global) config. Project chains
+    /// replace global chains with the same name or chain ID.
+    fn merge_project(&mut self, mut project: ProjectConfig) -> Overlay {
+        let (shadowed_chains, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.chains)
+            .into_iter()
+            .partition(|global| {
+                project.chains.iter().any(|p| {
+                    p.chain_id == global.chain_id || p.names().any(|n| global.matches_exact(n))
+                })
+            });
+        self.chains = kept;
+        self.chains.extend(project.chains.iter().cloned());
+
+        let mut shadowed_variables = HashMap::new();
+        for (name, value) in project.variables.list_rpc_expansions() {
+            if let Some(previous) = self.globals.get_rpc_expansion(name) {
+                shadowed_variables.insert(name.clone(), previous.to_string());
+            }
+            self.globals.add_rpc_expansion(name, value);
+        }
+
+        let global_default = self.default_chain.clone();
+        if let Some(default) = &project.default_chain {
+            // Canonicalize like `chainz use`; an unknown name fails validation.
+            let canonical = self
+                .get_chain(default)
+                .map(|chain| chain.name.clone())
+                .unwrap_or_else(|_| default.clone());
+            project.default_chain = Some(canonical);
+            self.default_chain = project.default_chain.clone();
+        } else if let Some(default) = &global_default
+            && let Some(shadow) = shadowed_chains.iter().find(|c| &c.name == default)
+            && let Some(replacement) = project
+                .chains
+                .iter()
+                .find(|p| p.chain_id == shadow.chain_id || p.matches_exact(&shadow.name))
+        {
+            // Keep the global default pointing at the chain that replaced it.
+            self.default_chain = Some(replacement.name.clone());
+        }
+
+        Overlay {
+            project,
+            shadowed_chains,
+            shadowed_variables,
+            global_default,
+        }
+    }
+
     pub(crate) fn get_chain(&self, name_or_id: &str) -> Result<&ChainDefinition> {
         self.find_chain_index(name_or_id)
             .map(|index| &self.chains[index])
     }
 
     /// Resolve a chain reference: exact chain ID, then exact name/alias
-    /// (case-insensitive), then unambiguous name/alias prefix.
+    /// (case-insensitive), then unambiguous name/alias prefix. With a profile
+    /// active, its chains are searched first, then all chains.
     pub(crate) fn find_chain_index(&self, name_or_id: &str) -> Result<usize> {
+        if self.active_profile().is_some()
+            && let Ok(index) = self.find_chain_index_in(name_or_id, |c| self.in_profile(c))
+        {
+            return Ok(index);
+        }
+        self.find_chain_index_in(name_or_id, |_| true)
+    }
+
+    fn find_chain_index_in(
+        &self,
+        name_or_id: &str,
+        candidate: impl Fn(&ChainDefinition) -> bool,
+    ) -> Result<usize> {
+        let candidates: Vec<usize> = (0..self.chains.len())
+            .filter(|&i| candidate(&self.chains[i]))
+            .collect();
         if let Ok(chain_id) = name_or_id.parse::<u64>()
-            && let Some(i) = self.chains.iter().position(|c| c.chain_id == chain_id)
+            && let Some(&i) = candidates
+                .iter()
+                .find(|&&i| self.chains[i].chain_id == chain_id)
         {
             return Ok(i);
         }
 
-        if let Some(i) = self.chains.iter().position(|c| c.matches_exact(name_or_id)) {
+        if let Some(&i) = candidates
+            .iter()
+            .find(|&&i| self.chains[i].matches_exact(name_or_id))
+        {
             return Ok(i);
         }
 
-        let matches: Vec<usize> = (0..self.chains.len())
+        let matches: Vec<usize> = candidates
+            .into_iter()
             .filter(|&i| self.chains[i].matches_prefix(name_or_id))
             .collect();
         match matches.as_slice() {
@@ -385,6 +736,10 @@ impl Config {
 
     pub(crate) fn validate(&self) -> Result<()> {
         self.globals.validate()?;
+        for selector in self.doctor.keys() {
+            crate::doctor::validate_selector(selector)
+                .context("Invalid doctor severity override")?;
+        }
 
         for (map_name, key) in &self.keys {
             if map_name != &key.name {
@@ -413,6 +768,29 @@ impl Config {
                     chain.name
                 );
             }
+            if let Some(ws_url) = &chain.selected_ws
+                && !chain.ws_urls.contains(ws_url)
+            {
+                anyhow::bail!(
+                    "Chain '{}' selected WebSocket URL is not present in its WebSocket list",
+                    chain.name
+                );
+            }
+            if let Some(url) = chain.ws_urls.iter().find(|url| !is_ws_url(url)) {
+                anyhow::bail!(
+                    "Chain '{}' WebSocket URL {} must start with ws:// or wss://",
+                    chain.name,
+                    crate::endpoint::redact(url)
+                );
+            }
+            for name in chain.env.keys() {
+                crate::variables::validate_chain_variable(name)
+                    .with_context(|| format!("Chain '{}' has an invalid variable", chain.name))?;
+            }
+            for tag in &chain.tags {
+                crate::chain::normalize_tag(tag)
+                    .with_context(|| format!("Chain '{}' has an invalid tag", chain.name))?;
+            }
             if let Some(key_name) = chain.key_name.as_deref()
                 && !self.keys.contains_key(key_name)
             {
@@ -451,7 +829,7 @@ impl Config {
         {
             anyhow::bail!("Default chain '{}' is not configured", default);
         }
-        Ok(())
+        self.validate_profiles()
     }
 
     fn normalize_legacy(&mut self) {
@@ -0,0 +1,288 @@
+//! Named profiles: bundles of chains with their own default chain and key
+//! mapping, e.g. production chains vs. their testnet twins.
+//!
+//! The active profile is `CHAINZ_PROFILE` when set, else the one persisted by
+//! `chainz profile use`. While a profile is active, `exec`, `shell`, `list`,
+//! and `doctor` only see its chains, and name lookups try it first.
+
+use super::{Chainz, Config};
+use crate::{chain::ChainDefinition, opt::ProfileCommand};
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Environment variable selecting a profile for one process.
+pub(crate) const PROFILE_ENV: &str = "CHAINZ_PROFILE";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    /// Primary names of the member chains
+    pub chains: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_chain: Option<String>,
+    /// Chain name to key name, overriding each chain's own `key_name`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub keys: BTreeMap<String, String>,
+}
+
+impl Profile {
+    pub(crate) fn contains(&self, chain_name: &str) -> bool {
+        self.chains.iter().any(|name| name == chain_name)
+    }
+}
+
+impl Config {
+    /// The selected profile name, whether or not it exists.
+    pub(crate) fn active_profile_name(&self) -> Option<&str> {
+        self.session_profile
+            .as_deref()
+            .or(self.active_profile.as_deref())
+    }
+
+    pub(crate) fn active_profile(&self) -> Option<(&str, &Profile)> {
+        let name = self.active_profile_name()?;
+        self.profiles
+            .get_key_value(name)
+            .map(|(name, profile)| (name.as_str(), profile))
+    }
+
+    /// Whether `chain` is visible under the active profile.
+    pub(crate) fn in_profile(&self, chain: &ChainDefinition) -> bool {
+        self.active_profile()
+            .is_none_or(|(_, profile)| profile.contains(&chain.name))
+    }
+
+    /// The default chain, as chosen by the active profile if there is one.
+    /// A profile without its own default inherits the global one only if
+    /// that chain is a member.
+    pub(crate) fn effective_default(&self) -> Option<&str> {
+        match self.active_profile() {
+            Some((_, profile)) => profile.default_chain.as_deref().or(self
+                .default_chain
+                .as_deref()
+                .filter(|name| profile.contains(name))),
+            None => self.default_chain.as_deref(),
+        }
+    }
+
+    /// The key name for `chain` after the active profile's mapping.
+    pub(crate) fn effective_key_name<'a>(&'a self, chain: &'a ChainDefinition) -> Option<&'a str> {
+        self.active_profile()
+            .and_then(|(_, profile)| profile.keys.get(&chain.name))
+            .map(String::as_str)
+            .or(chain.key_name.as_deref())
+    }
+
+    /// Keep profile references pointing at a renamed chain.
+    pub(super) fn rename_in_profiles(&mut self, old: &str, new: &str) {
+        for profile in self.profiles.values_mut() {
+            for name in &mut profile.chains {
+                if name == old {
+                    *name = new.to_string();
+                }
+            }
+            if profile.default_chain.as_deref() == Some(old) {
+                profile.default_chain = Some(new.to_string());
+            }
+            if let Some(key) = profile.keys.remove(old) {
+                profile.keys.insert(new.to_string(), key);
+            }
+        }
+    }
+
+    /// Drop a removed chain from every profile.
+    pub(super) fn forget_in_profiles(&mut self, chain_name: &str) {
+        for profile in self.profiles.values_mut() {
+            profile.chains.retain(|name| name != chain_name);
+            if profile.default_chain.as_deref() == Some(chain_name) {
+                profile.default_chain = None;
+            }
+            profile.keys.remove(chain_name);
+        }
+    }
+
+    pub(super) fn validate_profiles(&self) -> Result<()> {
+        for (profile_name, profile) in &self.profiles {
+            if profile_name.trim().is_empty() {
+                anyhow::bail!("Profile names cannot be empty");
+            }
+            for (index, chain) in profile.chains.iter().enumerate() {
+                if !self.chains.iter().any(|c| c.name == *chain) {
+                    anyhow::bail!(
+                        "Profile '{}' references missing chain '{}'",
+                        profile_name,
+                        chain
+                    );
+                }
+                if profile.chains[..index].contains(chain) {
+                    anyhow::bail!("Profile '{}' lists '{}' twice", profile_name, chain);
+                }
+            }
+            if let Some(default) = &profile.default_chain
+                && !profile.contains(default)
+            {
+                anyhow::bail!(
+                    "Profile '{}' default chain '{}' is not in the profile",
+                    profile_name,
+                    default
+                );
+            }
+            for (chain, key) in &profile.keys {
+                if !profile.contains(chain) {
+                    anyhow::bail!(
+                        "Profile '{}' maps a key for '{}', which is not in the profile",
+                        profile_name,
+                        chain
+                    );
+                }
+                if !self.keys.contains_key(key) {
+                    anyhow::bail!(
+                        "Profile '{}' references missing key '{}'",
+                        profile_name,
+                        key
+                    );
+                }
+            }
+        }
+        if let Some(active) = &self.active_profile
+            && !self.profiles.contains_key(active)
+        {
+            anyhow::bail!("Active profile '{}' is not configured", active);
+        }
+        Ok(())
+    }
+}
+
+impl Chainz {
+    /// Chains visible under the active profile, with its key mapping
+    /// applied. Without a profile this is every configured chain.
+    pub(crate) fn profile_chains(&self) -> Vec<ChainDefinition> {
+        self.config
+            .chains
+            .iter()
+            .filter(|chain| self.config.in_profile(chain))
+            .map(|chain| {
+                let mut chain = chain.clone();
+                chain.key_name = self.config.effective_key_name(&chain).map(str::to_string);
+                chain
+            })
+            .collect()
+    }
+
+    /// Resolve a chain for commands scoped to the active profile.
+    pub(crate) fn get_profile_chain(
+        &self,
+        name_or_id: &str,
+    ) -> Result<crate::chain::ChainInstance> {
+        let chain = self.get_chain(name_or_id)?;
+        if let Some((profile, _)) = self.config.active_profile()
+            && !self.config.in_profile(&chain.definition)
+        {
+            anyhow::bail!(
+                "Chain '{}' is not in the active profile '{}'",
+                chain.definition.name,
+                profile
+            );
+        }
+        Ok(chain)
+    }
+}
+
+impl ProfileCommand {
+    pub async fn handle(self, chainz: &mut Chainz) -> Result<()> {
+        match self {
+            ProfileCommand::Create {
+                name,
+                chains,
+                default,
+                keys,
+            } => {
+                if chainz.config.profiles.contains_key(&name) {
+                    anyhow::bail!("Profile '{}' already exists", name);
+                }
+                let mut profile = Profile::default();
+                for chain in &chains {
+                    profile
+                        .chains
+                        .push(chainz.config.get_chain(chain)?.name.clone());
+                }
+                if let Some(default) = default {
+                    profile.default_chain = Some(chainz.config.get_chain(&default)?.name.clone());
+                }
+                for mapping in keys {
+                    let (chain, key) = mapping
+                        .split_once('=')
+                        .ok_or_else(|| anyhow!("Expected CHAIN=KEY, got '{}'", mapping))?;
+                    let chain = chainz.config.get_chain(chain)?.name.clone();
+                    chainz.get_key(key)?;
+                    profile.keys.insert(chain, key.to_string());
+                }
+                chainz.config.profiles.insert(name.clone(), profile);
+                if let Err(error) = chainz.config.validate() {
+                    chainz.config.profiles.remove(&name);
+                    return Err(error);
+                }
+                chainz.save().await?;
+                println!("Created profile '{}'", name);
+            }
+            ProfileCommand::Use { name } => {
+                if !chainz.config.profiles.contains_key(&name) {
+                    anyhow::bail!("Profile '{}' not found", name);
+                }
+                chainz.config.active_profile = Some(name.clone());
+                chainz.save().await?;
+                println!("Active profile set to '{}'", name);
+                warn_if_overridden(&chainz.config);
+            }
+            ProfileCommand::Off => {
+                chainz.config.active_profile = None;
+                chainz.save().await?;
+                println!("Profiles deactivated; all chains are visible");
+                warn_if_overridden(&chainz.config);
+            }
+            ProfileCommand::List => {
+                if chainz.config.profiles.is_empty() {
+                    println!("No profiles configured");
+                }
+                let active = chainz.config.active_profile_name();
+                for (name, profile) in &chainz.config.profiles {
+                    println!(
+                        "{} {} ({}){}",
+                        if Some(name.as_str()) == active {
+                            "*"
+                        } else {
+                            " "
+                        },
+                        name,
+                        profile.chains.join(", "),
+                        profile
+                            .default_chain
+                            .as_deref()
+                            .map(|default| format!(" default: {}", default))
+                            .unwrap_or_default()
+                    );
+                }
+            }
+            ProfileCommand::Remove { name } => {
+                if chainz.config.profiles.remove(&name).is_none() {
+                    anyhow::bail!("Profile '{}' not found", name);
+                }
+                if chainz.config.active_profile.as_deref() == Some(name.as_str()) {
+                    chainz.config.active_profile = None;
+                }
+                chainz.save().await?;
+                println!("Removed profile '{}'", name);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn warn_if_overridden(config: &Config) {
+    if let Some(session) = &config.session_profile {
+        eprintln!(
+            "Note: {}={} overrides this in the current environment",
+            PROFILE_ENV, session
+        );
+    }
+}
@@ -0,0 +1,281 @@
+//! Per-project overlays discovered from the working directory.
+//!
+//! A repository can carry a `.chainz.toml` or `.chainz.json` with its own
+//! chains, variables, and default chain. The overlay is merged over the
+//! global config for reads and is never written by chainz; keys and secret
+//! references stay in the global config only, since a cloned repository
+//! must not be able to run commands or read secrets. For the same reason
+//! project chains cannot attach keys or set the chain variables `exec`
+//! exports (a `PATH` or `BASH_ENV` there would run the repository's code),
+//! and their URLs expand only from the project's own variables, never global
+//! ones or the environment.
+
+use crate::{chain::ChainDefinition, variables::GlobalVariables};
+use anyhow::{Context, Result, anyhow};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Checked in this order in each directory.
+const PROJECT_FILES: [&str; 2] = [".chainz.toml", ".chainz.json"];
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProjectFile {
+    #[serde(default)]
+    chains: Vec<ChainDefinition>,
+    #[serde(default)]
+    variables: GlobalVariables,
+    #[serde(default)]
+    default_chain: Option<String>,
+    /// Accepted only so its presence gets a clear error below.
+    #[serde(default)]
+    keys: Option<serde::de::IgnoredAny>,
+}
+
+#[derive(Debug)]
+pub(crate) struct ProjectConfig {
+    pub(crate) path: PathBuf,
+    pub(crate) chains: Vec<ChainDefinition>,
+    pub(crate) variables: GlobalVariables,
+    pub(crate) default_chain: Option<String>,
+}
+
+/// Find the nearest project file at or above `start`. The walk stops below
+/// `home`, whose `.chainz.json` is the legacy global config.
+pub(super) fn discover(start: &Path, home: Option<&Path>) -> Result<Option<PathBuf>> {
+    for dir in start.ancestors() {
+        if Some(dir) == home {
+            break;
+        }
+        let found: Vec<PathBuf> = PROJECT_FILES
+            .iter()
+            .map(|file| dir.join(file))
+            .filter(|path| path.is_file())
+            .collect();
+        match found.as_slice() {
+            [] => continue,
+            [path] => return Ok(Some(path.clone())),
+            _ => anyhow::bail!(
+                "Both .chainz.toml and .chainz.json exist in {}; keep only one",
+                dir.display()
+            ),
+        }
+    }
+    Ok(None)
+}
+
+pub(super) async fn load(path: &Path) -> Result<ProjectConfig> {
+    let text = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Failed to read project config at {}", path.display()))?;
+    parse(path, &text)
+}
+
+fn parse(path: &Path, text: &str) -> Result<ProjectConfig> {
+    let parsed = if path.extension().is_some_and(|ext| ext == "toml") {
+        toml_to_json(text).and_then(|value| Ok(serde_json::from_value(value)?))
+    } else {
+        serde_json::from_str(text).map_err(Into::into)
+    };
+    let file: ProjectFile =
+        parsed.with_context(|| format!("Failed to parse project config at {}", path.display()))?;
+    if file.keys.is_some() {
+        anyhow::bail!(
+            "Project config at {} defines keys; keys are global-only (use `chainz key add`)",
+            path.display()
+        );
+    }
+    if let Some(name) = file.variables.secret_references().first() {
+        anyhow::bail!(
+            "Project config at {} sets variable '{}' to a secret reference; references are global-only (use `chainz var set`)",
+            path.display(),
+            name
+        );
+    }
+    if let Some(chain) = file.chains.iter().find(|chain| chain.key_name.is_some()) {
+        anyhow::bail!(
+            "Project config at {} attaches a key to chain '{}'; keys are global-only (assign one with a profile)",
+            path.display(),
+            chain.name
+        );
+    }
+    if let Some(chain) = file.chains.iter().find(|chain| !chain.env.is_empty()) {
+        anyhow::bail!(
+            "Project config at {} sets variables on chain '{}'; chain variables are exported to commands, so they are global-only (use `chainz var set --chain`)",
+            path.display(),
+            chain.name
+        );
+    }
+    let mut variables = file.variables;
+    variables.isolate();
+    let chains = file
+        .chains
+        .into_iter()
+        .map(|mut chain| {
+            // Project files may list endpoints without picking one.
+            if chain.selected_rpc.is_empty()
+                && let Some(first) = chain.rpc_urls.first()
+            {
+                chain.selected_rpc = first.clone();
+            }
+            chain
+        })
+        .collect();
+    Ok(ProjectConfig {
+        path: path.to_path_buf(),
+        chains,
+        variables,
+        default_chain: file.default_chain,
+    })
+}
+
+/// Convert a TOML document into the equivalent JSON value so both formats
+/// share one set of serde definitions.
+fn toml_to_json(text: &str) -> Result<serde_json::Value> {
+    let document: toml_edit::DocumentMut = text.parse()?;
+    item_to_json(document.as_item())
+}
+
+fn item_to_json(item: &toml_edit::Item) -> Result<serde_json::Value> {
+    use toml_edit::Item;
+    match item {
+        Item::None => Ok(serde_json::Value::Null),
+        Item::Value(value) => value_to_json(value),
+        Item::Table(table) => table
+            .iter()
+            .map(|(key, item)| Ok((key.to_string(), item_to_json(item)?)))
+            .collect::<Result<_>>()
+            .map(serde_json::Value::Object),
+        Item::ArrayOfTables(tables) => tables
+            .iter()
+            .map(|table| item_to_json(&Item::Table(table.clone())))
+            .collect::<Result<_>>()
+            .map(serde_json::Value::Array),
+    }
+}
+
+fn value_to_json(value: &toml_edit::Value) -> Result<serde_json::Value> {
+    use toml_edit::Value;
+    Ok(match value {
+        Value::String(s) => serde_json::Value::String(s.value().clone()),
+        Value::Integer(i) => serde_json::Value::from(*i.value()),
+        Value::Float(f) => serde_json::Value::from(*f.value()),
+        Value::Boolean(b) => serde_json::Value::Bool(*b.value()),
+        Value::Datetime(_) => return Err(anyhow!("Dates are not supported in project config")),
+        Value::Array(array) => {
+            serde_json::Value::Array(array.iter().map(value_to_json).collect::<Result<_>>()?)
+        }
+        Value::InlineTable(table) => serde_json::Value::Object(
+            table
+                .iter()
+                .map(|(key, value)| Ok((key.to_string(), value_to_json(value)?)))
+                .collect::<Result<_>>()?,
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const TOML: &str = r#"
+default_chain = "devnet"
+
+[variables]
+DEV_RPC = "http://localhost:8545"
+
+[[chains]]
+name = "devnet"
+chain_id = 1337
+rpc_urls = ["${DEV_RPC}"]
+"#;
+
+    #[test]
+    fn toml_and_json_overlays_parse_to_the_same_config() -> Result<()> {
+        let toml = parse(Path::new(".chainz.toml"), TOML)?;
+        let json = parse(
+            Path::new(".chainz.json"),
+            r#"{
+                "default_chain": "devnet",
+                "variables": {"DEV_RPC": "http://localhost:8545"},
+                "chains": [{"name": "devnet", "chain_id": 1337,
+                            "rpc_urls": ["${DEV_RPC}"]}]
+            }"#,
+        )?;
+        for project in [toml, json] {
+            assert_eq!(project.default_chain.as_deref(), Some("devnet"));
+            assert_eq!(project.chains[0].selected_rpc, "${DEV_RPC}");
+            assert_eq!(
+                project.variables.get_rpc_expansion("DEV_RPC"),
+                Some("http://localhost:8545")
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn project_files_cannot_define_keys_or_unknown_fields() {
+        let keys = parse(
+            Path::new(".chainz.json"),
+            r#"{"keys": {"default": {"type": "PrivateKey", "value": "0x01"}}}"#,
+        );
+        assert!(format!("{:#}", keys.unwrap_err()).contains("keys are global-only"));
+        let attached = parse(
+            Path::new(".chainz.toml"),
+            &format!("{TOML}key_name = \"default\"\n"),
+        );
+        assert!(format!("{:#}", attached.unwrap_err()).contains("attaches a key"));
+        let exported = parse(
+            Path::new(".chainz.json"),
+            r#"{"chains": [{"name": "devnet", "chain_id": 1337,
+                            "rpc_urls": ["http://localhost:8545"],
+                            "env": {"PATH": "./bin", "BASH_ENV": "/tmp/evil.sh"}}]}"#,
+        );
+        assert!(format!("{:#}", exported.unwrap_err()).contains("chain variables are exported"));
+        assert!(parse(Path::new(".chainz.toml"), "defualt_chain = \"x\"").is_err());
+    }
+
+    #[test]
+    fn project_files_cannot_define_secret_references() {
+        for value in [
+            "secret:cmd:touch /tmp/pwned && echo http://127.0.0.1:1",
+            "secret:env:HOME",
+            "secret:keyring:var:ALCHEMY_KEY",
+            "secret:keyring:default",
+            "secret:op://dev/alchemy",
+        ] {
+            let text = serde_json::json!({"variables": {"X": value}}).to_string();
+            let error = parse(Path::new(".chainz.json"), &text).unwrap_err();
+            assert!(
+                format!("{:#}", error).contains("references are global-only"),
+                "{value} was accepted"
+            );
+        }
+        let toml = parse(
+            Path::new(".chainz.toml"),
+            "[variables]\nX = \"secret:cmd:id\"\n",
+        );
+        assert!(toml.is_err());
+    }
+
+    #[test]
+    fn discovery_walks_up_but_stops_at_home() -> Result<()> {
+        let home = TempDir::new()?;
+        let repo = home.path().join("repo");
+        let nested = repo.join("contracts").join("src");
+        std::fs::create_dir_all(&nested)?;
+        std::fs::write(home.path().join(".chainz.json"), "{}")?;
+        assert_eq!(discover(&nested, Some(home.path()))?, None);
+
+        std::fs::write(repo.join(".chainz.toml"), TOML)?;
+        assert_eq!(
+            discover(&nested, Some(home.path()))?,
+            Some(repo.join(".chainz.toml"))
+        );
+
+        std::fs::write(repo.join(".chainz.json"), "{}")?;
+        assert!(discover(&nested, Some(home.path())).is_err());
+        Ok(())
+    }
+}
@@ -64,7 +64,7 @@ pub(super) fn write_atomically(path: &Path, contents: &[u8]) -> Result<()> {
     Ok(())
 }
 
-pub(super) async fn ensure_private_dir(dir: &Path) -> std::io::Result<()> {
+pub(crate) async fn ensure_private_dir(dir: &Path) -> std::io::Result<()> {
     tokio::fs::create_dir_all(dir).await?;
     #[cfg(unix)]
     tokio::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700)).await?;
@@ -104,6 +104,12 @@ pub(super) fn get_config_path() -> Option<PathBuf> {
     }
 }
 
+/// The private directory holding the config, its lock, and other
+/// per-user chainz state.
+pub(crate) fn config_dir() -> Option<PathBuf> {
+    get_config_path()?.parent().map(Path::to_path_buf)
+}
+
 fn legacy_config_path() -> Option<PathBuf> {
     Some(home_dir()?.join(LEGACY_CONFIG_FILE))
 }
@@ -6,12 +6,17 @@ fn test_chain(name: &str, chain_id: u64) -> ChainDefinition {
     ChainDefinition {
         name: name.to_string(),
         aliases: vec![],
+        tags: vec![],
         chain_id,
         rpc_urls: vec!["https://rpc.example.com".to_string()],
         selected_rpc: "https://rpc.example.com".to_string(),
+        ws_urls: vec![],
+        selected_ws: None,
         verification_api_key: None,
         verification_url: None,
         key_name: Some("default".to_string()),
+        env: Default::default(),
+        metadata: Default::default(),
     }
 }
 
@@ -61,6 +66,33 @@ fn config_json_round_trip() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn legacy_values_that_look_like_references_stay_plain() -> Result<()> {
+    let json = r#"{
+        "chains": [],
+        "variables": {
+            "KEYRING": "keyring:default",
+            "VAULT": "op://vault",
+            "EMPTY_ENV": "env:",
+            "COMMAND": "cmd:touch /tmp/chainz-should-not-run"
+        },
+        "keys": {}
+    }"#;
+    let config: Config = serde_json::from_str(json)?;
+    config.validate()?;
+    assert!(config.globals.secret_references().is_empty());
+    assert_eq!(
+        config
+            .globals
+            .expand_rpc_url("${COMMAND}|${EMPTY_ENV}|${VAULT}|${KEYRING}"),
+        "cmd:touch /tmp/chainz-should-not-run|env:|op://vault|keyring:default"
+    );
+    let restored: serde_json::Value = serde_json::to_value(&config)?;
+    let original: serde_json::Value = serde_json::from_str(json)?;
+    assert_eq!(restored["variables"], original["variables"]);
+    Ok(())
+}
+
 #[test]
 fn get_chain_by_name_case_insensitive() -> Result<()> {
     let mut config = Config::default();
@@ -362,3 +394,228 @@ fn selecting_a_new_rpc_adds_it_to_the_configured_list() -> Result<()> {
     assert!(chainz.config.validate().is_ok());
     Ok(())
 }
+
+fn with_project(mut config: Config, chains: Vec<ChainDefinition>, default: Option<&str>) -> Chainz {
+    let mut variables = GlobalVariables::default();
+    variables.add_rpc_expansion("RPC_KEY", "project");
+    variables.isolate();
+    let overlay = config.merge_project(ProjectConfig {
+        path: std::path::PathBuf::from("/repo/.chainz.toml"),
+        chains,
+        variables,
+        default_chain: default.map(str::to_string),
+    });
+    Chainz {
+        config,
+        overlay: Some(overlay),
+        _config_lock: None,
+    }
+}
+
+fn global_config() -> Config {
+    let mut config = Config::default();
+    config.chains.push(test_chain("ethereum", 1));
+    config.chains.push(test_chain("sepolia", 11155111));
+    config.globals.add_rpc_expansion("RPC_KEY", "global");
+    config.globals.add_rpc_expansion("OTHER", "kept");
+    config
+        .keys
+        .insert("default".to_string(), test_key("default"));
+    config.default_chain = Some("sepolia".to_string());
+    config
+}
+
+#[test]
+fn project_overlay_shadows_global_chains_and_variables() -> Result<()> {
+    // Same chain ID under another name replaces the global definition.
+    let chainz = with_project(global_config(), vec![test_chain("testnet", 11155111)], None);
+
+    let names: Vec<_> = chainz
+        .config
+        .chains
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect();
+    assert_eq!(names, ["ethereum", "testnet"]);
+    assert_eq!(
+        chainz.config.globals.get_rpc_expansion("RPC_KEY"),
+        Some("project")
+    );
+    assert_eq!(
+        chainz.config.globals.get_rpc_expansion("OTHER"),
+        Some("kept")
+    );
+    // The global default follows the chain that replaced it.
+    assert_eq!(chainz.config.default_chain.as_deref(), Some("testnet"));
+    assert_eq!(chainz.chain_layer("testnet"), ConfigLayer::Project);
+    assert_eq!(chainz.chain_layer("ethereum"), ConfigLayer::Global);
+    assert_eq!(chainz.default_layer(), ConfigLayer::Global);
+    Ok(())
+}
+
+#[test]
+fn project_chain_urls_cannot_read_global_variables_or_the_environment() -> Result<()> {
+    let mut config = global_config();
+    config.globals.add_rpc_expansion("GLOBAL_SECRET", "hunter2");
+    let url = "https://evil.example/${GLOBAL_SECRET}/${PATH}/${RPC_KEY}";
+    let mut devnet = test_chain("devnet", 1337);
+    devnet.rpc_urls = vec![url.to_string()];
+    devnet.selected_rpc = url.to_string();
+    let mut global = test_chain("mainnet", 1);
+    global.rpc_urls = vec!["https://rpc.example/${GLOBAL_SECRET}".to_string()];
+    global.selected_rpc = global.rpc_urls[0].clone();
+    config.chains[0] = global;
+    let chainz = with_project(config, vec![devnet], None);
+
+    assert_eq!(
+        chainz.get_chain("devnet")?.rpc_url,
+        "https://evil.example/${GLOBAL_SECRET}/${PATH}/project"
+    );
+    assert_eq!(
+        chainz.get_chain("mainnet")?.rpc_url,
+        "https://rpc.example/hunter2"
+    );
+    Ok(())
+}
+
+#[test]
+fn saving_with_an_overlay_writes_only_the_global_layer() -> Result<()> {
+    let mut chainz = with_project(
+        global_config(),
+        vec![test_chain("devnet", 1337)],
+        Some("DEVNET"),
+    );
+    assert_eq!(chainz.config.default_chain.as_deref(), Some("devnet"));
+    assert_eq!(chainz.default_layer(), ConfigLayer::Project);
+
+    chainz.add_chain(test_chain("base", 8453))?;
+    chainz.config.globals.add_rpc_expansion("NEW", "value");
+    let global = chainz.global_layer()?.expect("overlay is active");
+
+    let names: Vec<_> = global.chains.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["ethereum", "sepolia", "base"]);
+    assert_eq!(global.globals.get_rpc_expansion("RPC_KEY"), Some("global"));
+    assert_eq!(global.globals.get_rpc_expansion("NEW"), Some("value"));
+    assert_eq!(global.default_chain.as_deref(), Some("sepolia"));
+    assert!(global.keys.contains_key("default"));
+    Ok(())
+}
+
+#[test]
+fn project_owned_values_cannot_be_changed_through_the_global_config() -> Result<()> {
+    let mut chainz = with_project(global_config(), vec![test_chain("devnet", 1337)], None);
+    chainz.remove_chain_exact("devnet")?;
+    let error = chainz.global_layer().unwrap_err().to_string();
+    assert!(
+        error.contains("Chain 'devnet' is defined in /repo/.chainz.toml"),
+        "{error}"
+    );
+
+    let mut chainz = with_project(global_config(), vec![test_chain("devnet", 1337)], None);
+    chainz.config.globals.add_rpc_expansion("RPC_KEY", "edited");
+    assert!(chainz.global_layer().is_err());
+
+    let mut chainz = with_project(global_config(), vec![test_chain("devnet", 1337)], None);
+    chainz.set_default_chain("devnet")?;
+    assert!(chainz.global_layer().is_err());
+    Ok(())
+}
+
+fn chainz_with_testnets() -> Result<Chainz> {
+    let mut chainz = chainz_for_chains()?;
+    chainz.add_key("burner", test_key("burner"))?;
+    chainz.add_chain(test_chain("ethereum", 1))?;
+    chainz.add_chain(test_chain("sepolia", 11155111))?;
+    chainz.add_chain(test_chain("base", 8453))?;
+    chainz.add_chain(test_chain("base-sepolia", 84532))?;
+    chainz.set_default_chain("ethereum")?;
+    chainz.config.profiles.insert(
+        "testnets".to_string(),
+        Profile {
+            chains: vec!["sepolia".to_string(), "base-sepolia".to_string()],
+            default_chain: Some("sepolia".to_string()),
+            keys: BTreeMap::from([("sepolia".to_string(), "burner".to_string())]),
+        },
+    );
+    chainz.config.validate()?;
+    Ok(chainz)
+}
+
+#[test]
+fn active_profile_scopes_chains_default_and_keys() -> Result<()> {
+    let mut chainz = chainz_with_testnets()?;
+    assert_eq!(chainz.profile_chains().len(), 4);
+    assert_eq!(chainz.config.get_chain("base")?.name, "base");
+
+    chainz.config.active_profile = Some("testnets".to_string());
+    let names: Vec<_> = chainz
+        .profile_chains()
+        .into_iter()
+        .map(|c| c.name)
+        .collect();
+    assert_eq!(names, ["sepolia", "base-sepolia"]);
+    assert_eq!(chainz.config.effective_default(), Some("sepolia"));
+    // "base" is a prefix of both base chains; the profile resolves it first.
+    assert_eq!(chainz.config.get_chain("base")?.name, "base-sepolia");
+    // Chains outside the profile still resolve for management commands...
+    assert_eq!(chainz.config.get_chain("ethereum")?.name, "ethereum");
+    // ...but not for profile-scoped ones.
+    assert!(chainz.get_profile_chain("ethereum").is_err());
+    let sepolia = chainz.get_profile_chain("sepolia")?;
+    assert_eq!(
+        sepolia.key.as_ref().map(|key| key.name.as_str()),
+        Some("burner")
+    );
+
+    // The environment override wins over the persisted selection.
+    chainz.config.session_profile = Some("missing".to_string());
+    assert_eq!(chainz.config.active_profile_name(), Some("missing"));
+    assert!(chainz.config.active_profile().is_none());
+    Ok(())
+}
+
+#[test]
+fn use_sets_the_profile_default_within_the_profile() -> Result<()> {
+    let mut chainz = chainz_with_testnets()?;
+    chainz.config.active_profile = Some("testnets".to_string());
+    chainz.set_default_chain("base-sepolia")?;
+    assert_eq!(
+        chainz.config.profiles["testnets"].default_chain.as_deref(),
+        Some("base-sepolia")
+    );
+    assert_eq!(chainz.config.default_chain.as_deref(), Some("ethereum"));
+    assert!(chainz.set_default_chain("ethereum").is_err());
+    Ok(())
+}
+
+#[test]
+fn profiles_follow_chain_renames_and_removals() -> Result<()> {
+    let mut chainz = chainz_with_testnets()?;
+    chainz.replace_chain("sepolia", test_chain("eth-sepolia", 11155111))?;
+    let profile = &chainz.config.profiles["testnets"];
+    assert_eq!(profile.chains, ["eth-sepolia", "base-sepolia"]);
+    assert_eq!(profile.default_chain.as_deref(), Some("eth-sepolia"));
+    assert!(profile.keys.contains_key("eth-sepolia"));
+
+    assert!(chainz.remove_key("burner").is_err());
+    chainz.remove_chain_exact("eth-sepolia")?;
+    let profile = &chainz.config.profiles["testnets"];
+    assert_eq!(profile.chains, ["base-sepolia"]);
+    assert_eq!(profile.default_chain, None);
+    assert!(profile.keys.is_empty());
+    chainz.remove_key("burner")?;
+    chainz.config.validate()
+}
+
+#[test]
+fn profile_validation_rejects_dangling_references() -> Result<()> {
+    let mut chainz = chainz_with_testnets()?;
+    chainz.config.active_profile = Some("mainnets".to_string());
+    assert!(chainz.config.validate().is_err());
+    chainz.config.active_profile = None;
+
+    let profile = chainz.config.profiles.get_mut("testnets").unwrap();
+    profile.default_chain = Some("ethereum".to_string());
+    assert!(chainz.config.validate().is_err());
+    Ok(())
+}
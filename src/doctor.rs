@@ -2,141 +2,544 @@
 //!
 //! Failures (dangling key references, dead selected RPCs) make the command
 //! exit nonzero; warnings (plaintext key storage) are informational only.
-//! RPC failover deliberately lives here rather than in `exec`, which stays
-//! network-free and fast.
+//! Every outcome is recorded as a `Check` under a stable ID, printed live as
+//! text or rendered afterwards as JSON, SARIF, or JUnit.
+//! Persistent RPC repair deliberately lives here rather than in `exec`, which
+//! stays network-free and fast unless `--failover` opts into a local proxy.
 
 use crate::{
-    chain::rpc::check_urls,
+    chain::{
+        ChainDefinition,
+        history::{History, probe_and_rank},
+        rpc::{CHECK_DEADLINE, ProbeResult, probe, probe_ws},
+    },
     config::Chainz,
     key::KeyType,
     prompt::{Prompt, SystemPrompt},
     ui,
+    verifier::{KeyCheck, VerifierKind, check_api_key, registry},
 };
 use anyhow::Result;
 use console::style;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, time::Duration};
+
+mod format;
+
+pub(crate) use format::render;
+
+/// One entry in the check registry.
+pub(crate) struct CheckInfo {
+    /// Stable `<section>.<name>` ID, part of the machine-readable output;
+    /// never rename one.
+    pub(crate) id: &'static str,
+    /// Whether the check goes over the network; offline checks are fast
+    /// enough for a pre-commit hook.
+    pub(crate) network: bool,
+    pub(crate) description: &'static str,
+}
+
+/// Every check doctor runs, in run order. `--only`, `--skip`, and severity
+/// overrides select from this list by ID or by section.
+pub(crate) const CHECKS: [CheckInfo; 7] = [
+    CheckInfo {
+        id: "config.invariants",
+        network: false,
+        description: "Config references and selections are consistent",
+    },
+    CheckInfo {
+        id: "keys.storage",
+        network: false,
+        description: "Keys are kept in safe storage, not plaintext",
+    },
+    CheckInfo {
+        id: "keys.reference",
+        network: false,
+        description: "Every chain's key exists",
+    },
+    CheckInfo {
+        id: "rpc.health",
+        network: true,
+        description: "The selected RPC answers with the right chain ID",
+    },
+    CheckInfo {
+        id: "rpc.ws",
+        network: true,
+        description: "The selected WebSocket completes a subscription handshake",
+    },
+    CheckInfo {
+        id: "verification.url",
+        network: false,
+        description: "A verification API key has a verifier URL to go with it",
+    },
+    CheckInfo {
+        id: "verification.key",
+        network: true,
+        description: "The explorer accepts the verification API key",
+    },
+];
+
+/// Whether `selector` names the check `id` itself or its section.
+fn selects(selector: &str, id: &str) -> bool {
+    id == selector || id.split('.').next() == Some(selector)
+}
+
+/// Reject selectors that match no check, so a typo in `--skip` can't
+/// silently run everything.
+pub(crate) fn validate_selector(selector: &str) -> Result<()> {
+    if CHECKS.iter().any(|check| selects(selector, check.id)) {
+        return Ok(());
+    }
+    let mut sections: Vec<&str> = CHECKS
+        .iter()
+        .filter_map(|check| check.id.split('.').next())
+        .collect();
+    sections.dedup();
+    anyhow::bail!(
+        "Unknown doctor check '{}'; use a section ({}) or a check ID from `chainz doctor --list-checks`",
+        selector,
+        sections.join(", ")
+    )
+}
+
+/// Print the registry for `--list-checks`.
+pub(crate) fn list_checks() {
+    for check in &CHECKS {
+        println!(
+            "{:<20} {:<8} {}",
+            check.id,
+            if check.network { "network" } else { "offline" },
+            check.description
+        );
+    }
+}
+
+/// A configured replacement for a check's non-passing severity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckLevel {
+    Failure,
+    Warning,
+    /// Never run the check
+    Off,
+}
+
+/// Which checks run, against which chains, at which severity.
+#[derive(Default)]
+pub(crate) struct Selection {
+    only: Vec<String>,
+    skip: Vec<String>,
+    /// Canonical chain names; `None` checks every chain in the profile.
+    chains: Option<Vec<String>>,
+    /// Chains must carry every one of these tags.
+    tags: Vec<String>,
+    levels: BTreeMap<String, CheckLevel>,
+}
+
+impl Selection {
+    pub(crate) fn new(
+        chainz: &Chainz,
+        only: Vec<String>,
+        skip: Vec<String>,
+        chains: Vec<String>,
+        tags: Vec<String>,
+    ) -> Result<Self> {
+        for selector in only.iter().chain(&skip) {
+            validate_selector(selector)?;
+        }
+        let chains = if chains.is_empty() {
+            None
+        } else {
+            let mut names = Vec::new();
+            for name_or_id in &chains {
+                let chain = chainz.config.get_chain(name_or_id)?;
+                if let Some((profile, _)) = chainz.config.active_profile()
+                    && !chainz.config.in_profile(chain)
+                {
+                    anyhow::bail!(
+                        "Chain '{}' is not in the active profile '{}'",
+                        chain.name,
+                        profile
+                    );
+                }
+                names.push(chain.name.clone());
+            }
+            Some(names)
+        };
+        Ok(Self {
+            only,
+            skip,
+            chains,
+            tags,
+            levels: chainz.config.doctor.clone(),
+        })
+    }
+
+    /// The configured level for a check: its own entry, else its section's.
+    fn level(&self, id: &str) -> Option<CheckLevel> {
+        self.levels.get(id).copied().or_else(|| {
+            self.levels
+                .iter()
+                .find(|(selector, _)| selects(selector, id))
+                .map(|(_, level)| *level)
+        })
+    }
+
+    fn enabled(&self, id: &str) -> bool {
+        (self.only.is_empty() || self.only.iter().any(|selector| selects(selector, id)))
+            && !self.skip.iter().any(|selector| selects(selector, id))
+            && self.level(id) != Some(CheckLevel::Off)
+    }
+
+    fn includes_chain(&self, chain: &ChainDefinition) -> bool {
+        self.chains
+            .as_ref()
+            .is_none_or(|chains| chains.contains(&chain.name))
+            && chain.has_tags(&self.tags)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Pass,
+    Warning,
+    Failure,
+}
+
+/// One check outcome. Every field is always serialized, null when it does
+/// not apply, so consumers see a regular shape.
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub id: &'static str,
+    pub severity: Severity,
+    pub chain: Option<String>,
+    pub key: Option<String>,
+    /// Redacted, so credential-bearing URLs are safe to ship to monitoring.
+    pub endpoint: Option<String>,
+    pub message: String,
+    /// Short machine-matchable reason, such as "timeout".
+    pub detail: Option<String>,
+    pub latency_ms: Option<u64>,
+    /// The repair `--fix` applied, if any; a fixed failure no longer counts.
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn new(id: &'static str, severity: Severity, message: impl Into<String>) -> Self {
+        debug_assert!(CHECKS.iter().any(|check| check.id == id));
+        Self {
+            id,
+            severity,
+            chain: None,
+            key: None,
+            endpoint: None,
+            message: message.into(),
+            detail: None,
+            latency_ms: None,
+            fix: None,
+        }
+    }
+
+    fn chain(mut self, name: &str) -> Self {
+        self.chain = Some(name.to_string());
+        self
+    }
+
+    fn key(mut self, name: &str) -> Self {
+        self.key = Some(name.to_string());
+        self
+    }
+
+    fn endpoint(mut self, raw_url: &str) -> Self {
+        self.endpoint = Some(crate::endpoint::redact(raw_url));
+        self
+    }
+
+    fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    fn latency(mut self, latency: Duration) -> Self {
+        self.latency_ms = Some(latency.as_millis() as u64);
+        self
+    }
+
+    fn counts_as(&self, severity: Severity) -> bool {
+        self.severity == severity && self.fix.is_none()
+    }
+}
 
 pub struct Report {
-    pub failures: usize,
-    pub warnings: usize,
+    pub checks: Vec<Check>,
+    /// Print sections and lines as checks complete; off for machine formats.
+    human: bool,
+    selection: Selection,
 }
 
-pub async fn run(chainz: &mut Chainz, fix: bool) -> Result<Report> {
-    run_with(&mut SystemPrompt, chainz, fix).await
+impl Report {
+    fn new(human: bool, selection: Selection) -> Self {
+        Self {
+            checks: Vec::new(),
+            human,
+            selection,
+        }
+    }
+
+    fn enabled(&self, id: &str) -> bool {
+        self.selection.enabled(id)
+    }
+
+    /// The profile's chains, narrowed by `--chain` and `--tag`.
+    fn chains(&self, chainz: &Chainz) -> Vec<ChainDefinition> {
+        chainz
+            .profile_chains()
+            .into_iter()
+            .filter(|chain| self.selection.includes_chain(chain))
+            .collect()
+    }
+
+    pub fn failures(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.counts_as(Severity::Failure))
+            .count()
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.counts_as(Severity::Warning))
+            .count()
+    }
+
+    fn section(&self, title: &str) {
+        if self.human {
+            println!("{}", ui::section(title));
+        }
+    }
+
+    /// A human-only line that is not itself a check.
+    fn note(&self, line: &str) {
+        if self.human {
+            println!("  {}", line);
+        }
+    }
+
+    fn push(&mut self, mut check: Check) {
+        if check.severity != Severity::Pass
+            && let Some(level) = self.selection.level(check.id)
+        {
+            check.severity = match level {
+                CheckLevel::Warning => Severity::Warning,
+                CheckLevel::Failure | CheckLevel::Off => Severity::Failure,
+            };
+        }
+        if self.human {
+            let mut line = check.message.clone();
+            if let Some(detail) = &check.detail {
+                line = format!("{}  {}", line, ui::dim(detail));
+            }
+            let line = match check.severity {
+                Severity::Pass => ui::success(&line),
+                Severity::Warning => ui::warn(&line),
+                Severity::Failure => ui::fail(&line),
+            };
+            println!("  {}", line);
+        }
+        self.checks.push(check);
+    }
+
+    /// Record a repair against the matching outstanding check.
+    fn fixed(&mut self, id: &str, matches: impl Fn(&Check) -> bool, fix: String) {
+        if let Some(check) = self
+            .checks
+            .iter_mut()
+            .find(|check| check.id == id && check.severity != Severity::Pass && matches(check))
+        {
+            check.fix = Some(fix);
+        }
+    }
 }
 
-async fn run_with(terminal: &mut impl Prompt, chainz: &mut Chainz, fix: bool) -> Result<Report> {
-    let mut report = Report {
-        failures: 0,
-        warnings: 0,
-    };
+pub(crate) async fn run(
+    chainz: &mut Chainz,
+    fix: bool,
+    human: bool,
+    selection: Selection,
+) -> Result<Report> {
+    run_with(&mut SystemPrompt, chainz, fix, human, selection).await
+}
+
+async fn run_with(
+    terminal: &mut impl Prompt,
+    chainz: &mut Chainz,
+    fix: bool,
+    human: bool,
+    selection: Selection,
+) -> Result<Report> {
+    let mut report = Report::new(human, selection);
 
-    check_config_invariants(chainz, &mut report);
-    let plaintext_keys = check_keys(chainz, &mut report);
-    if fix && plaintext_keys > 0 && terminal.is_interactive() {
+    if report.enabled("config.invariants") {
+        check_config_invariants(chainz, &mut report);
+    }
+    let plaintext_keys = if report.enabled("keys.storage") {
+        check_keys(chainz, &mut report)
+    } else {
+        0
+    };
+    // Machine formats run unattended and keep stdout for the document.
+    if fix && human && plaintext_keys > 0 && terminal.is_interactive() {
         let migrate = terminal.confirm("Migrate plaintext keys to safe storage now?", true)?;
         if migrate {
-            let migrated = crate::key::migrate_plaintext_keys(chainz).await?;
-            report.warnings = report.warnings.saturating_sub(migrated);
+            crate::key::migrate_plaintext_keys(chainz).await?;
+            for (name, key) in chainz.list_keys() {
+                if !matches!(key.kind, KeyType::PrivateKey { .. }) {
+                    report.fixed(
+                        "keys.storage",
+                        |check| check.key.as_deref() == Some(name),
+                        "migrated to safe storage".to_string(),
+                    );
+                }
+            }
         }
     }
-    check_key_references(chainz, &mut report);
-    let failed_chains = check_rpc_health(chainz, &mut report).await;
+    if report.enabled("keys.reference") {
+        check_key_references(chainz, &mut report);
+    }
+    let failed_chains = if report.enabled("rpc.health") || report.enabled("rpc.ws") {
+        check_rpc_health(chainz, &mut report).await
+    } else {
+        vec![]
+    };
+    if report.enabled("verification.url") || report.enabled("verification.key") {
+        check_verification(chainz, &mut report).await;
+    }
 
     if fix && !failed_chains.is_empty() {
         fix_rpcs(chainz, &failed_chains, &mut report).await?;
     }
 
-    println!();
-    match (report.failures, report.warnings) {
-        (0, 0) => println!("{}", ui::success("no issues found")),
-        (f, w) => {
-            println!(
-                "{} {} failure(s), {} warning(s){}",
-                if f > 0 {
-                    style("✗").red().to_string()
-                } else {
-                    style("⚠").yellow().to_string()
-                },
-                f,
-                w,
-                if !failed_chains.is_empty() && !fix {
-                    " — run with --fix to attempt RPC repairs"
-                } else {
-                    ""
-                }
-            );
+    if human {
+        println!();
+        match (report.failures(), report.warnings()) {
+            (0, 0) => println!("{}", ui::success("no issues found")),
+            (f, w) => {
+                println!(
+                    "{} {} failure(s), {} warning(s){}",
+                    if f > 0 {
+                        style("✗").red().to_string()
+                    } else {
+                        style("⚠").yellow().to_string()
+                    },
+                    f,
+                    w,
+                    if !failed_chains.is_empty() && !fix {
+                        " — run with --fix to attempt RPC repairs"
+                    } else {
+                        ""
+                    }
+                );
+            }
         }
     }
     Ok(report)
 }
 
 fn check_config_invariants(chainz: &Chainz, report: &mut Report) {
-    println!("{}", ui::section("Configuration"));
-    match chainz.config.validate() {
-        Ok(()) => println!("  {}", ui::success("configuration invariants hold")),
-        Err(error) => {
-            report.failures += 1;
-            println!("  {}", ui::fail(&format!("{:#}", error)));
-        }
-    }
+    report.section("Configuration");
+    report.push(match chainz.config.validate() {
+        Ok(()) => Check::new(
+            "config.invariants",
+            Severity::Pass,
+            "configuration invariants hold",
+        ),
+        Err(error) => Check::new(
+            "config.invariants",
+            Severity::Failure,
+            format!("{:#}", error),
+        ),
+    });
 }
 
 fn check_keys(chainz: &Chainz, report: &mut Report) -> usize {
-    println!("{}", ui::section("Keys"));
-    let keys = chainz.list_keys();
+    report.section("Keys");
+    let mut keys = chainz.list_keys();
+    // With --chain, only the keys those chains use are of interest.
+    if report.selection.chains.is_some() {
+        let used: Vec<String> = report
+            .chains(chainz)
+            .into_iter()
+            .filter_map(|chain| chain.key_name)
+            .collect();
+        keys.retain(|(name, _)| used.iter().any(|used| used == name));
+    }
     if keys.is_empty() {
-        println!("  no keys configured");
+        report.note("no keys configured");
     }
     let mut plaintext = 0;
     for (name, key) in keys {
         if let KeyType::PrivateKey { .. } = key.kind {
-            report.warnings += 1;
             plaintext += 1;
-            println!(
-                "  {}",
-                ui::warn(&format!(
-                    "'{}' is stored as a plaintext private key — migrate with `chainz key migrate {}`",
-                    name, name
-                ))
+            report.push(
+                Check::new(
+                    "keys.storage",
+                    Severity::Warning,
+                    format!(
+                        "'{}' is stored as a plaintext private key — migrate with `chainz key migrate {}`",
+                        name, name
+                    ),
+                )
+                .key(name)
+                .detail("plaintext"),
             );
         } else {
-            println!("  {}", ui::success(&key.to_string()));
+            report.push(Check::new("keys.storage", Severity::Pass, key.to_string()).key(name));
         }
     }
     plaintext
 }
 
 fn check_key_references(chainz: &Chainz, report: &mut Report) {
-    println!("{}", ui::section("Key references"));
+    report.section("Key references");
     let mut ok = true;
-    for chain in chainz.list_chains() {
+    for chain in report.chains(chainz) {
         let Some(key_name) = chain.key_name.as_deref() else {
             continue;
         };
         if chainz.get_key(key_name).is_err() {
-            report.failures += 1;
             ok = false;
-            println!(
-                "  {}",
-                ui::fail(&format!(
-                    "chain '{}' references missing key '{}'",
-                    chain.name, key_name
-                ))
+            report.push(
+                Check::new(
+                    "keys.reference",
+                    Severity::Failure,
+                    format!(
+                        "chain '{}' references missing key '{}'",
+                        chain.name, key_name
+                    ),
+                )
+                .chain(&chain.name)
+                .key(key_name),
             );
         }
     }
     if ok {
-        println!("  {}", ui::success("all chains reference existing keys"));
+        report.push(Check::new(
+            "keys.reference",
+            Severity::Pass,
+            "all chains reference existing keys",
+        ));
     }
 }
 
 /// Concurrently health-check every chain's selected RPC.
 /// Returns the names of chains whose RPC failed.
 async fn check_rpc_health(chainz: &Chainz, report: &mut Report) -> Vec<String> {
-    println!("{}", ui::section("RPC health"));
-    let chains = chainz.list_chains();
+    report.section("RPC health");
+    let chains = report.chains(chainz);
     if chains.is_empty() {
-        println!("  no chains configured");
+        report.note("no chains configured");
         return vec![];
     }
 
@@ -144,86 +547,197 @@ async fn check_rpc_health(chainz: &Chainz, report: &mut Report) -> Vec<String> {
     // ids can't share one batch; check per chain, but all chains in parallel.
     let checks: Vec<_> = chains
         .iter()
+        .filter(|_| report.enabled("rpc.health"))
         .map(|c| {
-            let expanded = chainz.config.globals.expand_rpc_url(&c.selected_rpc);
+            let expanded = chainz.variables_for(c).expand_rpc_url(&c.selected_rpc);
             let raw = c.selected_rpc.clone();
             let chain_id = c.chain_id;
             let name = c.name.clone();
             tokio::spawn(async move {
-                let (healthy, latency) = crate::chain::rpc::probe(&expanded, chain_id).await;
-                (name, healthy, raw, latency)
+                let (outcome, latency) = probe(&expanded, chain_id).await;
+                (name, chain_id, outcome, raw, latency)
             })
         })
         .collect();
+    // WebSockets are optional; only a selected one is checked, by handshake.
+    let ws_checks: Vec<_> = chains
+        .iter()
+        .filter(|_| report.enabled("rpc.ws"))
+        .filter_map(|c| {
+            let raw = c.selected_ws.clone()?;
+            let expanded = chainz.variables_for(c).expand_rpc_url(&raw);
+            let chain_id = c.chain_id;
+            let name = c.name.clone();
+            Some(tokio::spawn(async move {
+                let (outcome, latency) = probe_ws(&expanded, chain_id).await;
+                (name, outcome, raw, latency)
+            }))
+        })
+        .collect();
 
+    let mut history = History::load().await;
     let mut failed = Vec::new();
     for handle in checks {
-        let Ok((name, is_healthy, raw_url, latency)) = handle.await else {
+        let Ok((name, chain_id, outcome, raw_url, latency)) = handle.await else {
             continue;
         };
-        if is_healthy {
-            println!(
-                "  {}",
-                ui::success(&format!(
-                    "{} ({}) {}ms",
-                    name,
-                    crate::endpoint::redact(&raw_url),
-                    latency.as_millis()
-                ))
-            );
-        } else {
-            report.failures += 1;
-            println!(
-                "  {}",
-                ui::fail(&format!("{} ({})", name, crate::endpoint::redact(&raw_url)))
-            );
-            failed.push(name);
-        }
+        history.record(
+            chain_id,
+            &raw_url,
+            &ProbeResult {
+                index: 0,
+                healthy: outcome.is_ok(),
+                latency,
+                error: outcome.err(),
+            },
+        );
+        let label = format!("{} ({})", name, crate::endpoint::redact(&raw_url));
+        let check = match outcome {
+            Ok(()) => Check::new(
+                "rpc.health",
+                Severity::Pass,
+                format!("{} {}ms", label, latency.as_millis()),
+            ),
+            Err(error) => {
+                failed.push(name.clone());
+                Check::new("rpc.health", Severity::Failure, label).detail(error.as_str())
+            }
+        };
+        report.push(check.chain(&name).endpoint(&raw_url).latency(latency));
+    }
+    history.save().await;
+    for handle in ws_checks {
+        let Ok((name, outcome, raw_url, latency)) = handle.await else {
+            continue;
+        };
+        let label = format!("{} ws ({})", name, crate::endpoint::redact(&raw_url));
+        let check = match outcome {
+            Ok(()) => Check::new(
+                "rpc.ws",
+                Severity::Pass,
+                format!("{} {}ms", label, latency.as_millis()),
+            ),
+            Err(error) => Check::new("rpc.ws", Severity::Failure, label).detail(error.as_str()),
+        };
+        report.push(check.chain(&name).endpoint(&raw_url).latency(latency));
     }
     failed
 }
 
+/// Concurrently make one authenticated explorer API call per chain with
+/// verification settings, so a bad key surfaces before a deploy does.
+async fn check_verification(chainz: &Chainz, report: &mut Report) {
+    report.section("Verification");
+    let client = reqwest::Client::builder()
+        .timeout(CHECK_DEADLINE)
+        .build()
+        .expect("static reqwest configuration is valid");
+    let mut checks = Vec::new();
+    for chain in report.chains(chainz) {
+        let api_key = chain.verification_api_key.clone();
+        let Some(url) = chain.verification_url.clone() else {
+            if api_key.is_some() && report.enabled("verification.url") {
+                report.push(
+                    Check::new(
+                        "verification.url",
+                        Severity::Warning,
+                        format!(
+                            "{}: API key set but no verifier URL — set one with `chainz update {} --verification-url <url>`",
+                            chain.name, chain.name
+                        ),
+                    )
+                    .chain(&chain.name),
+                );
+            }
+            continue;
+        };
+        if !report.enabled("verification.key") {
+            continue;
+        }
+        let kind = registry()
+            .kind_for(chain.chain_id, Some(&url))
+            .unwrap_or(VerifierKind::Etherscan);
+        let client = client.clone();
+        let name = chain.name.clone();
+        checks.push(tokio::spawn(async move {
+            // Sourcify takes no key, so there is nothing to verify.
+            let outcome = match kind {
+                VerifierKind::Sourcify => KeyCheck::Valid,
+                _ => check_api_key(&client, kind, &url, api_key.as_deref()).await,
+            };
+            (name, kind, url, api_key.is_some(), outcome)
+        }));
+    }
+    if checks.is_empty() {
+        report.note("no verifiers configured");
+        return;
+    }
+
+    for handle in checks {
+        let Ok((name, kind, url, has_key, outcome)) = handle.await else {
+            continue;
+        };
+        let label = format!("{} {} ({})", name, kind, crate::endpoint::redact(&url));
+        let (severity, detail) = match outcome {
+            KeyCheck::Valid if has_key || kind != VerifierKind::Etherscan => (Severity::Pass, None),
+            // Some explorers still answer keyless calls at a low rate, but
+            // verification itself will be refused.
+            KeyCheck::Valid => (Severity::Warning, Some("no API key".to_string())),
+            KeyCheck::RateLimited => (Severity::Warning, Some("rate limited".to_string())),
+            KeyCheck::Rejected(reason) if !has_key => {
+                (Severity::Failure, Some(format!("no API key ({})", reason)))
+            }
+            KeyCheck::Rejected(reason) => {
+                (Severity::Failure, Some(format!("key rejected: {}", reason)))
+            }
+            KeyCheck::Unreachable(reason) => (Severity::Failure, Some(reason.to_string())),
+            KeyCheck::BadResponse => (Severity::Failure, Some("unexpected response".to_string())),
+        };
+        let mut check = Check::new("verification.key", severity, label)
+            .chain(&name)
+            .endpoint(&url);
+        check.detail = detail;
+        report.push(check);
+    }
+}
+
 async fn fix_rpcs(chainz: &mut Chainz, failed: &[String], report: &mut Report) -> Result<()> {
-    println!("{}", ui::section("Fixing RPCs"));
+    report.section("Fixing RPCs");
     let mut fixed_any = false;
     for name in failed {
         let chain = chainz.config.get_chain(name)?.clone();
         // Probe all alternatives concurrently (chainlist chains can carry
         // dozens of RPCs; sequential 10s timeouts would stall for minutes),
-        // then prefer the first healthy one in configured order.
-        let candidates: Vec<&String> = chain
+        // then prefer the best healthy one by this probe and past history.
+        let candidates: Vec<String> = chain
             .rpc_urls
             .iter()
             .filter(|url| **url != chain.selected_rpc)
+            .cloned()
             .collect();
         let expanded: Vec<String> = candidates
             .iter()
-            .map(|url| chainz.config.globals.expand_rpc_url(url))
+            .map(|url| chainz.variables_for(&chain).expand_rpc_url(url))
             .collect();
-        let health = check_urls(&expanded, chain.chain_id).await;
+        let (results, order) = probe_and_rank(chain.chain_id, &candidates, &expanded).await;
 
-        match health.iter().position(|h| *h) {
-            Some(i) => {
+        match order.first().filter(|&&i| results[i].healthy) {
+            Some(&i) => {
                 chainz.set_selected_rpc(name, candidates[i].clone())?;
-                println!(
-                    "  {}",
-                    ui::success(&format!(
-                        "{}: switched to {}",
-                        name,
-                        crate::endpoint::redact(candidates[i])
-                    ))
+                let fix = format!("switched to {}", crate::endpoint::redact(&candidates[i]));
+                report.note(&ui::success(&format!("{}: {}", name, fix)));
+                report.fixed(
+                    "rpc.health",
+                    |check| check.chain.as_deref() == Some(name),
+                    fix,
                 );
-                report.failures = report.failures.saturating_sub(1);
                 fixed_any = true;
             }
-            None => println!(
-                "  {}",
-                ui::fail(&format!(
-                    "{}: no healthy alternative among {} configured RPC(s)",
-                    name,
-                    chain.rpc_urls.len()
-                ))
-            ),
+            None => report.note(&ui::fail(&format!(
+                "{}: no healthy alternative among {} configured RPC(s)",
+                name,
+                chain.rpc_urls.len()
+            ))),
         }
     }
     if fixed_any {
@@ -236,6 +750,7 @@ async fn fix_rpcs(chainz: &mut Chainz, failed: &[String], report: &mut Report) -
 mod tests {
     use super::*;
     use crate::{
+        chain::ChainDefinition,
         key::{Key, KeyType},
         prompt::testing::{Answer, ScriptedPrompt},
     };
@@ -255,10 +770,94 @@ mod tests {
         )?;
         let mut terminal = ScriptedPrompt::new([Answer::Confirm(false)]);
 
-        let report = run_with(&mut terminal, &mut chainz, true).await?;
+        let report = run_with(&mut terminal, &mut chainz, true, true, Selection::default()).await?;
+
+        assert_eq!(report.failures(), 0);
+        assert_eq!(report.warnings(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn selection_matches_sections_and_ids_and_applies_levels() {
+        let selection = Selection {
+            only: vec!["keys".into(), "rpc.ws".into()],
+            skip: vec!["keys.storage".into()],
+            chains: None,
+            tags: vec![],
+            levels: BTreeMap::from([
+                ("keys".into(), CheckLevel::Warning),
+                ("keys.reference".into(), CheckLevel::Failure),
+                ("rpc".into(), CheckLevel::Off),
+            ]),
+        };
+        assert!(selection.enabled("keys.reference"));
+        assert!(!selection.enabled("keys.storage"), "skip wins over only");
+        assert!(!selection.enabled("config.invariants"));
+        assert!(!selection.enabled("rpc.ws"), "off disables the check");
+        assert_eq!(
+            selection.level("keys.reference"),
+            Some(CheckLevel::Failure),
+            "a check's own entry wins over its section's"
+        );
+        assert_eq!(selection.level("keys.storage"), Some(CheckLevel::Warning));
+        assert!(validate_selector("verification").is_ok());
+        assert!(validate_selector("rpc.health").is_ok());
+        assert!(validate_selector("rp").is_err());
+    }
 
-        assert_eq!(report.failures, 0);
-        assert_eq!(report.warnings, 1);
+    #[test]
+    fn level_overrides_replace_only_non_passing_severities() {
+        let selection = Selection {
+            levels: BTreeMap::from([("config".into(), CheckLevel::Warning)]),
+            ..Default::default()
+        };
+        let mut report = Report::new(false, selection);
+        report.push(Check::new("config.invariants", Severity::Failure, "broken"));
+        report.push(Check::new("config.invariants", Severity::Pass, "fine"));
+        assert_eq!(report.checks[0].severity, Severity::Warning);
+        assert_eq!(report.checks[1].severity, Severity::Pass);
+        assert_eq!(report.failures(), 0);
+    }
+
+    #[tokio::test]
+    async fn verification_section_reports_rejected_throttled_and_missing_keys() -> Result<()> {
+        let url = crate::verifier::testing::mock_explorer().await;
+        let mut chainz = Chainz::new();
+        for (name, chain_id, key) in [
+            ("good", 1, Some("good")),
+            ("bad", 2, Some("bad")),
+            ("busy", 3, Some("busy")),
+            ("keyless", 4, None),
+        ] {
+            chainz.add_chain(ChainDefinition {
+                name: name.into(),
+                aliases: vec![],
+                tags: vec![],
+                chain_id,
+                rpc_urls: vec!["http://127.0.0.1:1".into()],
+                selected_rpc: "http://127.0.0.1:1".into(),
+                ws_urls: vec![],
+                selected_ws: None,
+                verification_api_key: key.map(str::to_string),
+                verification_url: Some(url.clone()),
+                key_name: None,
+                env: Default::default(),
+                metadata: Default::default(),
+            })?;
+        }
+        let mut report = Report::new(false, Selection::default());
+
+        check_verification(&chainz, &mut report).await;
+
+        assert_eq!(report.failures(), 2, "bad and keyless keys fail");
+        assert_eq!(report.warnings(), 1, "rate limiting only warns");
+        let bad = report
+            .checks
+            .iter()
+            .find(|check| check.chain.as_deref() == Some("bad"))
+            .unwrap();
+        assert_eq!(bad.id, "verification.key");
+        assert_eq!(bad.detail.as_deref(), Some("key rejected: Invalid API Key"));
         Ok(())
     }
 }
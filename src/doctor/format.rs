@@ -0,0 +1,250 @@
+//! Machine-readable doctor reports: JSON for scripts and monitoring, SARIF
+//! for code-scanning dashboards, and JUnit XML for CI test views.
+
+use super::{CHECKS, Check, Report, Severity};
+use crate::opt::DoctorFormat;
+use anyhow::Result;
+use serde_json::json;
+use std::fmt::Write;
+
+pub(crate) fn render(report: &Report, format: DoctorFormat) -> Result<String> {
+    let document = match format {
+        DoctorFormat::Text => unreachable!("text is printed while checks run"),
+        DoctorFormat::Json => json!({
+            "failures": report.failures(),
+            "warnings": report.warnings(),
+            "checks": report.checks,
+        }),
+        DoctorFormat::Sarif => sarif(report),
+        DoctorFormat::Junit => return Ok(junit(report)),
+    };
+    Ok(serde_json::to_string_pretty(&document)? + "\n")
+}
+
+/// SARIF 2.1.0 with one rule per check ID. Passing and repaired checks are
+/// kept as `pass` results so dashboards can tell "fixed" from "not run".
+fn sarif(report: &Report) -> serde_json::Value {
+    let rules: Vec<_> = CHECKS
+        .iter()
+        .map(|check| {
+            json!({
+                "id": check.id,
+                "shortDescription": {"text": check.description},
+                "properties": {"network": check.network},
+            })
+        })
+        .collect();
+    let results: Vec<_> = report
+        .checks
+        .iter()
+        .map(|check| {
+            let (kind, level) = match check.severity {
+                _ if check.fix.is_some() => ("pass", "none"),
+                Severity::Pass => ("pass", "none"),
+                Severity::Warning => ("fail", "warning"),
+                Severity::Failure => ("fail", "error"),
+            };
+            json!({
+                "ruleId": check.id,
+                "kind": kind,
+                "level": level,
+                "message": {"text": text(check)},
+                "properties": {
+                    "chain": check.chain,
+                    "key": check.key,
+                    "endpoint": check.endpoint,
+                    "latency_ms": check.latency_ms,
+                    "fix": check.fix,
+                },
+            })
+        })
+        .collect();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "chainz",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+/// One testcase per check, classed by check ID. JUnit has no warning state,
+/// so warnings pass with the reason on `system-out`.
+fn junit(report: &Report) -> String {
+    let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let tests = report.checks.len();
+    let failures = report.failures();
+    writeln!(
+        output,
+        "<testsuites name=\"chainz doctor\" tests=\"{tests}\" failures=\"{failures}\">\n  \
+         <testsuite name=\"chainz doctor\" tests=\"{tests}\" failures=\"{failures}\">"
+    )
+    .expect("writing to a String cannot fail");
+    for check in &report.checks {
+        let name = check
+            .chain
+            .as_deref()
+            .or(check.key.as_deref())
+            .unwrap_or(check.id);
+        write!(
+            output,
+            "    <testcase classname=\"{}\" name=\"{}\"",
+            xml_escape(check.id),
+            xml_escape(name)
+        )
+        .expect("writing to a String cannot fail");
+        if let Some(latency) = check.latency_ms {
+            write!(output, " time=\"{:.3}\"", latency as f64 / 1000.0)
+                .expect("writing to a String cannot fail");
+        }
+        let body = match check.severity {
+            _ if check.fix.is_some() => format!("fixed: {}", text(check)),
+            Severity::Pass => {
+                output.push_str("/>\n");
+                continue;
+            }
+            Severity::Warning => format!("warning: {}", text(check)),
+            Severity::Failure => {
+                let message = xml_escape(&text(check));
+                writeln!(
+                    output,
+                    ">\n      <failure message=\"{message}\">{message}</failure>\n    </testcase>"
+                )
+                .expect("writing to a String cannot fail");
+                continue;
+            }
+        };
+        writeln!(
+            output,
+            ">\n      <system-out>{}</system-out>\n    </testcase>",
+            xml_escape(&body)
+        )
+        .expect("writing to a String cannot fail");
+    }
+    output.push_str("  </testsuite>\n</testsuites>\n");
+    output
+}
+
+/// The human line without styling, with the detail and any fix appended.
+fn text(check: &Check) -> String {
+    let mut text = check.message.clone();
+    if let Some(detail) = &check.detail {
+        write!(text, " ({})", detail).expect("writing to a String cannot fail");
+    }
+    if let Some(fix) = &check.fix {
+        write!(text, "; fixed: {}", fix).expect("writing to a String cannot fail");
+    }
+    text
+}
+
+fn xml_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn report() -> Report {
+        let mut report = Report::new(false, Default::default());
+        report.push(
+            Check::new(
+                "rpc.health",
+                Severity::Failure,
+                "base (https://rpc.example)",
+            )
+            .chain("base")
+            .endpoint("https://rpc.example")
+            .detail("timeout")
+            .latency(Duration::from_millis(4000)),
+        );
+        report.push(
+            Check::new("keys.storage", Severity::Warning, "'dev' <plaintext>")
+                .key("dev")
+                .detail("plaintext"),
+        );
+        report.push(Check::new(
+            "config.invariants",
+            Severity::Pass,
+            "configuration invariants hold",
+        ));
+        report
+    }
+
+    #[test]
+    fn json_lists_every_check_with_a_regular_shape() -> Result<()> {
+        let parsed: serde_json::Value =
+            serde_json::from_str(&render(&report(), DoctorFormat::Json)?)?;
+        assert_eq!(parsed["failures"], 1);
+        assert_eq!(parsed["warnings"], 1);
+        let checks = parsed["checks"].as_array().unwrap();
+        assert_eq!(checks.len(), 3);
+        assert_eq!(checks[0]["id"], "rpc.health");
+        assert_eq!(checks[0]["severity"], "failure");
+        assert_eq!(checks[0]["latency_ms"], 4000);
+        assert_eq!(checks[0]["detail"], "timeout");
+        assert!(checks[2]["chain"].is_null(), "absent fields are null");
+        assert!(checks[2]["fix"].is_null());
+        Ok(())
+    }
+
+    #[test]
+    fn sarif_maps_severities_and_counts_fixes_as_passes() -> Result<()> {
+        let mut report = report();
+        report.fixed(
+            "rpc.health",
+            |check| check.chain.as_deref() == Some("base"),
+            "switched to https://backup.example".into(),
+        );
+        assert_eq!(report.failures(), 0);
+        let parsed: serde_json::Value =
+            serde_json::from_str(&render(&report, DoctorFormat::Sarif)?)?;
+        let run = &parsed["runs"][0];
+        assert_eq!(
+            run["tool"]["driver"]["rules"].as_array().unwrap().len(),
+            CHECKS.len()
+        );
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results[0]["kind"], "pass");
+        assert_eq!(
+            results[0]["properties"]["fix"],
+            "switched to https://backup.example"
+        );
+        assert_eq!(results[1]["level"], "warning");
+        assert_eq!(results[2]["kind"], "pass");
+        Ok(())
+    }
+
+    #[test]
+    fn junit_escapes_text_and_reports_failures() -> Result<()> {
+        let output = render(&report(), DoctorFormat::Junit)?;
+        assert!(output.contains(r#"<testsuite name="chainz doctor" tests="3" failures="1">"#));
+        assert!(output.contains(r#"<testcase classname="rpc.health" name="base" time="4.000">"#));
+        assert!(output.contains(r#"<failure message="base (https://rpc.example) (timeout)">"#));
+        assert!(output.contains("warning: &apos;dev&apos; &lt;plaintext&gt; (plaintext)"));
+        assert!(
+            output
+                .contains(r#"<testcase classname="config.invariants" name="config.invariants"/>"#)
+        );
+        Ok(())
+    }
+}
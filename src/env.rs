@@ -0,0 +1,235 @@
+//! `chainz env`: write a chain's environment to disk-friendly formats.
+//!
+//! `exec` and `shell` hand `ChainVariables` straight to a child process; CI
+//! jobs and docker-compose need the same variables as text instead. Only the
+//! foundry.toml format spans every chain, since Foundry selects one itself.
+
+use crate::{chain::ChainDefinition, opt::EnvFormat};
+use anyhow::Result;
+use std::{collections::HashMap, fmt::Write};
+
+/// Heredoc delimiter for multi-line values in `$GITHUB_ENV`.
+const GITHUB_DELIMITER: &str = "CHAINZ_EOF";
+
+/// Render one chain's variables, sorted by name for stable diffs.
+pub(crate) fn render(vars: &HashMap<String, String>, format: EnvFormat) -> Result<String> {
+    let mut entries: Vec<_> = vars.iter().collect();
+    entries.sort_by_key(|(name, _)| *name);
+    if format == EnvFormat::Json {
+        let object: serde_json::Map<_, _> = entries
+            .into_iter()
+            .map(|(name, value)| (name.clone(), value.clone().into()))
+            .collect();
+        return Ok(serde_json::to_string_pretty(&object)? + "\n");
+    }
+
+    let mut output = String::new();
+    for (name, value) in entries {
+        match format {
+            EnvFormat::Dotenv => writeln!(output, "{}={}", name, dotenv_quote(value)),
+            EnvFormat::Sh => writeln!(output, "export {}={}", name, sh_quote(value)),
+            EnvFormat::Fish => writeln!(output, "set -gx {} {}", name, fish_quote(value)),
+            EnvFormat::GithubActions if value.contains('\n') => {
+                if value.lines().any(|line| line == GITHUB_DELIMITER) {
+                    anyhow::bail!("Value of {} contains the heredoc delimiter", name);
+                }
+                writeln!(
+                    output,
+                    "{}<<{}\n{}\n{}",
+                    name, GITHUB_DELIMITER, value, GITHUB_DELIMITER
+                )
+            }
+            EnvFormat::GithubActions => writeln!(output, "{}={}", name, value),
+            EnvFormat::Json | EnvFormat::FoundryToml => {
+                unreachable!("rendered as a whole document")
+            }
+        }
+        .expect("writing to a String cannot fail");
+    }
+    Ok(output)
+}
+
+/// `[rpc_endpoints]` for every chain and `[etherscan]` for each chain with a
+/// verification key, ready to paste into foundry.toml. `rpc_url` expands a
+/// chain's selected RPC.
+pub(crate) fn foundry_toml(
+    chains: &[ChainDefinition],
+    rpc_url: impl Fn(&ChainDefinition) -> String,
+) -> String {
+    let mut output = String::from("[rpc_endpoints]\n");
+    for chain in chains {
+        writeln!(
+            output,
+            "{} = {}",
+            toml_key(&chain.name),
+            toml_string(&rpc_url(chain))
+        )
+        .expect("writing to a String cannot fail");
+    }
+
+    let verified: Vec<_> = chains
+        .iter()
+        .filter_map(|chain| Some((chain, chain.verification_api_key.as_deref()?)))
+        .collect();
+    if !verified.is_empty() {
+        output.push_str("\n[etherscan]\n");
+    }
+    for (chain, api_key) in verified {
+        write!(
+            output,
+            "{} = {{ key = {}, chain = {}",
+            toml_key(&chain.name),
+            toml_string(api_key),
+            chain.chain_id
+        )
+        .expect("writing to a String cannot fail");
+        if let Some(url) = &chain.verification_url {
+            write!(output, ", url = {}", toml_string(url))
+                .expect("writing to a String cannot fail");
+        }
+        output.push_str(" }\n");
+    }
+    output
+}
+
+/// Single quotes are literal in both dotenv and docker-compose env files;
+/// fall back to an escaped double-quoted value when that is not enough.
+/// Loaders interpolate `$VAR` inside double quotes, so `$` is escaped too.
+fn dotenv_quote(value: &str) -> String {
+    if !value.contains(['\'', '\n']) {
+        return format!("'{}'", value);
+    }
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('$', "\\$")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+fn sh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+fn fish_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', r"\\").replace('\'', r"\'"))
+}
+
+fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if bare {
+        key.to_string()
+    } else {
+        toml_string(key)
+    }
+}
+
+fn toml_string(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => {
+                write!(quoted, "\\u{:04X}", c as u32).expect("writing to a String cannot fail")
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variables::GlobalVariables;
+
+    fn vars() -> HashMap<String, String> {
+        HashMap::from([
+            ("CHAIN_ID".to_string(), "8453".to_string()),
+            (
+                "ETH_RPC_URL".to_string(),
+                "https://rpc.example/it's".to_string(),
+            ),
+        ])
+    }
+
+    #[test]
+    fn shell_formats_quote_values_literally() -> Result<()> {
+        assert_eq!(
+            render(&vars(), EnvFormat::Sh)?,
+            "export CHAIN_ID='8453'\nexport ETH_RPC_URL='https://rpc.example/it'\\''s'\n"
+        );
+        assert_eq!(
+            render(&vars(), EnvFormat::Fish)?,
+            "set -gx CHAIN_ID '8453'\nset -gx ETH_RPC_URL 'https://rpc.example/it\\'s'\n"
+        );
+        assert_eq!(
+            render(&vars(), EnvFormat::Dotenv)?,
+            "CHAIN_ID='8453'\nETH_RPC_URL=\"https://rpc.example/it's\"\n"
+        );
+        let dollar = HashMap::from([("PASS".to_string(), "it's $HOME".to_string())]);
+        assert_eq!(
+            render(&dollar, EnvFormat::Dotenv)?,
+            "PASS=\"it's \\$HOME\"\n"
+        );
+        let json: serde_json::Value = serde_json::from_str(&render(&vars(), EnvFormat::Json)?)?;
+        assert_eq!(json["CHAIN_ID"], "8453");
+        Ok(())
+    }
+
+    #[test]
+    fn github_actions_uses_heredocs_for_multiline_values() -> Result<()> {
+        let vars = HashMap::from([("NOTE".to_string(), "a\nb".to_string())]);
+        assert_eq!(
+            render(&vars, EnvFormat::GithubActions)?,
+            "NOTE<<CHAINZ_EOF\na\nb\nCHAINZ_EOF\n"
+        );
+        let hostile = HashMap::from([("NOTE".to_string(), "a\nCHAINZ_EOF\nX=1".to_string())]);
+        assert!(render(&hostile, EnvFormat::GithubActions).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn foundry_toml_lists_endpoints_and_verifiers() {
+        let mut globals = GlobalVariables::default();
+        globals.add_rpc_expansion("KEY", "secret");
+        let chain = |name: &str, id, api_key: Option<&str>| ChainDefinition {
+            name: name.to_string(),
+            aliases: vec![],
+            tags: vec![],
+            chain_id: id,
+            rpc_urls: vec!["https://rpc.example/${KEY}".to_string()],
+            selected_rpc: "https://rpc.example/${KEY}".to_string(),
+            ws_urls: vec![],
+            selected_ws: None,
+            verification_api_key: api_key.map(str::to_string),
+            verification_url: api_key.map(|_| "https://api.example/api".to_string()),
+            key_name: None,
+            env: Default::default(),
+            metadata: Default::default(),
+        };
+        let output = foundry_toml(
+            &[
+                chain("base", 8453, Some("abc")),
+                chain("OP Mainnet", 10, None),
+            ],
+            |chain| globals.expand_rpc_url(&chain.selected_rpc),
+        );
+        assert_eq!(
+            output,
+            "[rpc_endpoints]\n\
+             base = \"https://rpc.example/secret\"\n\
+             \"OP Mainnet\" = \"https://rpc.example/secret\"\n\
+             \n\
+             [etherscan]\n\
+             base = { key = \"abc\", chain = 8453, url = \"https://api.example/api\" }\n"
+        );
+    }
+}
@@ -0,0 +1,223 @@
+//! `chainz exec --chains/--all`: run one command once per chain.
+//!
+//! Every chain's variables are prepared up front, so any password prompts
+//! happen before the first child starts and each distinct key is unlocked
+//! once. Children then run with bounded parallelism; their output lines are
+//! prefixed with the chain name, and a per-chain exit summary follows.
+
+use crate::{
+    chain::ChainInstance,
+    key::{Key, KeyType},
+    ui,
+    variables::{ChainVariables, requested_tokens},
+};
+use anyhow::Result;
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    process::{Command, ExitStatus, Stdio},
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+/// A fully expanded command for one chain.
+pub(crate) struct Target {
+    chain_name: String,
+    command: Vec<String>,
+    env: HashMap<String, String>,
+}
+
+pub(crate) struct Outcome {
+    chain_name: String,
+    status: std::io::Result<ExitStatus>,
+}
+
+impl Outcome {
+    fn success(&self) -> bool {
+        self.status.as_ref().is_ok_and(ExitStatus::success)
+    }
+}
+
+/// Expand `command` for every chain, resolving each distinct key at most
+/// once.
+pub(crate) fn prepare(
+    chains: Vec<ChainInstance>,
+    command: &[String],
+    expose_key: bool,
+) -> Result<Vec<Target>> {
+    let requested = requested_tokens(command);
+    let needs_secret = expose_key || requested.contains(&"key");
+    let needs_wallet = requested.contains(&"wallet");
+    let mut unlocked: HashMap<String, Key> = HashMap::new();
+    let mut targets = Vec::with_capacity(chains.len());
+    for mut chain in chains {
+        if let Some(key) = &chain.key
+            && (needs_secret || (needs_wallet && key.address_noninteractive().is_none()))
+        {
+            if !unlocked.contains_key(&key.name) {
+                let resolved = Key::new(
+                    key.name.clone(),
+                    KeyType::PrivateKey {
+                        value: key.private_key()?.to_string(),
+                    },
+                );
+                unlocked.insert(key.name.clone(), resolved);
+            }
+            let resolved = unlocked[&key.name].clone();
+            chain = chain.with_key(resolved);
+        }
+        let variables = ChainVariables::new(&chain, command, expose_key)?;
+        targets.push(Target {
+            chain_name: chain.definition.name.clone(),
+            command: variables.expand(command.to_vec())?,
+            env: variables.as_map().clone(),
+        });
+    }
+    Ok(targets)
+}
+
+/// Run every target, at most `jobs` at a time, returning outcomes in
+/// target order.
+pub(crate) fn run(targets: &[Target], jobs: usize) -> Vec<Outcome> {
+    let width = targets
+        .iter()
+        .map(|target| target.chain_name.len())
+        .max()
+        .unwrap_or(0);
+    let next = AtomicUsize::new(0);
+    let outcomes: Mutex<Vec<Option<Outcome>>> = Mutex::new(targets.iter().map(|_| None).collect());
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, targets.len().max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(target) = targets.get(index) else {
+                        break;
+                    };
+                    let outcome = run_one(target, width);
+                    outcomes.lock().expect("outcome lock poisoned")[index] = Some(outcome);
+                }
+            });
+        }
+    });
+    outcomes
+        .into_inner()
+        .expect("outcome lock poisoned")
+        .into_iter()
+        .map(|outcome| outcome.expect("every target runs"))
+        .collect()
+}
+
+fn run_one(target: &Target, width: usize) -> Outcome {
+    let prefix = ui::dim(&format!("{:<width$} │ ", target.chain_name));
+    let child = Command::new(&target.command[0])
+        .args(&target.command[1..])
+        .envs(&target.env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+    let status = child.and_then(|mut child| {
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let stderr = child.stderr.take().expect("stderr is piped");
+        std::thread::scope(|scope| {
+            scope.spawn(|| forward(stdout, std::io::stdout(), &prefix));
+            scope.spawn(|| forward(stderr, std::io::stderr(), &prefix));
+        });
+        child.wait()
+    });
+    Outcome {
+        chain_name: target.chain_name.clone(),
+        status,
+    }
+}
+
+/// Copy `source` to `sink` line by line, prefixing each line. Each line is
+/// one `write_all`, which std's stdout and stderr perform under a single
+/// lock, so concurrent chains never interleave within a line.
+fn forward(source: impl Read, mut sink: impl Write, prefix: &str) {
+    let mut reader = BufReader::new(source);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                if !line.ends_with(b"\n") {
+                    line.push(b'\n');
+                }
+                let mut framed = prefix.as_bytes().to_vec();
+                framed.extend_from_slice(&line);
+                if sink.write_all(&framed).and_then(|()| sink.flush()).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Print the per-chain summary to stderr; returns whether every chain
+/// succeeded.
+pub(crate) fn summarize(outcomes: &[Outcome]) -> bool {
+    eprintln!();
+    for outcome in outcomes {
+        let line = match &outcome.status {
+            Ok(status) => match status.code() {
+                Some(code) => format!("{}: exit {}", outcome.chain_name, code),
+                None => format!("{}: terminated by signal", outcome.chain_name),
+            },
+            Err(error) => format!("{}: failed to start ({})", outcome.chain_name, error),
+        };
+        if outcome.success() {
+            eprintln!("{}", ui::success(&line));
+        } else {
+            eprintln!("{}", ui::fail(&line));
+        }
+    }
+    let failed = outcomes.iter().filter(|outcome| !outcome.success()).count();
+    eprintln!(
+        "{} of {} chain(s) succeeded",
+        outcomes.len() - failed,
+        outcomes.len()
+    );
+    failed == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(name: &str, script: &str) -> Target {
+        Target {
+            chain_name: name.to_string(),
+            command: vec!["sh".to_string(), "-c".to_string(), script.to_string()],
+            env: HashMap::new(),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn outcomes_keep_target_order_under_parallelism() {
+        let targets = [
+            target("slow", "sleep 0.2; exit 3"),
+            target("fast", "exit 0"),
+            target("missing", "exec /nonexistent/chainz-test"),
+        ];
+        let outcomes = run(&targets, 3);
+        let names: Vec<_> = outcomes.iter().map(|o| o.chain_name.as_str()).collect();
+        assert_eq!(names, ["slow", "fast", "missing"]);
+        assert_eq!(outcomes[0].status.as_ref().unwrap().code(), Some(3));
+        assert!(outcomes[1].success());
+        assert!(!outcomes[2].success());
+        assert!(!summarize(&outcomes));
+    }
+
+    #[test]
+    fn forwarded_lines_are_prefixed_and_terminated() {
+        let mut sink = Vec::new();
+        forward(&b"one\ntwo"[..], &mut sink, "[base] ");
+        assert_eq!(sink, b"[base] one\n[base] two\n");
+    }
+}
@@ -0,0 +1,281 @@
+//! `chainz exec --failover`: a keyless local JSON-RPC proxy over every RPC
+//! configured for a chain.
+//!
+//! Upstreams are tried in the order ranked from a fresh probe and the
+//! recorded RPC history. When the active upstream fails at the transport
+//! level, idempotent reads are retried on the next one; writes are never
+//! replayed, since the failed attempt may still have been delivered. Every
+//! switch is reported on stderr, with redacted URLs.
+
+use crate::{
+    chain::{ChainDefinition, history::probe_and_rank},
+    endpoint,
+    serve::{RpcHandler, error, new_token, serve_http, url},
+    ui,
+    variables::GlobalVariables,
+};
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+use std::{
+    net::SocketAddr,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+use tokio::net::TcpListener;
+
+/// Per-attempt deadline; long enough for heavy `eth_call`s and traces.
+const UPSTREAM_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Method prefixes with side effects; a failed attempt is not retried
+/// elsewhere. Prefixes rather than names, so new write methods
+/// (`eth_sendRawTransactionSync`, `eth_sendUserOperation`, ...) are covered.
+const NON_IDEMPOTENT_PREFIXES: [&str; 3] = ["eth_send", "personal_send", "mev_send"];
+
+struct Upstream {
+    url: String,
+    /// Redacted raw URL, safe to print.
+    label: String,
+}
+
+pub(crate) struct Failover {
+    chain_name: String,
+    upstreams: Vec<Upstream>,
+    active: AtomicUsize,
+    client: reqwest::Client,
+}
+
+impl Failover {
+    /// Probe every configured RPC and order them best first.
+    pub(crate) async fn ranked(chain: &ChainDefinition, globals: &GlobalVariables) -> Self {
+        let expanded: Vec<String> = chain
+            .rpc_urls
+            .iter()
+            .map(|url| globals.expand_rpc_url(url))
+            .collect();
+        let (_, order) = probe_and_rank(chain.chain_id, &chain.rpc_urls, &expanded).await;
+        let upstreams = order
+            .into_iter()
+            .map(|index| Upstream {
+                url: expanded[index].clone(),
+                label: endpoint::redact(&chain.rpc_urls[index]),
+            })
+            .collect();
+        Self::new(&chain.name, upstreams)
+    }
+
+    fn new(chain_name: &str, upstreams: Vec<Upstream>) -> Self {
+        Self {
+            chain_name: chain_name.to_string(),
+            upstreams,
+            active: AtomicUsize::new(0),
+            client: reqwest::Client::builder()
+                .timeout(UPSTREAM_TIMEOUT)
+                .build()
+                .expect("static reqwest configuration is valid"),
+        }
+    }
+
+    /// Bind on an ephemeral localhost port and serve in the background.
+    /// Returns the URL to hand to the child process.
+    pub(crate) async fn spawn(self) -> Result<String> {
+        if self.upstreams.is_empty() {
+            anyhow::bail!("Chain '{}' has no RPC URLs", self.chain_name);
+        }
+        let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0))).await?;
+        let token = new_token();
+        let url = url(&listener, &token)?;
+        eprintln!(
+            "{}",
+            ui::dim(&format!(
+                "failover: {} via {}",
+                self.chain_name, self.upstreams[0].label
+            ))
+        );
+        tokio::spawn(serve_http(Arc::new(self), listener, token));
+        Ok(url)
+    }
+
+    async fn post(&self, upstream: &Upstream, body: &[u8]) -> Result<Value> {
+        // Upstream errors can echo the URL, which may embed API keys.
+        let response = self
+            .client
+            .post(&upstream.url)
+            .header("Content-Type", "application/json")
+            .body(body.to_vec())
+            .send()
+            .await
+            .map_err(|err| {
+                anyhow!(if err.is_timeout() {
+                    "request timed out"
+                } else {
+                    "request failed"
+                })
+            })?;
+        let status = response.status();
+        if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            anyhow::bail!("HTTP {}", status.as_u16());
+        }
+        response
+            .json()
+            .await
+            .map_err(|_| anyhow!("invalid JSON response"))
+    }
+
+    /// Move off `from` unless another request already did.
+    fn switch(&self, from: usize, reason: &anyhow::Error) -> usize {
+        let to = (from + 1) % self.upstreams.len();
+        if self
+            .active
+            .compare_exchange(from, to, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+        {
+            eprintln!(
+                "{}",
+                ui::warn(&format!(
+                    "failover: {} {} ({}); switching to {}",
+                    self.chain_name, self.upstreams[from].label, reason, self.upstreams[to].label
+                ))
+            );
+        }
+        to
+    }
+}
+
+impl RpcHandler for Failover {
+    async fn handle_body(&self, body: &[u8]) -> Value {
+        let Ok(request) = serde_json::from_slice::<Value>(body) else {
+            return error(Value::Null, -32700, "Parse error");
+        };
+        let retryable = is_idempotent(&request);
+        let mut current = self.active.load(Ordering::SeqCst);
+        let mut last_error = anyhow!("no upstream attempted");
+        for _ in 0..self.upstreams.len() {
+            match self.post(&self.upstreams[current], body).await {
+                Ok(response) => return response,
+                Err(err) => {
+                    current = self.switch(current, &err);
+                    last_error = err;
+                    if !retryable {
+                        break;
+                    }
+                }
+            }
+        }
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let message = if retryable {
+            format!("All upstream RPCs failed; last error: {}", last_error)
+        } else {
+            format!(
+                "Upstream RPC failed ({}); not retried because the request has side effects",
+                last_error
+            )
+        };
+        error(id, -32603, &message)
+    }
+}
+
+/// Whether a call or batch can safely be replayed on another upstream.
+fn is_idempotent(request: &Value) -> bool {
+    let calls = match request {
+        Value::Array(calls) => calls.as_slice(),
+        call => std::slice::from_ref(call),
+    };
+    calls.iter().all(|call| {
+        call.get("method")
+            .and_then(Value::as_str)
+            .is_some_and(|method| {
+                !NON_IDEMPOTENT_PREFIXES
+                    .iter()
+                    .any(|prefix| method.starts_with(prefix))
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Answer every call with `eth_blockNumber`-style results.
+    async fn healthy_upstream() -> String {
+        crate::serve::testing::mock_http(
+            |call| json!({"jsonrpc": "2.0", "id": call["id"], "result": "0x10"}),
+        )
+        .await
+    }
+
+    async fn failover() -> Failover {
+        let upstream = |url: &str| Upstream {
+            url: url.to_string(),
+            label: url.to_string(),
+        };
+        Failover::new(
+            "testchain",
+            vec![
+                upstream("http://127.0.0.1:1"),
+                upstream(&healthy_upstream().await),
+            ],
+        )
+    }
+
+    async fn call(failover: &Failover, method: &str) -> Value {
+        let body = json!({"jsonrpc": "2.0", "id": 7, "method": method, "params": []});
+        failover.handle_body(body.to_string().as_bytes()).await
+    }
+
+    #[tokio::test]
+    async fn reads_retry_on_the_next_upstream_and_stay_there() {
+        let failover = failover().await;
+        assert_eq!(call(&failover, "eth_blockNumber").await["result"], "0x10");
+        assert_eq!(failover.active.load(Ordering::SeqCst), 1);
+        assert_eq!(call(&failover, "eth_chainId").await["id"], 7);
+    }
+
+    #[tokio::test]
+    async fn writes_are_not_replayed_but_later_calls_move_on() {
+        let failover = failover().await;
+        let response = call(&failover, "eth_sendRawTransaction").await;
+        assert!(
+            response["error"]["message"]
+                .as_str()
+                .unwrap()
+                .contains("not retried")
+        );
+        assert_eq!(
+            call(&failover, "eth_sendRawTransaction").await["result"],
+            "0x10"
+        );
+    }
+
+    #[test]
+    fn batches_are_idempotent_only_without_writes() {
+        assert!(is_idempotent(
+            &json!([{"method": "eth_call"}, {"method": "eth_getLogs"}])
+        ));
+        assert!(!is_idempotent(&json!([
+            {"method": "eth_call"},
+            {"method": "eth_sendRawTransaction"}
+        ])));
+        assert!(!is_idempotent(&json!({"params": []})));
+    }
+
+    #[test]
+    fn every_send_method_is_a_write() {
+        for method in [
+            "eth_sendRawTransaction",
+            "eth_sendRawTransactionSync",
+            "eth_sendTransaction",
+            "eth_sendPrivateTransaction",
+            "eth_sendBundle",
+            "eth_sendUserOperation",
+            "personal_sendTransaction",
+            "mev_sendBundle",
+        ] {
+            assert!(!is_idempotent(&json!({"method": method})), "{method}");
+        }
+        assert!(is_idempotent(&json!({"method": "eth_estimateGas"})));
+    }
+}
@@ -5,6 +5,7 @@
 //! adapter and never touch developer credentials.
 
 use crate::{
+    agent,
     config::Chainz,
     opt::{KeyCommand, KeyTypeArg, MigrationTargetArg},
     prompt::{Prompt, SystemPrompt},
@@ -13,22 +14,27 @@ use aes_gcm::{
     Aes256Gcm, Nonce,
     aead::{Aead, KeyInit},
 };
-use alloy::{primitives::Address, signers::local::PrivateKeySigner};
+use alloy::{
+    primitives::{Address, keccak256},
+    signers::local::{MnemonicBuilder, PrivateKeySigner, coins_bip39::English},
+};
 use anyhow::{Context, Result, anyhow};
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 use keyring::Entry;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
-use std::{fmt, io::IsTerminal, process::Command, sync::OnceLock};
+use std::{fmt, io::IsTerminal, path::Path, process::Command, sync::OnceLock};
 use zeroize::{Zeroize, Zeroizing};
 
-const KEYRING_SERVICE: &str = "chainz";
+pub(crate) const KEYRING_SERVICE: &str = "chainz";
 const ENVELOPE_VERSION: u8 = 1;
 // These are Argon2 0.5's defaults. Persisting them makes encrypted records
 // independent from future library-default changes.
 const KDF_MEMORY_KIB: u32 = 19_456;
 const KDF_ITERATIONS: u32 = 2;
 const KDF_PARALLELISM: u32 = 1;
+/// BIP-44 Ethereum account prefix; the address index is appended per key.
+pub(crate) const DEFAULT_DERIVATION_PATH: &str = "m/44'/60'/0'/0";
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Key {
@@ -64,6 +70,94 @@ pub enum KeyType {
     OnePassword { vault: String, item: String },
     #[serde(rename = "Keyring")]
     Keyring { service: String, username: String },
+    /// A BIP-39 seed phrase, itself held in keyring or encrypted storage, and
+    /// the HD account this key resolves to.
+    #[serde(rename = "Mnemonic")]
+    Mnemonic {
+        seed: Box<KeyType>,
+        derivation_path: String,
+        index: u32,
+    },
+}
+
+/// Argon2id cost parameters persisted with each encrypted envelope.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct KdfParams {
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+impl KdfParams {
+    const DEFAULT: Self = Self {
+        memory_kib: KDF_MEMORY_KIB,
+        iterations: KDF_ITERATIONS,
+        parallelism: KDF_PARALLELISM,
+    };
+
+    fn of(kind: &KeyType) -> Option<Self> {
+        match kind {
+            KeyType::EncryptedKey {
+                kdf_memory_kib,
+                kdf_iterations,
+                kdf_parallelism,
+                ..
+            } => Some(Self {
+                memory_kib: *kdf_memory_kib,
+                iterations: *kdf_iterations,
+                parallelism: *kdf_parallelism,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Per-parameter maximum, so re-encryption never weakens an envelope.
+    fn max(self, other: Self) -> Self {
+        Self {
+            memory_kib: self.memory_kib.max(other.memory_kib),
+            iterations: self.iterations.max(other.iterations),
+            parallelism: self.parallelism.max(other.parallelism),
+        }
+    }
+}
+
+/// KDF cost requested for re-encryption. Unset fields keep the stronger of
+/// the envelope's current cost and the default; set fields may not go below
+/// the current cost unless `allow_weaker` is given.
+#[derive(Clone, Copy, Debug, Default)]
+struct KdfOverride {
+    memory_kib: Option<u32>,
+    iterations: Option<u32>,
+    allow_weaker: bool,
+}
+
+impl KdfOverride {
+    fn apply(self, current: KdfParams) -> Result<KdfParams> {
+        let base = current.max(KdfParams::DEFAULT);
+        let kdf = KdfParams {
+            memory_kib: self.memory_kib.unwrap_or(base.memory_kib),
+            iterations: self.iterations.unwrap_or(base.iterations),
+            parallelism: base.parallelism,
+        };
+        if kdf.memory_kib < KDF_MEMORY_KIB || kdf.iterations < KDF_ITERATIONS {
+            anyhow::bail!(
+                "Key-derivation parameters below the defaults ({} KiB, {} iterations) are not allowed",
+                KDF_MEMORY_KIB,
+                KDF_ITERATIONS
+            );
+        }
+        if !self.allow_weaker
+            && (kdf.memory_kib < current.memory_kib || kdf.iterations < current.iterations)
+        {
+            anyhow::bail!(
+                "Key-derivation parameters below the key's current cost ({} KiB, {} iterations) weaken it; pass --allow-weaker to do so deliberately",
+                current.memory_kib,
+                current.iterations
+            );
+        }
+        argon2_params(kdf.memory_kib, kdf.iterations, kdf.parallelism)?;
+        Ok(kdf)
+    }
 }
 
 const fn default_envelope_version() -> u8 {
@@ -116,11 +210,21 @@ impl fmt::Debug for KeyType {
                 .field("service", service)
                 .field("username", username)
                 .finish(),
+            Self::Mnemonic {
+                seed,
+                derivation_path,
+                index,
+            } => f
+                .debug_struct("Mnemonic")
+                .field("seed", seed)
+                .field("derivation_path", derivation_path)
+                .field("index", index)
+                .finish(),
         }
     }
 }
 
-trait KeyBackend {
+pub(crate) trait KeyBackend {
     fn is_interactive(&self) -> bool;
     fn prompt_secret(&self, prompt: &str) -> Result<Zeroizing<String>>;
     fn keyring_available(&self) -> bool;
@@ -129,9 +233,13 @@ trait KeyBackend {
     fn keyring_set(&self, service: &str, username: &str, value: &str) -> Result<bool>;
     fn keyring_delete(&self, service: &str, username: &str) -> Result<()>;
     fn one_password_get(&self, vault: &str, item: &str) -> Result<Zeroizing<String>>;
+    /// Look up a decrypted secret cached by a running `chainz agent`.
+    fn agent_get(&self, id: &str) -> Option<Zeroizing<String>>;
+    /// Offer a decrypted secret to a running agent; failures are ignored.
+    fn agent_put(&self, id: &str, secret: &str);
 }
 
-struct SystemKeyBackend;
+pub(crate) struct SystemKeyBackend;
 
 impl KeyBackend for SystemKeyBackend {
     fn is_interactive(&self) -> bool {
@@ -207,6 +315,14 @@ impl KeyBackend for SystemKeyBackend {
         value.zeroize();
         Ok(trimmed)
     }
+
+    fn agent_get(&self, id: &str) -> Option<Zeroizing<String>> {
+        agent::get(id)
+    }
+
+    fn agent_put(&self, id: &str, secret: &str) {
+        agent::put(id, secret)
+    }
 }
 
 struct KeyVault<B> {
@@ -232,56 +348,54 @@ impl<B: KeyBackend> KeyVault<B> {
 
     fn resolve(&self, key: &Key) -> Result<Zeroizing<String>> {
         let value = match &key.kind {
+            KeyType::Mnemonic {
+                seed,
+                derivation_path,
+                index,
+            } => {
+                let phrase = self.resolve_stored(&key.name, seed)?;
+                derive_private_key(&phrase, derivation_path, *index)?
+            }
+            kind => self.resolve_stored(&key.name, kind)?,
+        };
+        Key::validate_private_key(&value)?;
+        Ok(value)
+    }
+
+    /// Read the secret held by one storage record without interpreting it.
+    fn resolve_stored(&self, name: &str, kind: &KeyType) -> Result<Zeroizing<String>> {
+        Ok(match kind {
             KeyType::PrivateKey { value } => Zeroizing::new(value.clone()),
             KeyType::EncryptedKey {
-                value,
-                nonce,
-                salt,
-                version,
-                kdf_memory_kib,
-                kdf_iterations,
-                kdf_parallelism,
+                value, nonce, salt, ..
             } => {
-                if *version != ENVELOPE_VERSION {
-                    anyhow::bail!("Unsupported encrypted-key version {}", version);
+                // The envelope itself identifies the cache entry, so renaming a
+                // key keeps it unlocked and changing its password does not.
+                let agent_id = keccak256(format!("{}:{}:{}", value, nonce, salt)).to_string();
+                if let Some(secret) = self.backend.agent_get(&agent_id) {
+                    return Ok(secret);
                 }
                 if !self.backend.is_interactive() {
                     anyhow::bail!(
                         "Key '{}' is encrypted and needs an interactive password prompt",
-                        key.name
+                        name
                     );
                 }
                 let password = self
                     .backend
-                    .prompt_secret(&format!("Enter decryption password for {}: ", key.name))?;
-                let salt_bytes = BASE64.decode(salt)?;
-                let mut derived = derive_key(
-                    &password,
-                    &salt_bytes,
-                    *kdf_memory_kib,
-                    *kdf_iterations,
-                    *kdf_parallelism,
-                )?;
-                let cipher = Aes256Gcm::new_from_slice(&derived)
-                    .map_err(|_| anyhow!("Failed to initialize decryption"))?;
-                derived.zeroize();
-                let nonce_bytes = BASE64.decode(nonce)?;
-                if nonce_bytes.len() != 12 {
-                    anyhow::bail!("Invalid encrypted-key nonce");
-                }
-                let ciphertext = BASE64.decode(value)?;
-                let plaintext = cipher
-                    .decrypt(Nonce::from_slice(&nonce_bytes), ciphertext.as_ref())
-                    .map_err(|_| anyhow!("Failed to decrypt key '{}'", key.name))?;
-                Zeroizing::new(String::from_utf8(plaintext)?)
+                    .prompt_secret(&format!("Enter decryption password for {}: ", name))?;
+                let secret = decrypt_envelope(name, kind, &password)?;
+                self.backend.agent_put(&agent_id, &secret);
+                secret
             }
             KeyType::OnePassword { vault, item } => self.backend.one_password_get(vault, item)?,
             KeyType::Keyring { service, username } => {
                 self.backend.keyring_get(service, username)?
             }
-        };
-        Key::validate_private_key(&value)?;
-        Ok(value)
+            KeyType::Mnemonic { .. } => {
+                anyhow::bail!("Key '{}' nests a mnemonic inside a mnemonic", name)
+            }
+        })
     }
 
     fn safe_default(&self) -> MigrationTargetArg {
@@ -325,6 +439,17 @@ impl<B: KeyBackend> KeyVault<B> {
         self.provision_target(name, private_key, storage)
     }
 
+    /// Create a key from the OS CSPRNG and store it without ever exposing
+    /// the secret outside this process.
+    fn generate_private_key(
+        &self,
+        name: &str,
+        requested: Option<KeyTypeArg>,
+    ) -> Result<KeyProvision> {
+        let private_key = Zeroizing::new(alloy::hex::encode(PrivateKeySigner::random().to_bytes()));
+        self.provision_private_key(name, &private_key, requested)
+    }
+
     fn provision_target(
         &self,
         name: &str,
@@ -339,20 +464,7 @@ impl<B: KeyBackend> KeyVault<B> {
                 self.store_keyring(name, name, private_key)
             }
             MigrationTargetArg::Encrypted => {
-                if !self.backend.is_interactive() {
-                    anyhow::bail!(
-                        "No OS keyring is available and encrypted storage needs an interactive password prompt; use `--type private-key --stdin` only if plaintext storage is intentional"
-                    );
-                }
-                let password = self
-                    .backend
-                    .prompt_secret(&format!("Enter encryption password for {}: ", name))?;
-                let confirmation = self
-                    .backend
-                    .prompt_secret("Confirm encryption password: ")?;
-                if password.as_str() != confirmation.as_str() {
-                    anyhow::bail!("Encryption passwords do not match");
-                }
+                let password = self.prompt_new_password(name)?;
                 Ok(KeyProvision {
                     key: encrypt_with_password(name.to_string(), private_key, &password)?,
                     created_external: false,
@@ -361,6 +473,96 @@ impl<B: KeyBackend> KeyVault<B> {
         }
     }
 
+    fn prompt_new_password(&self, name: &str) -> Result<Zeroizing<String>> {
+        if !self.backend.is_interactive() {
+            anyhow::bail!(
+                "No OS keyring is available and encrypted storage needs an interactive password prompt; use `--type private-key --stdin` only if plaintext storage is intentional"
+            );
+        }
+        let password = self
+            .backend
+            .prompt_secret(&format!("Enter encryption password for {}: ", name))?;
+        let confirmation = self
+            .backend
+            .prompt_secret("Confirm encryption password: ")?;
+        if password.as_str() != confirmation.as_str() {
+            anyhow::bail!("Encryption passwords do not match");
+        }
+        Ok(password)
+    }
+
+    /// Store a seed phrase in keyring or encrypted storage and record the HD
+    /// account it resolves to. Plaintext phrases are never persisted.
+    fn provision_mnemonic(
+        &self,
+        name: &str,
+        phrase: &str,
+        derivation_path: &str,
+        index: u32,
+        requested: Option<KeyTypeArg>,
+    ) -> Result<KeyProvision> {
+        let address =
+            Key::address_from_private_key(&derive_private_key(phrase, derivation_path, index)?)?;
+        let target = match requested {
+            Some(KeyTypeArg::Keyring) => MigrationTargetArg::Keyring,
+            Some(KeyTypeArg::Encrypted) => MigrationTargetArg::Encrypted,
+            Some(KeyTypeArg::PrivateKey | KeyTypeArg::OnePassword) => anyhow::bail!(
+                "Mnemonic keys are stored in the OS keyring or encrypted; use --type keyring or --type encrypted"
+            ),
+            None => self.safe_default(),
+        };
+        let (seed, created_external) = match target {
+            MigrationTargetArg::Keyring => {
+                if !self.backend.keyring_available() {
+                    anyhow::bail!("The OS keyring is unavailable; use --type encrypted instead");
+                }
+                let created = self.backend.keyring_set(KEYRING_SERVICE, name, phrase)?;
+                (
+                    KeyType::Keyring {
+                        service: KEYRING_SERVICE.to_string(),
+                        username: name.to_string(),
+                    },
+                    created,
+                )
+            }
+            MigrationTargetArg::Encrypted => {
+                let password = self.prompt_new_password(name)?;
+                (encrypt_secret(phrase, &password)?, false)
+            }
+        };
+        Ok(KeyProvision {
+            key: Key {
+                name: name.to_string(),
+                address: Some(address.to_string()),
+                kind: KeyType::Mnemonic {
+                    seed: Box::new(seed),
+                    derivation_path: derivation_path.to_string(),
+                    index,
+                },
+            },
+            created_external,
+        })
+    }
+
+    /// Derive consecutive account addresses from a mnemonic key's seed.
+    fn derive_addresses(&self, key: &Key, start: u32, count: u32) -> Result<Vec<(u32, Address)>> {
+        let KeyType::Mnemonic {
+            seed,
+            derivation_path,
+            ..
+        } = &key.kind
+        else {
+            anyhow::bail!("Key '{}' is not a mnemonic key", key.name);
+        };
+        let phrase = self.resolve_stored(&key.name, seed)?;
+        (start..start.saturating_add(count))
+            .map(|index| {
+                let private_key = derive_private_key(&phrase, derivation_path, index)?;
+                Ok((index, Key::address_from_private_key(&private_key)?))
+            })
+            .collect()
+    }
+
     fn provision_replacement_private_key(
         &self,
         name: &str,
@@ -396,11 +598,60 @@ impl<B: KeyBackend> KeyVault<B> {
         })
     }
 
+    /// Decrypt a password-encrypted key with its current password and seal it
+    /// in a fresh envelope, optionally under a new password.
+    fn reencrypt(
+        &self,
+        key: &Key,
+        change_password: bool,
+        kdf: KdfOverride,
+    ) -> Result<KeyProvision> {
+        let envelope = match &key.kind {
+            KeyType::Mnemonic { seed, .. } => seed.as_ref(),
+            kind => kind,
+        };
+        let current = KdfParams::of(envelope)
+            .ok_or_else(|| anyhow!("Key '{}' is not password-encrypted", key.name))?;
+        let kdf = kdf.apply(current)?;
+        if !self.backend.is_interactive() {
+            anyhow::bail!(
+                "Key '{}' is encrypted and needs an interactive password prompt",
+                key.name
+            );
+        }
+        // Always prompt: the agent can supply the secret but not the password.
+        let password = self
+            .backend
+            .prompt_secret(&format!("Enter current password for {}: ", key.name))?;
+        let secret = decrypt_envelope(&key.name, envelope, &password)?;
+        let password = if change_password {
+            self.prompt_new_password(&key.name)?
+        } else {
+            password
+        };
+        let sealed = encrypt_secret_with(&secret, &password, kdf)?;
+        let mut rekeyed = key.clone();
+        match &mut rekeyed.kind {
+            KeyType::Mnemonic { seed, .. } => **seed = sealed,
+            kind => *kind = sealed,
+        }
+        Ok(KeyProvision {
+            key: rekeyed,
+            created_external: false,
+        })
+    }
+
     fn provision_migration(
         &self,
         key: &Key,
         target: Option<MigrationTargetArg>,
     ) -> Result<KeyProvision> {
+        if let KeyType::Mnemonic { .. } = key.kind {
+            anyhow::bail!(
+                "Key '{}' is a mnemonic key and already uses safe storage",
+                key.name
+            );
+        }
         let private_key = self.resolve(key)?;
         self.provision_target(
             &key.name,
@@ -409,8 +660,56 @@ impl<B: KeyBackend> KeyVault<B> {
         )
     }
 
+    /// Decrypt a Web3 Secret Storage file and provision its key exactly like
+    /// a key entered through `key add`.
+    fn import_keystore(
+        &self,
+        name: &str,
+        path: &Path,
+        requested: Option<KeyTypeArg>,
+    ) -> Result<KeyProvision> {
+        if !path.is_file() {
+            anyhow::bail!("Keystore file {} not found", path.display());
+        }
+        if !self.backend.is_interactive() {
+            anyhow::bail!("Keystore import needs an interactive password prompt");
+        }
+        let password = self
+            .backend
+            .prompt_secret(&format!("Enter keystore password for {}: ", path.display()))?;
+        let signer = PrivateKeySigner::decrypt_keystore(path, password.as_bytes())
+            .with_context(|| format!("Failed to decrypt keystore at {}", path.display()))?;
+        let private_key = Zeroizing::new(alloy::hex::encode(signer.to_bytes()));
+        self.provision_private_key(name, &private_key, requested)
+    }
+
+    fn export_keystore(&self, key: &Key, path: &Path) -> Result<()> {
+        if !self.backend.is_interactive() {
+            anyhow::bail!("Keystore export needs an interactive password prompt");
+        }
+        // Claim the path before prompting; a failed export removes it again.
+        let mut file = create_private_file(path)?;
+        let result = (|| {
+            let private_key = self.resolve(key)?;
+            let password = self
+                .backend
+                .prompt_secret(&format!("Enter keystore password for {}: ", key.name))?;
+            let confirmation = self.backend.prompt_secret("Confirm keystore password: ")?;
+            if password.as_str() != confirmation.as_str() {
+                anyhow::bail!("Keystore passwords do not match");
+            }
+            write_keystore(&mut file, &private_key, &password)
+                .with_context(|| format!("Failed to write keystore to {}", path.display()))
+        })();
+        if result.is_err() {
+            drop(file);
+            let _ = std::fs::remove_file(path);
+        }
+        result
+    }
+
     fn cleanup_external(&self, key: &Key) -> Result<()> {
-        if let KeyType::Keyring { service, username } = &key.kind {
+        if let Some((service, username)) = keyring_location(&key.kind) {
             self.backend.keyring_delete(service, username)?;
         }
         Ok(())
@@ -478,43 +777,7 @@ impl Key {
                 anyhow::bail!("Cached address does not match the private key");
             }
         }
-        match &self.kind {
-            KeyType::PrivateKey { value } => Self::validate_private_key(value),
-            KeyType::EncryptedKey {
-                value,
-                nonce,
-                salt,
-                version,
-                kdf_memory_kib,
-                kdf_iterations,
-                kdf_parallelism,
-                ..
-            } => {
-                if *version != ENVELOPE_VERSION {
-                    anyhow::bail!("Unsupported encrypted-key version {}", version);
-                }
-                if BASE64.decode(value)?.is_empty()
-                    || BASE64.decode(nonce)?.len() != 12
-                    || BASE64.decode(salt)?.len() < 16
-                {
-                    anyhow::bail!("Invalid encrypted-key parameters");
-                }
-                let _ = argon2_params(*kdf_memory_kib, *kdf_iterations, *kdf_parallelism)?;
-                Ok(())
-            }
-            KeyType::OnePassword { vault, item } => {
-                if vault.trim().is_empty() || item.trim().is_empty() {
-                    anyhow::bail!("1Password vault and item cannot be empty");
-                }
-                Ok(())
-            }
-            KeyType::Keyring { service, username } => {
-                if service.trim().is_empty() || username.trim().is_empty() {
-                    anyhow::bail!("Keyring service and username cannot be empty");
-                }
-                Ok(())
-            }
-        }
+        validate_kind(&self.kind)
     }
 
     fn kind_name(&self) -> &'static str {
@@ -523,6 +786,7 @@ impl Key {
             KeyType::EncryptedKey { .. } => "EncryptedKey",
             KeyType::OnePassword { .. } => "OnePassword",
             KeyType::Keyring { .. } => "Keyring",
+            KeyType::Mnemonic { .. } => "Mnemonic",
         }
     }
 
@@ -534,6 +798,91 @@ impl Key {
     }
 }
 
+fn validate_kind(kind: &KeyType) -> Result<()> {
+    match kind {
+        KeyType::PrivateKey { value } => Key::validate_private_key(value),
+        KeyType::EncryptedKey {
+            value,
+            nonce,
+            salt,
+            version,
+            kdf_memory_kib,
+            kdf_iterations,
+            kdf_parallelism,
+            ..
+        } => {
+            if *version != ENVELOPE_VERSION {
+                anyhow::bail!("Unsupported encrypted-key version {}", version);
+            }
+            if BASE64.decode(value)?.is_empty()
+                || BASE64.decode(nonce)?.len() != 12
+                || BASE64.decode(salt)?.len() < 16
+            {
+                anyhow::bail!("Invalid encrypted-key parameters");
+            }
+            let _ = argon2_params(*kdf_memory_kib, *kdf_iterations, *kdf_parallelism)?;
+            Ok(())
+        }
+        KeyType::OnePassword { vault, item } => {
+            if vault.trim().is_empty() || item.trim().is_empty() {
+                anyhow::bail!("1Password vault and item cannot be empty");
+            }
+            Ok(())
+        }
+        KeyType::Keyring { service, username } => {
+            if service.trim().is_empty() || username.trim().is_empty() {
+                anyhow::bail!("Keyring service and username cannot be empty");
+            }
+            Ok(())
+        }
+        KeyType::Mnemonic {
+            seed,
+            derivation_path,
+            index,
+        } => {
+            if !matches!(
+                **seed,
+                KeyType::EncryptedKey { .. } | KeyType::Keyring { .. }
+            ) {
+                anyhow::bail!("Mnemonic seeds must use keyring or encrypted storage");
+            }
+            validate_kind(seed)?;
+            let _ = mnemonic_builder(derivation_path, *index)?;
+            Ok(())
+        }
+    }
+}
+
+fn keyring_location(kind: &KeyType) -> Option<(&str, &str)> {
+    match kind {
+        KeyType::Keyring { service, username } => Some((service, username)),
+        KeyType::Mnemonic { seed, .. } => keyring_location(seed),
+        _ => None,
+    }
+}
+
+fn mnemonic_builder(derivation_path: &str, index: u32) -> Result<MnemonicBuilder<English>> {
+    MnemonicBuilder::<English>::default()
+        .derivation_path(format!(
+            "{}/{}",
+            derivation_path.trim_end_matches('/'),
+            index
+        ))
+        .map_err(|error| anyhow!("Invalid derivation path: {}", error))
+}
+
+fn derive_private_key(
+    phrase: &str,
+    derivation_path: &str,
+    index: u32,
+) -> Result<Zeroizing<String>> {
+    let signer = mnemonic_builder(derivation_path, index)?
+        .phrase(phrase)
+        .build()
+        .map_err(|_| anyhow!("Invalid mnemonic phrase"))?;
+    Ok(Zeroizing::new(alloy::hex::encode(signer.to_bytes())))
+}
+
 fn argon2_params(memory_kib: u32, iterations: u32, parallelism: u32) -> Result<argon2::Params> {
     if memory_kib > 262_144 || iterations > 10 || parallelism > 16 {
         anyhow::bail!("Key-derivation parameters exceed safety limits");
@@ -542,15 +891,9 @@ fn argon2_params(memory_kib: u32, iterations: u32, parallelism: u32) -> Result<a
         .map_err(|e| anyhow!("Invalid key-derivation parameters: {}", e))
 }
 
-fn derive_key(
-    password: &str,
-    salt: &[u8],
-    memory_kib: u32,
-    iterations: u32,
-    parallelism: u32,
-) -> Result<[u8; 32]> {
+fn derive_key(password: &str, salt: &[u8], kdf: KdfParams) -> Result<[u8; 32]> {
     let mut key = [0u8; 32];
-    let params = argon2_params(memory_kib, iterations, parallelism)?;
+    let params = argon2_params(kdf.memory_kib, kdf.iterations, kdf.parallelism)?;
     let argon2 = argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
     argon2
         .hash_password_into(password.as_bytes(), salt, &mut key)
@@ -558,42 +901,122 @@ fn derive_key(
     Ok(key)
 }
 
+/// Open an `EncryptedKey` envelope with `password`.
+fn decrypt_envelope(name: &str, kind: &KeyType, password: &str) -> Result<Zeroizing<String>> {
+    let KeyType::EncryptedKey {
+        value,
+        nonce,
+        salt,
+        version,
+        kdf_memory_kib,
+        kdf_iterations,
+        kdf_parallelism,
+    } = kind
+    else {
+        anyhow::bail!("Key '{}' is not password-encrypted", name);
+    };
+    if *version != ENVELOPE_VERSION {
+        anyhow::bail!("Unsupported encrypted-key version {}", version);
+    }
+    let salt_bytes = BASE64.decode(salt)?;
+    let mut derived = derive_key(
+        password,
+        &salt_bytes,
+        KdfParams {
+            memory_kib: *kdf_memory_kib,
+            iterations: *kdf_iterations,
+            parallelism: *kdf_parallelism,
+        },
+    )?;
+    let cipher = Aes256Gcm::new_from_slice(&derived)
+        .map_err(|_| anyhow!("Failed to initialize decryption"))?;
+    derived.zeroize();
+    let nonce_bytes = BASE64.decode(nonce)?;
+    if nonce_bytes.len() != 12 {
+        anyhow::bail!("Invalid encrypted-key nonce");
+    }
+    let ciphertext = BASE64.decode(value)?;
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&nonce_bytes), ciphertext.as_ref())
+        .map_err(|_| anyhow!("Failed to decrypt key '{}'", name))?;
+    Ok(Zeroizing::new(String::from_utf8(plaintext)?))
+}
+
 fn encrypt_with_password(name: String, private_key: &str, password: &str) -> Result<Key> {
+    Ok(Key::new(name, encrypt_secret(private_key, password)?).with_public_address(private_key))
+}
+
+fn encrypt_secret(secret: &str, password: &str) -> Result<KeyType> {
+    encrypt_secret_with(secret, password, KdfParams::DEFAULT)
+}
+
+/// Seal `secret` in a new envelope with a fresh salt and nonce.
+fn encrypt_secret_with(secret: &str, password: &str, kdf: KdfParams) -> Result<KeyType> {
     if password.is_empty() {
         anyhow::bail!("Encryption password cannot be empty");
     }
     let mut rng = rand::rng();
     let mut salt_bytes = [0u8; 16];
     rng.fill(&mut salt_bytes);
-    let mut derived = derive_key(
-        password,
-        &salt_bytes,
-        KDF_MEMORY_KIB,
-        KDF_ITERATIONS,
-        KDF_PARALLELISM,
-    )?;
+    let mut derived = derive_key(password, &salt_bytes, kdf)?;
     let cipher = Aes256Gcm::new_from_slice(&derived)
         .map_err(|_| anyhow!("Failed to initialize encryption"))?;
     derived.zeroize();
     let mut nonce_bytes = [0u8; 12];
     rng.fill(&mut nonce_bytes);
     let ciphertext = cipher
-        .encrypt(Nonce::from_slice(&nonce_bytes), private_key.as_bytes())
-        .map_err(|_| anyhow!("Failed to encrypt private key"))?;
+        .encrypt(Nonce::from_slice(&nonce_bytes), secret.as_bytes())
+        .map_err(|_| anyhow!("Failed to encrypt secret"))?;
 
-    Ok(Key::new(
-        name,
-        KeyType::EncryptedKey {
-            value: BASE64.encode(ciphertext),
-            nonce: BASE64.encode(nonce_bytes),
-            salt: BASE64.encode(salt_bytes),
-            version: ENVELOPE_VERSION,
-            kdf_memory_kib: KDF_MEMORY_KIB,
-            kdf_iterations: KDF_ITERATIONS,
-            kdf_parallelism: KDF_PARALLELISM,
-        },
-    )
-    .with_public_address(private_key))
+    Ok(KeyType::EncryptedKey {
+        value: BASE64.encode(ciphertext),
+        nonce: BASE64.encode(nonce_bytes),
+        salt: BASE64.encode(salt_bytes),
+        version: ENVELOPE_VERSION,
+        kdf_memory_kib: kdf.memory_kib,
+        kdf_iterations: kdf.iterations,
+        kdf_parallelism: kdf.parallelism,
+    })
+}
+
+/// Create `path` owner-only, failing if anything already exists there.
+fn create_private_file(path: &Path) -> Result<std::fs::File> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path).map_err(|error| match error.kind() {
+        std::io::ErrorKind::AlreadyExists => anyhow!(
+            "{} already exists; refusing to overwrite it",
+            path.display()
+        ),
+        _ => anyhow::Error::new(error).context(format!("Failed to create {}", path.display())),
+    })
+}
+
+/// Write a scrypt keystore readable by geth, `cast wallet`, and foundry.
+fn write_keystore(file: &mut std::fs::File, private_key: &str, password: &str) -> Result<()> {
+    use std::io::Write;
+    if password.is_empty() {
+        anyhow::bail!("Keystore password cannot be empty");
+    }
+    // The library only writes into a directory, so encrypt into a private
+    // temporary one and copy the JSON into the file created above.
+    let staging = tempfile::tempdir()?;
+    let secret = private_key.parse::<PrivateKeySigner>()?.to_bytes();
+    PrivateKeySigner::encrypt_keystore(
+        staging.path(),
+        &mut alloy::signers::k256::elliptic_curve::rand_core::OsRng,
+        secret,
+        password,
+        Some("keystore.json"),
+    )?;
+    file.write_all(&std::fs::read(staging.path().join("keystore.json"))?)?;
+    file.sync_all()?;
+    Ok(())
 }
 
 fn read_stdin_secret(label: &str) -> Result<Zeroizing<String>> {
@@ -735,19 +1158,88 @@ async fn migrate_names(
     Ok(migrated.len())
 }
 
+/// Re-encrypt `names` as one transaction under the held config lock. Any
+/// failure, including the config write, restores every staged record.
+async fn rekey_names(
+    chainz: &mut Chainz,
+    names: Vec<String>,
+    change_password: bool,
+    kdf: KdfOverride,
+) -> Result<usize> {
+    let vault = KeyVault::new(SystemKeyBackend);
+    let rekeyed = stage_rekeys(chainz, &vault, names, change_password, kdf)?;
+    if let Err(error) = chainz.save().await {
+        rollback_provisions(chainz, &vault, &rekeyed);
+        return Err(error);
+    }
+    Ok(rekeyed.len())
+}
+
+fn stage_rekeys<B: KeyBackend>(
+    chainz: &mut Chainz,
+    vault: &KeyVault<B>,
+    names: Vec<String>,
+    change_password: bool,
+    kdf: KdfOverride,
+) -> Result<Vec<(String, Key, KeyProvision)>> {
+    let mut rekeyed = Vec::new();
+    for name in names {
+        let staged = chainz
+            .get_key(&name)
+            .and_then(|source| Ok((vault.reencrypt(&source, change_password, kdf)?, source)));
+        match staged {
+            Ok((provision, source)) => {
+                chainz
+                    .config
+                    .keys
+                    .insert(name.clone(), provision.key().clone());
+                rekeyed.push((name, source, provision));
+            }
+            Err(error) => {
+                rollback_provisions(chainz, vault, &rekeyed);
+                return Err(error.context(format!("Failed to re-encrypt '{}'", name)));
+            }
+        }
+    }
+    Ok(rekeyed)
+}
+
+fn is_password_encrypted(key: &Key) -> bool {
+    match &key.kind {
+        KeyType::Mnemonic { seed, .. } => KdfParams::of(seed).is_some(),
+        kind => KdfParams::of(kind).is_some(),
+    }
+}
+
+/// Record a freshly provisioned key and commit the config, rolling back any
+/// external credential the provision created if the commit fails.
+async fn commit_new_key<B: KeyBackend>(
+    chainz: &mut Chainz,
+    vault: &KeyVault<B>,
+    provision: KeyProvision,
+) -> Result<()> {
+    let name = provision.key().name.clone();
+    if let Err(error) = chainz.add_key(&name, provision.key().clone()) {
+        let _ = vault.rollback(&provision);
+        return Err(error);
+    }
+    if let Err(error) = chainz.save().await {
+        chainz.config.keys.remove(&name);
+        if let Err(rollback_error) = vault.rollback(&provision) {
+            eprintln!(
+                "Warning: could not roll back credential for '{}': {rollback_error}",
+                name
+            );
+        }
+        return Err(error);
+    }
+    Ok(())
+}
+
 fn same_external_location(left: &Key, right: &Key) -> bool {
     matches!(
-        (&left.kind, &right.kind),
-        (
-            KeyType::Keyring {
-                service: left_service,
-                username: left_username,
-            },
-            KeyType::Keyring {
-                service: right_service,
-                username: right_username,
-            }
-        ) if left_service == right_service && left_username == right_username
+        (keyring_location(&left.kind), keyring_location(&right.kind)),
+        (Some(left), Some(right)) if left == right
     )
 }
 
@@ -759,6 +1251,9 @@ impl KeyCommand {
                 key,
                 stdin,
                 key_type,
+                mnemonic,
+                index,
+                derivation_path,
             } => {
                 if chainz.get_key(&name).is_ok() {
                     anyhow::bail!("Key '{}' already exists", name);
@@ -786,6 +1281,19 @@ impl KeyCommand {
                         ),
                         created_external: false,
                     }
+                } else if mnemonic {
+                    let phrase = if stdin {
+                        read_stdin_secret("mnemonic")?
+                    } else if vault.backend.is_interactive() {
+                        vault.backend.prompt_secret("Enter mnemonic phrase: ")?
+                    } else {
+                        anyhow::bail!(
+                            "No mnemonic provided; use --stdin for scripts or run interactively"
+                        );
+                    };
+                    let phrase =
+                        Zeroizing::new(phrase.split_whitespace().collect::<Vec<_>>().join(" "));
+                    vault.provision_mnemonic(&name, &phrase, &derivation_path, index, key_type)?
                 } else {
                     let private_key = match (key, stdin) {
                         (Some(value), false) => Zeroizing::new(value),
@@ -800,21 +1308,41 @@ impl KeyCommand {
                     };
                     vault.provision_private_key(&name, &private_key, key_type)?
                 };
-                if let Err(error) = chainz.add_key(&name, provision.key().clone()) {
-                    let _ = vault.rollback(&provision);
-                    return Err(error);
+                commit_new_key(chainz, &vault, provision).await?;
+                println!("Added key '{}'", name);
+            }
+            KeyCommand::Generate { name, key_type } => {
+                if chainz.get_key(&name).is_ok() {
+                    anyhow::bail!("Key '{}' already exists", name);
                 }
-                if let Err(error) = chainz.save().await {
-                    chainz.config.keys.remove(&name);
-                    if let Err(rollback_error) = vault.rollback(&provision) {
-                        eprintln!(
-                            "Warning: could not roll back credential for '{}': {rollback_error}",
-                            name
-                        );
-                    }
-                    return Err(error);
+                let vault = KeyVault::new(SystemKeyBackend);
+                let provision = vault.generate_private_key(&name, key_type)?;
+                let address = provision
+                    .key()
+                    .address
+                    .clone()
+                    .expect("generated keys cache their address");
+                commit_new_key(chainz, &vault, provision).await?;
+                println!("Generated key '{}' ({})", name, address);
+            }
+            KeyCommand::Derive { name, count, start } => {
+                let key = chainz.get_key(&name)?;
+                chainz.release_config_lock();
+                let KeyType::Mnemonic {
+                    derivation_path,
+                    index: selected,
+                    ..
+                } = &key.kind
+                else {
+                    anyhow::bail!("Key '{}' is not a mnemonic key", name);
+                };
+                let addresses =
+                    KeyVault::new(SystemKeyBackend).derive_addresses(&key, start, count)?;
+                println!("Accounts for '{}' ({}/N):", name, derivation_path);
+                for (index, address) in addresses {
+                    let marker = if index == *selected { "*" } else { " " };
+                    println!("{} {:>4}  {}", marker, index, address);
                 }
-                println!("Added key '{}'", name);
             }
             KeyCommand::List { json } => {
                 let keys = chainz.list_keys();
@@ -877,6 +1405,78 @@ impl KeyCommand {
                 let count = migrate_names(chainz, names, to, all).await?;
                 println!("Migrated {} key(s)", count);
             }
+            KeyCommand::Passwd { name } => {
+                rekey_names(chainz, vec![name.clone()], true, KdfOverride::default()).await?;
+                println!("Changed password for key '{}'", name);
+            }
+            KeyCommand::Rekey {
+                name,
+                all,
+                kdf_memory,
+                kdf_iterations,
+                allow_weaker,
+            } => {
+                let names = if all {
+                    chainz
+                        .list_keys()
+                        .into_iter()
+                        .filter(|(_, key)| is_password_encrypted(key))
+                        .map(|(name, _)| name.to_string())
+                        .collect()
+                } else {
+                    vec![name.ok_or_else(|| anyhow!("Provide a key name or use --all"))?]
+                };
+                let kdf = KdfOverride {
+                    memory_kib: kdf_memory,
+                    iterations: kdf_iterations,
+                    allow_weaker,
+                };
+                let count = rekey_names(chainz, names, false, kdf).await?;
+                println!("Re-encrypted {} key(s)", count);
+            }
+            KeyCommand::ImportKeystore {
+                file,
+                name,
+                key_type,
+            } => {
+                let name = match name {
+                    Some(name) => name,
+                    None => file
+                        .file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .map(str::to_string)
+                        .ok_or_else(|| {
+                            anyhow!("Cannot derive a key name from the file name; use --name")
+                        })?,
+                };
+                if chainz.get_key(&name).is_ok() {
+                    anyhow::bail!("Key '{}' already exists", name);
+                }
+                let vault = KeyVault::new(SystemKeyBackend);
+                let provision = vault.import_keystore(&name, &file, key_type)?;
+                let address = provision.key().address_noninteractive();
+                commit_new_key(chainz, &vault, provision).await?;
+                match address {
+                    Some(address) => println!("Imported key '{}' ({})", name, address),
+                    None => println!("Imported key '{}'", name),
+                }
+            }
+            KeyCommand::ExportKeystore { name, file } => {
+                let key = chainz.get_key(&name)?;
+                // Export only reads config; don't serialize other commands
+                // behind password prompts and scrypt.
+                chainz.release_config_lock();
+                KeyVault::new(SystemKeyBackend).export_keystore(&key, &file)?;
+                println!("Exported key '{}' to {}", name, file.display());
+            }
+            KeyCommand::Lock => {
+                chainz.release_config_lock();
+                match agent::lock() {
+                    Some(1) => println!("Locked 1 cached key"),
+                    Some(wiped) => println!("Locked {} cached keys", wiped),
+                    None => println!("No chainz agent is running"),
+                }
+            }
         }
         Ok(())
     }
@@ -889,6 +1489,7 @@ impl fmt::Display for Key {
             KeyType::EncryptedKey { .. } => "encrypted",
             KeyType::OnePassword { .. } => "1password",
             KeyType::Keyring { .. } => "keyring",
+            KeyType::Mnemonic { .. } => "mnemonic",
         };
         match self.address_noninteractive() {
             Some(address) => write!(f, "{} ({}, {})", self.name, address, storage),
@@ -897,5 +1498,7 @@ impl fmt::Display for Key {
     }
 }
 
+#[cfg(test)]
+pub(crate) mod testing;
 #[cfg(test)]
 mod tests;
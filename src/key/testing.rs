@@ -0,0 +1,89 @@
+//! An in-memory `KeyBackend`, so tests never touch developer credentials.
+
+use super::KeyBackend;
+use anyhow::{Result, anyhow};
+use std::{cell::RefCell, collections::HashMap};
+use zeroize::Zeroizing;
+
+pub(crate) struct MemoryBackend {
+    pub(crate) interactive: bool,
+    pub(crate) available: bool,
+    pub(crate) prompts: RefCell<Vec<String>>,
+    pub(crate) keyring: RefCell<HashMap<(String, String), String>>,
+    pub(crate) one_password: RefCell<HashMap<(String, String), String>>,
+    pub(crate) agent: RefCell<HashMap<String, String>>,
+}
+
+impl MemoryBackend {
+    pub(crate) fn new(interactive: bool, available: bool, prompts: &[&str]) -> Self {
+        Self {
+            interactive,
+            available,
+            prompts: RefCell::new(prompts.iter().rev().map(|s| s.to_string()).collect()),
+            keyring: RefCell::new(HashMap::new()),
+            one_password: RefCell::new(HashMap::new()),
+            agent: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+impl KeyBackend for MemoryBackend {
+    fn is_interactive(&self) -> bool {
+        self.interactive
+    }
+    fn prompt_secret(&self, _prompt: &str) -> Result<Zeroizing<String>> {
+        Ok(Zeroizing::new(
+            self.prompts
+                .borrow_mut()
+                .pop()
+                .ok_or_else(|| anyhow!("no prompt value"))?,
+        ))
+    }
+    fn keyring_available(&self) -> bool {
+        self.available
+    }
+    fn keyring_get(&self, service: &str, username: &str) -> Result<Zeroizing<String>> {
+        Ok(Zeroizing::new(
+            self.keyring
+                .borrow()
+                .get(&(service.to_string(), username.to_string()))
+                .cloned()
+                .ok_or_else(|| anyhow!("missing keyring entry"))?,
+        ))
+    }
+    fn keyring_set(&self, service: &str, username: &str, value: &str) -> Result<bool> {
+        let location = (service.to_string(), username.to_string());
+        let mut keyring = self.keyring.borrow_mut();
+        match keyring.get(&location) {
+            Some(existing) if existing == value => Ok(false),
+            Some(_) => anyhow::bail!("credential already exists"),
+            None => {
+                keyring.insert(location, value.to_string());
+                Ok(true)
+            }
+        }
+    }
+    fn keyring_delete(&self, service: &str, username: &str) -> Result<()> {
+        self.keyring
+            .borrow_mut()
+            .remove(&(service.to_string(), username.to_string()));
+        Ok(())
+    }
+    fn one_password_get(&self, vault: &str, item: &str) -> Result<Zeroizing<String>> {
+        Ok(Zeroizing::new(
+            self.one_password
+                .borrow()
+                .get(&(vault.to_string(), item.to_string()))
+                .cloned()
+                .ok_or_else(|| anyhow!("missing 1Password entry"))?,
+        ))
+    }
+    fn agent_get(&self, id: &str) -> Option<Zeroizing<String>> {
+        self.agent.borrow().get(id).cloned().map(Zeroizing::new)
+    }
+    fn agent_put(&self, id: &str, secret: &str) {
+        self.agent
+            .borrow_mut()
+            .insert(id.to_string(), secret.to_string());
+    }
+}
@@ -1,82 +1,9 @@
+use super::testing::MemoryBackend;
 use super::*;
-use std::{cell::RefCell, collections::HashMap};
 
 const TEST_PRIVATE_KEY: &str = "0000000000000000000000000000000000000000000000000000000000000001";
 const TEST_ADDRESS: &str = "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf";
 
-struct MemoryBackend {
-    interactive: bool,
-    available: bool,
-    prompts: RefCell<Vec<String>>,
-    keyring: RefCell<HashMap<(String, String), String>>,
-    one_password: RefCell<HashMap<(String, String), String>>,
-}
-
-impl MemoryBackend {
-    fn new(interactive: bool, available: bool, prompts: &[&str]) -> Self {
-        Self {
-            interactive,
-            available,
-            prompts: RefCell::new(prompts.iter().rev().map(|s| s.to_string()).collect()),
-            keyring: RefCell::new(HashMap::new()),
-            one_password: RefCell::new(HashMap::new()),
-        }
-    }
-}
-
-impl KeyBackend for MemoryBackend {
-    fn is_interactive(&self) -> bool {
-        self.interactive
-    }
-    fn prompt_secret(&self, _prompt: &str) -> Result<Zeroizing<String>> {
-        Ok(Zeroizing::new(
-            self.prompts
-                .borrow_mut()
-                .pop()
-                .ok_or_else(|| anyhow!("no prompt value"))?,
-        ))
-    }
-    fn keyring_available(&self) -> bool {
-        self.available
-    }
-    fn keyring_get(&self, service: &str, username: &str) -> Result<Zeroizing<String>> {
-        Ok(Zeroizing::new(
-            self.keyring
-                .borrow()
-                .get(&(service.to_string(), username.to_string()))
-                .cloned()
-                .ok_or_else(|| anyhow!("missing keyring entry"))?,
-        ))
-    }
-    fn keyring_set(&self, service: &str, username: &str, value: &str) -> Result<bool> {
-        let location = (service.to_string(), username.to_string());
-        let mut keyring = self.keyring.borrow_mut();
-        match keyring.get(&location) {
-            Some(existing) if existing == value => Ok(false),
-            Some(_) => anyhow::bail!("credential already exists"),
-            None => {
-                keyring.insert(location, value.to_string());
-                Ok(true)
-            }
-        }
-    }
-    fn keyring_delete(&self, service: &str, username: &str) -> Result<()> {
-        self.keyring
-            .borrow_mut()
-            .remove(&(service.to_string(), username.to_string()));
-        Ok(())
-    }
-    fn one_password_get(&self, vault: &str, item: &str) -> Result<Zeroizing<String>> {
-        Ok(Zeroizing::new(
-            self.one_password
-                .borrow()
-                .get(&(vault.to_string(), item.to_string()))
-                .cloned()
-                .ok_or_else(|| anyhow!("missing 1Password entry"))?,
-        ))
-    }
-}
-
 #[test]
 fn plaintext_and_encrypted_round_trip() -> Result<()> {
     let plaintext = Key::new(
@@ -117,6 +44,19 @@ fn encrypted_key_rejects_wrong_password() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn agent_cache_skips_the_second_password_prompt() -> Result<()> {
+    let encrypted = encrypt_with_password("test".into(), TEST_PRIVATE_KEY, "password")?;
+    let vault = KeyVault::new(MemoryBackend::new(true, false, &["password"]));
+    assert_eq!(vault.resolve(&encrypted)?.as_str(), TEST_PRIVATE_KEY);
+    // The scripted prompt is now empty, so this succeeds only via the agent.
+    assert_eq!(vault.resolve(&encrypted)?.as_str(), TEST_PRIVATE_KEY);
+
+    let reencrypted = encrypt_with_password("test".into(), TEST_PRIVATE_KEY, "password")?;
+    assert!(vault.resolve(&reencrypted).is_err());
+    Ok(())
+}
+
 #[test]
 fn one_password_adapter_is_hermetic() -> Result<()> {
     let backend = MemoryBackend::new(false, false, &[]);
@@ -348,3 +288,303 @@ fn legacy_encrypted_record_gets_default_kdf_parameters() -> Result<()> {
     );
     Ok(())
 }
+
+#[test]
+fn keystore_export_then_import_round_trips() -> Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join("deployer.json");
+    let source = Key::new(
+        "deployer".into(),
+        KeyType::PrivateKey {
+            value: TEST_PRIVATE_KEY.into(),
+        },
+    );
+    KeyVault::new(MemoryBackend::new(true, false, &["pw", "pw"]))
+        .export_keystore(&source, &path)?;
+    let keystore: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+    assert_eq!(keystore["version"], 3);
+    assert!(!keystore.to_string().contains(TEST_PRIVATE_KEY));
+
+    let vault = KeyVault::new(MemoryBackend::new(true, true, &["pw"]));
+    let imported = vault
+        .import_keystore("imported", &path, None)?
+        .key()
+        .clone();
+    assert!(matches!(imported.kind, KeyType::Keyring { .. }));
+    assert_eq!(imported.address.as_deref(), Some(TEST_ADDRESS));
+    assert_eq!(vault.resolve(&imported)?.as_str(), TEST_PRIVATE_KEY);
+    Ok(())
+}
+
+#[test]
+fn keystore_import_rejects_wrong_password_and_export_never_overwrites() -> Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join("deployer.json");
+    let source = Key::new(
+        "deployer".into(),
+        KeyType::PrivateKey {
+            value: TEST_PRIVATE_KEY.into(),
+        },
+    );
+    KeyVault::new(MemoryBackend::new(true, false, &["pw", "pw"]))
+        .export_keystore(&source, &path)?;
+
+    let error = KeyVault::new(MemoryBackend::new(true, true, &["wrong"]))
+        .import_keystore("imported", &path, None)
+        .unwrap_err();
+    assert!(
+        format!("{error:#}").contains("Failed to decrypt"),
+        "{error:#}"
+    );
+
+    let error = KeyVault::new(MemoryBackend::new(true, false, &["pw", "pw"]))
+        .export_keystore(&source, &path)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("refusing to overwrite"), "{error}");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path)?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    let mismatched = dir.path().join("mismatched.json");
+    assert!(
+        KeyVault::new(MemoryBackend::new(true, false, &["pw", "other"]))
+            .export_keystore(&source, &mismatched)
+            .is_err()
+    );
+    assert!(
+        !mismatched.exists(),
+        "a failed export leaves no file behind"
+    );
+    Ok(())
+}
+
+const TEST_MNEMONIC: &str = "test test test test test test test test test test test junk";
+
+#[test]
+fn mnemonic_key_resolves_the_derived_account() -> Result<()> {
+    let vault = KeyVault::new(MemoryBackend::new(false, true, &[]));
+    let key = vault
+        .provision_mnemonic("fleet", TEST_MNEMONIC, DEFAULT_DERIVATION_PATH, 1, None)?
+        .key()
+        .clone();
+    key.validate_record()?;
+    assert_eq!(
+        key.address.as_deref(),
+        Some("0x70997970C51812dc3A010C7d01b50e0d17dc79C8")
+    );
+    assert_eq!(
+        vault.resolve(&key)?.as_str(),
+        "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d"
+    );
+
+    let addresses = vault.derive_addresses(&key, 0, 2)?;
+    assert_eq!(
+        addresses[0].1.to_string(),
+        "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+    );
+    assert_eq!(addresses[1], (1, key.address.as_ref().unwrap().parse()?));
+
+    let restored: Key = serde_json::from_value(serde_json::to_value(&key)?)?;
+    assert_eq!(
+        vault.resolve(&restored)?.as_str(),
+        vault.resolve(&key)?.as_str()
+    );
+    Ok(())
+}
+
+#[test]
+fn mnemonic_seed_is_never_plaintext_and_rolls_back() -> Result<()> {
+    let vault = KeyVault::new(MemoryBackend::new(false, true, &[]));
+    let error = vault
+        .provision_mnemonic(
+            "fleet",
+            TEST_MNEMONIC,
+            DEFAULT_DERIVATION_PATH,
+            0,
+            Some(KeyTypeArg::PrivateKey),
+        )
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("--type keyring"), "{error}");
+
+    let provision =
+        vault.provision_mnemonic("fleet", TEST_MNEMONIC, DEFAULT_DERIVATION_PATH, 0, None)?;
+    assert!(
+        !format!("{:?}", provision.key()).contains("junk"),
+        "debug output must not contain the phrase"
+    );
+    assert!(vault.provision_migration(provision.key(), None).is_err());
+    vault.rollback(&provision)?;
+    assert!(vault.backend.keyring.borrow().is_empty());
+    Ok(())
+}
+
+#[test]
+fn encrypted_mnemonic_prompts_for_the_seed_password() -> Result<()> {
+    let vault = KeyVault::new(MemoryBackend::new(true, false, &["pw", "pw", "pw"]));
+    let key = vault
+        .provision_mnemonic("fleet", TEST_MNEMONIC, DEFAULT_DERIVATION_PATH, 0, None)?
+        .key()
+        .clone();
+    assert!(matches!(
+        key.kind,
+        KeyType::Mnemonic { ref seed, .. } if matches!(**seed, KeyType::EncryptedKey { .. })
+    ));
+    assert_eq!(
+        vault.resolve(&key)?.as_str(),
+        "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+    );
+    Ok(())
+}
+
+#[test]
+fn generated_keys_are_distinct_and_cache_their_address() -> Result<()> {
+    let vault = KeyVault::new(MemoryBackend::new(false, true, &[]));
+    let first = vault.generate_private_key("first", None)?.key().clone();
+    let second = vault.generate_private_key("second", None)?.key().clone();
+    assert!(matches!(first.kind, KeyType::Keyring { .. }));
+    assert_ne!(first.address, second.address);
+    assert_eq!(
+        Some(Key::address_from_private_key(&vault.resolve(&first)?)?.to_string()),
+        first.address
+    );
+    Ok(())
+}
+
+fn envelope(key: &Key) -> &KeyType {
+    match &key.kind {
+        KeyType::Mnemonic { seed, .. } => seed,
+        kind => kind,
+    }
+}
+
+#[test]
+fn passwd_reseals_under_the_new_password() -> Result<()> {
+    let key = encrypt_with_password("test".into(), TEST_PRIVATE_KEY, "old")?;
+    let vault = KeyVault::new(MemoryBackend::new(
+        true,
+        false,
+        &["old", "new", "new", "new"],
+    ));
+    let changed = vault.reencrypt(&key, true, KdfOverride::default())?.key;
+    let (
+        KeyType::EncryptedKey { salt, nonce, .. },
+        KeyType::EncryptedKey {
+            salt: new_salt,
+            nonce: new_nonce,
+            ..
+        },
+    ) = (&key.kind, &changed.kind)
+    else {
+        panic!("expected encrypted envelopes");
+    };
+    assert_ne!(salt, new_salt);
+    assert_ne!(nonce, new_nonce);
+    assert_eq!(vault.resolve(&changed)?.as_str(), TEST_PRIVATE_KEY);
+    assert_eq!(changed.address, key.address);
+
+    let wrong = KeyVault::new(MemoryBackend::new(true, false, &["wrong"]));
+    assert!(wrong.reencrypt(&key, true, KdfOverride::default()).is_err());
+    Ok(())
+}
+
+#[test]
+fn rekey_raises_kdf_cost_and_refuses_to_weaken_it() -> Result<()> {
+    let vault = KeyVault::new(MemoryBackend::new(true, false, &["pw"; 4]));
+    let key = vault
+        .provision_mnemonic("fleet", TEST_MNEMONIC, DEFAULT_DERIVATION_PATH, 0, None)?
+        .key()
+        .clone();
+    let stronger = KdfOverride {
+        memory_kib: Some(KDF_MEMORY_KIB * 2),
+        ..Default::default()
+    };
+    let rekeyed = vault.reencrypt(&key, false, stronger)?.key;
+    assert_eq!(
+        KdfParams::of(envelope(&rekeyed)),
+        Some(KdfParams {
+            memory_kib: KDF_MEMORY_KIB * 2,
+            ..KdfParams::DEFAULT
+        })
+    );
+    assert_eq!(
+        vault.resolve(&rekeyed)?.as_str(),
+        "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+    );
+
+    let weaker = KdfOverride {
+        memory_kib: Some(1024),
+        ..Default::default()
+    };
+    assert!(vault.reencrypt(&key, false, weaker).is_err());
+    Ok(())
+}
+
+#[test]
+fn rekey_keeps_a_hardened_cost_unless_weakening_is_allowed() -> Result<()> {
+    let hardened = KdfParams {
+        memory_kib: KDF_MEMORY_KIB * 2,
+        iterations: KDF_ITERATIONS + 1,
+        ..KdfParams::DEFAULT
+    };
+    let back_to_default = KdfOverride {
+        memory_kib: Some(KDF_MEMORY_KIB),
+        ..Default::default()
+    };
+    let error = back_to_default.apply(hardened).unwrap_err();
+    assert!(error.to_string().contains("--allow-weaker"));
+
+    let deliberate = KdfOverride {
+        allow_weaker: true,
+        ..back_to_default
+    };
+    assert_eq!(
+        deliberate.apply(hardened)?,
+        KdfParams {
+            memory_kib: KDF_MEMORY_KIB,
+            ..hardened
+        }
+    );
+    let floor = KdfOverride {
+        memory_kib: Some(1024),
+        allow_weaker: true,
+        ..Default::default()
+    };
+    assert!(floor.apply(hardened).is_err(), "defaults stay a floor");
+    Ok(())
+}
+
+#[test]
+fn failed_rekey_restores_every_staged_key() -> Result<()> {
+    let mut chainz = Chainz::new();
+    for name in ["first", "second"] {
+        let key = encrypt_with_password(name.into(), TEST_PRIVATE_KEY, "pw")?;
+        chainz.add_key(name, key)?;
+    }
+    let before: Vec<String> = ["first", "second"]
+        .iter()
+        .map(|name| serde_json::to_string(&chainz.config.keys[*name]))
+        .collect::<Result<_, _>>()?;
+
+    // The first key re-encrypts; the second gets a wrong password.
+    let vault = KeyVault::new(MemoryBackend::new(true, false, &["pw", "wrong"]));
+    let error = stage_rekeys(
+        &mut chainz,
+        &vault,
+        vec!["first".into(), "second".into()],
+        false,
+        KdfOverride::default(),
+    )
+    .unwrap_err();
+    assert!(format!("{:#}", error).contains("Failed to re-encrypt 'second'"));
+    let after: Vec<String> = ["first", "second"]
+        .iter()
+        .map(|name| serde_json::to_string(&chainz.config.keys[*name]))
+        .collect::<Result<_, _>>()?;
+    assert_eq!(before, after);
+    Ok(())
+}
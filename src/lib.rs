@@ -4,26 +4,34 @@
 //! and serialized configuration records. Before 1.0, those Rust model types
 //! may move while the CLI and serialized configuration remain compatible.
 
+mod agent;
 mod chain;
 mod chainlist;
 mod cli;
 mod config;
 mod doctor;
 mod endpoint;
+mod env;
+mod exec;
+mod failover;
 mod init;
 mod key;
 mod listing;
 mod opt;
 mod prompt;
+mod serve;
+mod sign;
 mod ui;
 mod variables;
+mod verifier;
 
 pub use cli::run_cli;
 
 /// Serialized configuration records supported by the pre-1.0 crate interface.
 pub mod model {
-    pub use crate::chain::ChainDefinition;
-    pub use crate::config::{Config, LEGACY_CONFIG_FILE};
+    pub use crate::chain::{ChainDefinition, ChainMetadata, Explorer, NativeCurrency, ParentChain};
+    pub use crate::config::{Config, LEGACY_CONFIG_FILE, Profile};
+    pub use crate::doctor::CheckLevel;
     pub use crate::key::{Key, KeyType};
     pub use crate::variables::GlobalVariables;
 }
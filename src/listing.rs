@@ -4,11 +4,18 @@
 //! stable JSON scripting contract. Endpoint redaction is applied when the view
 //! is built, so downstream renderers cannot accidentally expose raw secrets.
 
-use crate::{chain::ChainDefinition, endpoint, ui};
+use crate::{
+    chain::{ChainDefinition, ChainMetadata},
+    config::ConfigLayer,
+    endpoint, ui,
+};
 use console::{Alignment, pad_str, style};
 use serde::Serialize;
-use std::fmt::Write;
+use std::{collections::BTreeMap, fmt::Write, path::PathBuf};
 
+const REDACTED: &str = "[REDACTED]";
+/// Key names longer than this are truncated in the compact index.
+const KEY_WIDTH: usize = 16;
 const EMPTY_HINT: &str = "No chains configured. Run 'chainz init' or 'chainz add' to get started.";
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -27,19 +34,43 @@ impl From<bool> for SecretVisibility {
     }
 }
 
+/// Which config layers supplied a chain and the default selection.
+pub(crate) struct Sources {
+    pub(crate) chain: ConfigLayer,
+    pub(crate) chain_path: Option<PathBuf>,
+    pub(crate) default: ConfigLayer,
+}
+
 /// A credential-safe projection of a chain for display or serialization.
 #[derive(Serialize)]
 struct ChainView<'a> {
     name: &'a str,
     aliases: &'a [String],
+    tags: &'a [String],
     chain_id: u64,
     selected_rpc: String,
     rpc_urls: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    selected_ws: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    ws_urls: Vec<String>,
     key_name: Option<&'a str>,
+    /// Per-chain variables; values are redacted like `var list`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    env: BTreeMap<&'a str, &'a str>,
     verification_url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     verification_api_key: Option<&'a str>,
     is_default: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default_source: Option<&'static str>,
+    /// Public chainlist data, serialized flat like the config
+    #[serde(flatten)]
+    metadata: &'a ChainMetadata,
     #[serde(skip)]
     verification_key_configured: bool,
 }
@@ -61,31 +92,103 @@ impl<'a> ChainView<'a> {
         Self {
             name: &chain.name,
             aliases: &chain.aliases,
+            tags: &chain.tags,
             chain_id: chain.chain_id,
             selected_rpc: present(&chain.selected_rpc),
             rpc_urls: chain.rpc_urls.iter().map(|url| present(url)).collect(),
+            selected_ws: chain.selected_ws.as_deref().map(present),
+            ws_urls: chain.ws_urls.iter().map(|url| present(url)).collect(),
             key_name: chain.key_name.as_deref(),
+            env: chain
+                .env
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        name.as_str(),
+                        if reveal { value.as_str() } else { REDACTED },
+                    )
+                })
+                .collect(),
             verification_url: chain.verification_url.as_deref().map(present),
             verification_api_key: reveal
                 .then_some(chain.verification_api_key.as_deref())
                 .flatten(),
             is_default: default == Some(chain.name.as_str()),
+            source: None,
+            source_path: None,
+            default_source: None,
+            metadata: &chain.metadata,
             verification_key_configured: chain.verification_api_key.is_some(),
         }
     }
 
-    /// Render the detailed `show` view, including explicit default status.
+    fn with_sources(mut self, sources: &Sources) -> Self {
+        self.source = Some(sources.chain.as_str());
+        self.source_path = sources
+            .chain_path
+            .as_ref()
+            .map(|path| path.display().to_string());
+        self.default_source = self.is_default.then(|| sources.default.as_str());
+        self
+    }
+
+    /// Render the detailed `show` view, including explicit default status and
+    /// the config layer each value came from.
     fn show(&self) -> String {
         let mut output = self.description();
-        writeln!(
-            output,
-            "Default: {}",
-            if self.is_default { "Yes" } else { "No" }
-        )
+        if let Some(source) = self.source {
+            match &self.source_path {
+                Some(path) => writeln!(output, "Source: {} ({})", source, path),
+                None => writeln!(output, "Source: {}", source),
+            }
+            .expect("writing to a String cannot fail");
+        }
+        match self.default_source {
+            Some(source) => writeln!(output, "Default: Yes ({})", source),
+            None => writeln!(
+                output,
+                "Default: {}",
+                if self.is_default { "Yes" } else { "No" }
+            ),
+        }
         .expect("writing to a String cannot fail");
         output
     }
 
+    /// Labeled chainlist facts, omitting the ones a chain doesn't have.
+    fn metadata_lines(&self) -> Vec<(&'static str, String)> {
+        let metadata = self.metadata;
+        let mut lines = Vec::new();
+        if let Some(short_name) = &metadata.short_name {
+            lines.push(("Short Name", short_name.clone()));
+        }
+        if metadata.testnet {
+            lines.push(("Network", "Testnet".to_string()));
+        }
+        if let Some(currency) = &metadata.native_currency {
+            lines.push((
+                "Currency",
+                format!(
+                    "{} ({}, {} decimals)",
+                    currency.symbol, currency.name, currency.decimals
+                ),
+            ));
+        }
+        if let Some(explorer) = metadata.explorers.first() {
+            lines.push(("Explorer", format!("{} ({})", explorer.url, explorer.name)));
+        }
+        if let Some(parent) = &metadata.parent {
+            let chain = parent
+                .chain_id()
+                .map_or_else(|| parent.chain.clone(), |id| format!("chain {}", id));
+            lines.push(("Parent", format!("{} of {}", parent.kind, chain)));
+        }
+        if let Some(info_url) = &metadata.info_url {
+            lines.push(("Info", info_url.clone()));
+        }
+        lines
+    }
+
     fn description(&self) -> String {
         let mut output = String::new();
         writeln!(
@@ -108,6 +211,16 @@ impl<'a> ChainView<'a> {
             ui::emph(&self.chain_id.to_string())
         )
         .expect("writing to a String cannot fail");
+        if !self.tags.is_empty() {
+            writeln!(
+                output,
+                "{}─ {}: {}",
+                style("├").dim(),
+                style("Tags").cyan(),
+                self.tags.join(", ")
+            )
+            .expect("writing to a String cannot fail");
+        }
         writeln!(
             output,
             "{}─ {}: {}",
@@ -116,6 +229,26 @@ impl<'a> ChainView<'a> {
             style(&self.selected_rpc).green()
         )
         .expect("writing to a String cannot fail");
+        for (label, value) in self.metadata_lines() {
+            writeln!(
+                output,
+                "{}─ {}: {}",
+                style("├").dim(),
+                style(label).cyan(),
+                value
+            )
+            .expect("writing to a String cannot fail");
+        }
+        if let Some(ws_url) = &self.selected_ws {
+            writeln!(
+                output,
+                "{}─ {}: {}",
+                style("├").dim(),
+                style("WebSocket").cyan(),
+                style(ws_url).green()
+            )
+            .expect("writing to a String cannot fail");
+        }
         writeln!(
             output,
             "{}─ {}: {}",
@@ -141,6 +274,16 @@ impl<'a> ChainView<'a> {
             }
         )
         .expect("writing to a String cannot fail");
+        for (name, value) in &self.env {
+            writeln!(
+                output,
+                "{}─ {}: {}",
+                style("├").dim(),
+                style(name).cyan(),
+                value
+            )
+            .expect("writing to a String cannot fail");
+        }
         writeln!(
             output,
             "{}─ {}: {}",
@@ -170,22 +313,35 @@ pub(crate) fn compact(chains: &[ChainDefinition], default: Option<&str>) -> Stri
         .max()
         .unwrap_or(2)
         .clamp(2, 12);
+    // The tags column only appears once some chain is tagged; keys are
+    // padded only where a tag follows, so lines carry no trailing blanks.
+    let tagged = chains.iter().any(|chain| !chain.tags.is_empty());
+    let key_column = |key: &str, padded: bool| {
+        let key = console::truncate_str(key, KEY_WIDTH, "…");
+        if padded {
+            pad_str(&key, KEY_WIDTH, Alignment::Left, None).into_owned()
+        } else {
+            key.into_owned()
+        }
+    };
     let mut output = String::new();
-    writeln!(
+    write!(
         output,
-        "  {}  {}  {}  KEY",
+        "  {}  {}  {}  {}",
         pad_str("CHAIN", name_width, Alignment::Left, Some("…")),
         pad_str("ID", id_width, Alignment::Right, Some("…")),
-        pad_str("RPC", 30, Alignment::Left, Some("…"))
+        pad_str("RPC", 30, Alignment::Left, Some("…")),
+        key_column("KEY", tagged)
     )
     .expect("writing to a String cannot fail");
+    output.push_str(if tagged { "  TAGS\n" } else { "\n" });
     for chain in chains {
         let marker = if default == Some(chain.name.as_str()) {
             "*"
         } else {
             " "
         };
-        writeln!(
+        write!(
             output,
             "{} {}  {}  {}  {}",
             marker,
@@ -202,9 +358,16 @@ pub(crate) fn compact(chains: &[ChainDefinition], default: Option<&str>) -> Stri
                 Alignment::Left,
                 Some("…")
             ),
-            console::truncate_str(chain.key_name.as_deref().unwrap_or("—"), 16, "…")
+            key_column(
+                chain.key_name.as_deref().unwrap_or("—"),
+                !chain.tags.is_empty()
+            )
         )
         .expect("writing to a String cannot fail");
+        if !chain.tags.is_empty() {
+            write!(output, "  {}", chain.tags.join(",")).expect("writing to a String cannot fail");
+        }
+        output.push('\n');
     }
     if default.is_some() {
         output.push_str("* default chain\n");
@@ -251,32 +414,47 @@ pub(crate) fn show(
     chain: &ChainDefinition,
     default: Option<&str>,
     visibility: SecretVisibility,
+    sources: &Sources,
 ) -> String {
-    ChainView::new(chain, default, visibility).show()
+    ChainView::new(chain, default, visibility)
+        .with_sources(sources)
+        .show()
 }
 
 pub(crate) fn show_json(
     chain: &ChainDefinition,
     default: Option<&str>,
     visibility: SecretVisibility,
+    sources: &Sources,
 ) -> serde_json::Result<String> {
-    serde_json::to_string_pretty(&ChainView::new(chain, default, visibility))
+    serde_json::to_string_pretty(&ChainView::new(chain, default, visibility).with_sources(sources))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const GLOBAL: Sources = Sources {
+        chain: ConfigLayer::Global,
+        chain_path: None,
+        default: ConfigLayer::Global,
+    };
+
     fn chain(name: &str, id: u64, key: Option<&str>) -> ChainDefinition {
         ChainDefinition {
             name: name.to_string(),
             aliases: vec![],
+            tags: vec![],
             chain_id: id,
             rpc_urls: vec!["https://provider.example/v2/secret".to_string()],
             selected_rpc: "https://provider.example/v2/secret".to_string(),
+            ws_urls: vec![],
+            selected_ws: None,
             verification_api_key: Some("verification-secret".to_string()),
             verification_url: Some("https://verify.example/api/secret".to_string()),
             key_name: key.map(str::to_string),
+            env: Default::default(),
+            metadata: Default::default(),
         }
     }
 
@@ -296,25 +474,93 @@ mod tests {
         assert_eq!(output.lines().count(), 4);
     }
 
+    #[test]
+    fn tags_appear_in_every_renderer() -> anyhow::Result<()> {
+        let mut tagged = chain("base", 8453, Some("deployer"));
+        tagged.tags = vec!["l2".into(), "prod".into()];
+        let chains = [chain("ethereum", 1, None), tagged];
+
+        let output = compact(&chains, None);
+        let lines: Vec<_> = output.lines().collect();
+        assert!(lines[0].ends_with("KEY               TAGS"), "{output}");
+        assert!(lines[1].ends_with("—"), "untagged rows are not padded");
+        assert!(lines[2].ends_with("deployer          l2,prod"), "{output}");
+        assert!(!compact(&chains[..1], None).contains("TAGS"));
+
+        let output = verbose(&chains, None, SecretVisibility::Redacted);
+        assert!(console::strip_ansi_codes(&output).contains("├─ Tags: l2, prod"));
+
+        let parsed: serde_json::Value =
+            serde_json::from_str(&json(&chains, None, SecretVisibility::Redacted)?)?;
+        assert_eq!(parsed[0]["tags"], serde_json::json!([]));
+        assert_eq!(parsed[1]["tags"], serde_json::json!(["l2", "prod"]));
+        Ok(())
+    }
+
     #[test]
     fn redacted_view_is_safe_across_human_and_json_renderers() {
-        let chain = chain("ethereum", 1, Some("default"));
+        let mut chain = chain("ethereum", 1, Some("default"));
+        chain.select_ws("wss://provider.example/ws/secret".to_string());
         for output in [
-            show(&chain, Some("ethereum"), SecretVisibility::Redacted),
-            show_json(&chain, Some("ethereum"), SecretVisibility::Redacted).unwrap(),
+            show(
+                &chain,
+                Some("ethereum"),
+                SecretVisibility::Redacted,
+                &GLOBAL,
+            ),
+            show_json(
+                &chain,
+                Some("ethereum"),
+                SecretVisibility::Redacted,
+                &GLOBAL,
+            )
+            .unwrap(),
         ] {
             assert!(!output.contains("verification-secret"), "{output}");
             assert!(!output.contains("/secret"), "{output}");
         }
-        let output = show(&chain, Some("ethereum"), SecretVisibility::Redacted);
+        let output = show(
+            &chain,
+            Some("ethereum"),
+            SecretVisibility::Redacted,
+            &GLOBAL,
+        );
         assert!(output.contains("Configured"));
-        assert!(output.contains("Default: Yes"));
+        assert!(
+            output.contains("wss://provider.example/REDACTED"),
+            "{output}"
+        );
+        assert!(output.contains("Default: Yes (global)"));
+    }
+
+    #[test]
+    fn detail_view_reports_the_project_layer() {
+        let chain = chain("devnet", 1337, None);
+        let sources = Sources {
+            chain: ConfigLayer::Project,
+            chain_path: Some(PathBuf::from("/repo/.chainz.toml")),
+            default: ConfigLayer::Project,
+        };
+        let output = show(&chain, Some("devnet"), SecretVisibility::Redacted, &sources);
+        assert!(
+            output.contains("Source: project (/repo/.chainz.toml)"),
+            "{output}"
+        );
+        assert!(output.contains("Default: Yes (project)"), "{output}");
+
+        let json: serde_json::Value = serde_json::from_str(
+            &show_json(&chain, None, SecretVisibility::Redacted, &sources).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(json["source"], "project");
+        assert_eq!(json["source_path"], "/repo/.chainz.toml");
+        assert!(json.get("default_source").is_none());
     }
 
     #[test]
     fn revealed_view_preserves_explicit_show_secrets_behavior() {
         let chain = chain("ethereum", 1, None);
-        let json = show_json(&chain, None, SecretVisibility::Revealed).unwrap();
+        let json = show_json(&chain, None, SecretVisibility::Revealed, &GLOBAL).unwrap();
         assert!(json.contains("verification-secret"));
         assert!(json.contains("/v2/secret"));
     }
@@ -324,7 +570,7 @@ mod tests {
         let mut chain = chain("ethereum", 1, Some("default"));
         chain.verification_url = None;
         chain.verification_api_key = None;
-        let output = show(&chain, None, SecretVisibility::Redacted);
+        let output = show(&chain, None, SecretVisibility::Redacted, &GLOBAL);
 
         for expected in ["ethereum", "1", "provider.example", "default", "None"] {
             assert!(output.contains(expected), "{output}");
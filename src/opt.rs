@@ -1,4 +1,6 @@
+use crate::chain::capability::Capability;
 use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
 #[command(
@@ -63,6 +65,15 @@ pub enum Command {
         /// Show full details for every chain
         #[arg(long, conflicts_with = "json")]
         verbose: bool,
+        /// Only list testnets
+        #[arg(long, conflicts_with = "mainnets")]
+        testnets: bool,
+        /// Only list mainnets
+        #[arg(long)]
+        mainnets: bool,
+        /// Only list chains with all of these tags
+        #[arg(long = "tag", value_delimiter = ',', value_name = "TAGS")]
+        tags: Vec<String>,
     },
 
     /// Show one chain's full configuration
@@ -83,6 +94,44 @@ pub enum Command {
     Use {
         /// Chain name or ID (interactive picker if omitted)
         name_or_id: Option<String>,
+        /// Only offer chains with all of these tags in the picker
+        #[arg(
+            long = "tag",
+            value_delimiter = ',',
+            value_name = "TAGS",
+            conflicts_with = "name_or_id"
+        )]
+        tags: Vec<String>,
+    },
+
+    /// Manage profiles: named sets of chains with their own default and keys
+    ///
+    /// While a profile is active (`profile use`, or CHAINZ_PROFILE for one
+    /// shell), exec, shell, list, and doctor only see its chains.
+    ///
+    /// Example: chainz profile create testnets --chains sepolia,base-sepolia
+    Profile {
+        #[command(subcommand)]
+        cmd: ProfileCommand,
+    },
+
+    /// Label chains with free-form tags (e.g. `l2`, `prod`, `team-x`)
+    ///
+    /// list, doctor, exec, and use accept --tag to narrow to tagged chains.
+    ///
+    /// Example: chainz tag add base l2 prod
+    Tag {
+        #[command(subcommand)]
+        cmd: TagCommand,
+    },
+
+    /// Inspect recorded RPC health history
+    ///
+    /// Every RPC probe (doctor, the add/update picker, exec --failover) is
+    /// recorded in the local cache and used to rank endpoints.
+    Rpc {
+        #[command(subcommand)]
+        cmd: RpcCommand,
     },
 
     /// Check config health: key storage, key references, RPC connectivity
@@ -93,6 +142,27 @@ pub enum Command {
         /// Switch dead selected RPCs to a healthy configured alternative
         #[arg(long)]
         fix: bool,
+        /// Print every check as JSON (same as --format json)
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = DoctorFormat::Text)]
+        format: DoctorFormat,
+        /// Run only these sections or check IDs (e.g. `config,keys`)
+        #[arg(long, value_delimiter = ',', value_name = "CHECKS")]
+        only: Vec<String>,
+        /// Skip these sections or check IDs (e.g. `verification`)
+        #[arg(long, value_delimiter = ',', value_name = "CHECKS")]
+        skip: Vec<String>,
+        /// Check only these chains (names, aliases, or IDs)
+        #[arg(long = "chain", value_delimiter = ',', value_name = "CHAINS")]
+        chains: Vec<String>,
+        /// Check only chains with all of these tags
+        #[arg(long = "tag", value_delimiter = ',', value_name = "TAGS")]
+        tags: Vec<String>,
+        /// List every check ID and exit
+        #[arg(long, exclusive = true)]
+        list_checks: bool,
     },
 
     /// Execute a command with chain-specific variables expanded
@@ -104,11 +174,35 @@ pub enum Command {
     ///     @chainname  : Chain name
     ///     @key    : Private key
     ///
+    /// Use @{name} inside a word and @@ for a literal @.
+    ///
+    /// With --chains, --all, or --tag the command runs once per chain; each
+    /// output line is prefixed with the chain name and a summary follows.
+    ///
     /// Example: chainz exec ethereum -- cast balance @wallet
+    ///          chainz exec --chains base,optimism -j 4 -- cast block-number
+    ///          chainz exec --tag l2,prod -- cast block-number
     #[command(verbatim_doc_comment)]
     Exec {
         /// Chain name or ID to use (interactive picker if omitted)
         name_or_id: Option<String>,
+        /// Run once per listed chain (names, aliases, or IDs)
+        #[arg(long, value_delimiter = ',', conflicts_with_all = ["name_or_id", "all"])]
+        chains: Vec<String>,
+        /// Run once per configured chain (in the active profile)
+        #[arg(long, conflicts_with = "name_or_id")]
+        all: bool,
+        /// Run once per chain with all of these tags
+        #[arg(
+            long = "tag",
+            value_delimiter = ',',
+            value_name = "TAGS",
+            conflicts_with_all = ["name_or_id", "chains"]
+        )]
+        tags: Vec<String>,
+        /// How many chains to run at once with --chains, --all, or --tag
+        #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+        jobs: u16,
         /// Command to execute (after --)
         #[arg(last = true, required = true)]
         command: Vec<String>,
@@ -118,6 +212,30 @@ pub enum Command {
         /// Expose the selected private key as RAW_PRIVATE_KEY without adding it to argv
         #[arg(long)]
         expose_key: bool,
+        /// Serve ETH_RPC_URL from a local proxy that fails over across the
+        /// chain's RPCs
+        #[arg(long, conflicts_with_all = ["chains", "all", "tags"])]
+        failover: bool,
+    },
+
+    /// Serve a local JSON-RPC endpoint that signs with the chain's key
+    ///
+    /// Answers eth_accounts, eth_sendTransaction, eth_signTransaction,
+    /// eth_sign, personal_sign, and eth_signTypedData* in-process and
+    /// forwards everything else to the chain's RPC. Point tools at the
+    /// printed ETH_RPC_URL instead of handing them the private key.
+    ///
+    /// Example: chainz serve base --port 8545
+    #[command(verbatim_doc_comment)]
+    Serve {
+        /// Chain name or ID (default chain or interactive picker if omitted)
+        name_or_id: Option<String>,
+        /// Local port to listen on (127.0.0.1 only)
+        #[arg(short, long, default_value_t = 8545)]
+        port: u16,
+        /// Override the key to sign with
+        #[arg(short, long)]
+        key: Option<String>,
     },
 
     /// Open a subshell with the chain's environment loaded
@@ -144,6 +262,41 @@ pub enum Command {
         cmd: KeyCommand,
     },
 
+    /// Cache decrypted encrypted keys for this session
+    ///
+    /// Runs in the foreground, holding unlocked secrets in locked memory
+    /// until the TTL expires, `chainz key lock` runs, or the agent exits.
+    /// Other chainz commands use it automatically when it is running.
+    ///
+    /// Example: chainz agent --ttl 1h
+    #[command(verbatim_doc_comment)]
+    Agent {
+        /// How long each unlocked key stays cached (e.g. 900, 30m, 8h)
+        #[arg(long, default_value = "15m", value_parser = crate::agent::parse_ttl)]
+        ttl: std::time::Duration,
+    },
+
+    /// Print a chain's environment for CI, docker-compose, or Foundry
+    ///
+    /// Prints the same variables `exec` sets, minus the private key unless
+    /// --expose-key is given. --format foundry-toml instead emits
+    /// [rpc_endpoints] and [etherscan] sections for every configured chain.
+    ///
+    /// Example: chainz env base --format github-actions >> "$GITHUB_ENV"
+    Env {
+        /// Chain name or ID (default chain or interactive picker if omitted)
+        name_or_id: Option<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = EnvFormat::Dotenv)]
+        format: EnvFormat,
+        /// Override the key to use
+        #[arg(short, long)]
+        key: Option<String>,
+        /// Include the private key as RAW_PRIVATE_KEY
+        #[arg(long)]
+        expose_key: bool,
+    },
+
     /// Generate shell completions
     ///
     /// Example: chainz completions zsh > ~/.zfunc/_chainz
@@ -153,21 +306,51 @@ pub enum Command {
         shell: clap_complete::Shell,
     },
 
-    /// Manage global variables
+    /// Manage global and per-chain variables
     ///
     /// Variables can be used for dynamically creating RPC urls, setting environment variables, or
-    /// shell expansions
+    /// shell expansions. With --chain, a variable belongs to one chain and is exported by exec
+    /// as NAME and @NAME.
     ///
     /// Subcommands:
     ///     set   : Set or update a variable
     ///     get   : Get a variable's value
     ///     list  : List all variables
     ///     rm    : Remove a variable
+    ///     migrate : Move plaintext values into the OS keyring
+    ///     check : Show which variables each chain's URLs use and whether they resolve
+    ///
+    /// A value may instead reference a secret, resolved only when used:
+    ///     secret:keyring:var:<NAME>, secret:op://<vault>/<item>,
+    ///     secret:env:<NAME>, secret:cmd:<command>
     #[command(verbatim_doc_comment)]
     Var {
         #[command(subcommand)]
         cmd: VarCommand,
     },
+
+    /// Sign a message, EIP-712 typed data, or a transaction
+    ///
+    /// Signs with the chain's attached key (or --key) and prints only the
+    /// signature, so key material never leaves the chainz process.
+    ///
+    /// Subcommands:
+    ///     message     : EIP-191 personal message
+    ///     typed-data  : EIP-712 typed data from a JSON file
+    ///     tx          : Transaction request from a JSON file
+    ///
+    /// Example: chainz sign --chain base tx unsigned.json
+    #[command(verbatim_doc_comment)]
+    Sign {
+        /// Chain name or ID (default chain or interactive picker if omitted)
+        #[arg(short, long, global = true)]
+        chain: Option<String>,
+        /// Override the key to sign with
+        #[arg(short, long, global = true)]
+        key: Option<String>,
+        #[command(subcommand)]
+        cmd: SignCommand,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -175,6 +358,8 @@ pub enum KeyCommand {
     /// Add a new private key
     ///
     /// Use --stdin instead of --key in scripts to keep secrets out of argv.
+    /// With --mnemonic, the input is a BIP-39 seed phrase and the key
+    /// resolves to the HD account at --index.
     Add {
         /// Name for the private key
         name: String,
@@ -187,6 +372,37 @@ pub enum KeyCommand {
         /// How to store the key (safe OS-keyring/encrypted default if omitted)
         #[arg(long = "type", value_enum)]
         key_type: Option<KeyTypeArg>,
+        /// Store a BIP-39 mnemonic instead of a single private key
+        #[arg(long, conflicts_with = "key")]
+        mnemonic: bool,
+        /// Account index to derive from the mnemonic
+        #[arg(long, requires = "mnemonic", default_value_t = 0)]
+        index: u32,
+        /// HD derivation path prefix; the account index is appended
+        #[arg(long, requires = "mnemonic", default_value = crate::key::DEFAULT_DERIVATION_PATH)]
+        derivation_path: String,
+    },
+    /// Generate a new random private key and store it directly
+    ///
+    /// The secret never touches argv, a pipe, or the terminal; only the
+    /// address is printed.
+    Generate {
+        /// Name for the generated key
+        name: String,
+        /// How to store the key (safe OS-keyring/encrypted default if omitted)
+        #[arg(long = "type", value_enum)]
+        key_type: Option<KeyTypeArg>,
+    },
+    /// List addresses derived from a mnemonic key
+    Derive {
+        /// Name of the mnemonic key
+        name: String,
+        /// Number of accounts to derive
+        #[arg(long, default_value_t = 10)]
+        count: u32,
+        /// First account index to derive
+        #[arg(long, default_value_t = 0)]
+        start: u32,
     },
     /// List all stored private keys
     List {
@@ -213,6 +429,85 @@ pub enum KeyCommand {
         #[arg(long, value_enum)]
         to: Option<MigrationTargetArg>,
     },
+    /// Change the password of an encrypted key
+    ///
+    /// Prompts for the current password, then writes a new envelope with a
+    /// fresh salt and nonce under the new password.
+    Passwd {
+        /// Name of the encrypted key
+        name: String,
+    },
+    /// Re-encrypt keys with a fresh salt, nonce, and KDF parameters
+    ///
+    /// Keeps each key's password. With --all, every encrypted key is
+    /// rewritten in one transaction; any failure leaves all of them unchanged.
+    Rekey {
+        /// Key name to re-encrypt (omit when using --all)
+        name: Option<String>,
+        /// Re-encrypt every password-encrypted key
+        #[arg(long, conflicts_with = "name")]
+        all: bool,
+        /// Argon2 memory cost in KiB (default: keep the stronger of current and default)
+        #[arg(long)]
+        kdf_memory: Option<u32>,
+        /// Argon2 iteration count (default: keep the stronger of current and default)
+        #[arg(long)]
+        kdf_iterations: Option<u32>,
+        /// Accept --kdf-memory/--kdf-iterations below a key's current cost
+        #[arg(long)]
+        allow_weaker: bool,
+    },
+    /// Import a Web3 Secret Storage (keystore v3) JSON file
+    ///
+    /// Accepts scrypt and pbkdf2 keystores such as those written by geth,
+    /// `cast wallet`, or foundry's ~/.foundry/keystores.
+    ImportKeystore {
+        /// Path to the keystore JSON file
+        file: PathBuf,
+        /// Name for the imported key (defaults to the file name)
+        #[arg(long)]
+        name: Option<String>,
+        /// How to store the key (safe OS-keyring/encrypted default if omitted)
+        #[arg(long = "type", value_enum)]
+        key_type: Option<KeyTypeArg>,
+    },
+    /// Export a stored key as a Web3 Secret Storage (keystore v3) JSON file
+    ExportKeystore {
+        /// Name of the key to export
+        name: String,
+        /// Destination file (must not already exist)
+        file: PathBuf,
+    },
+    /// Wipe every secret cached by a running `chainz agent`
+    Lock,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum EnvFormat {
+    /// KEY='value' lines for .env and docker-compose env files
+    Dotenv,
+    /// POSIX `export` statements
+    Sh,
+    /// fish `set -gx` statements
+    Fish,
+    /// A JSON object
+    Json,
+    /// Lines for appending to $GITHUB_ENV
+    GithubActions,
+    /// foundry.toml sections covering every chain
+    FoundryToml,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DoctorFormat {
+    /// Sections and glyphs, printed as checks complete
+    Text,
+    /// Every check with its stable ID, chain, endpoint, and severity
+    Json,
+    /// SARIF 2.1.0 for code-scanning dashboards
+    Sarif,
+    /// JUnit XML for CI test reports
+    Junit,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
@@ -234,6 +529,31 @@ pub enum KeyTypeArg {
     Keyring,
 }
 
+#[derive(Debug, Subcommand)]
+pub enum SignCommand {
+    /// Sign an EIP-191 personal message and print the signature
+    Message {
+        /// Message to sign (read from stdin if omitted)
+        message: Option<String>,
+        /// Treat the message as 0x-prefixed hex bytes instead of text
+        #[arg(long)]
+        hex: bool,
+    },
+    /// Sign EIP-712 typed data and print the signature
+    TypedData {
+        /// JSON file with types, primaryType, domain, and message ("-" for stdin)
+        file: PathBuf,
+    },
+    /// Sign a transaction request and print the raw signed transaction
+    ///
+    /// The JSON uses eth_sendTransaction field names and must include the
+    /// nonce and gas fields; chainId defaults to the selected chain.
+    Tx {
+        /// Transaction request JSON file ("-" for stdin)
+        file: PathBuf,
+    },
+}
+
 #[derive(Debug, Subcommand)]
 pub enum VarCommand {
     /// Set or update a variable
@@ -245,6 +565,9 @@ pub enum VarCommand {
         /// Read the value from stdin instead of argv
         #[arg(long)]
         stdin: bool,
+        /// Set the variable for one chain instead of globally
+        #[arg(long)]
+        chain: Option<String>,
     },
     /// Get a variable's value
     Get {
@@ -253,6 +576,9 @@ pub enum VarCommand {
         /// Print the stored value instead of a redacted marker
         #[arg(long)]
         show: bool,
+        /// Read one chain's variable instead of a global one
+        #[arg(long)]
+        chain: Option<String>,
     },
     /// List all variables
     List {
@@ -262,12 +588,116 @@ pub enum VarCommand {
         /// Output names and values as JSON (explicitly reveals values)
         #[arg(long, conflicts_with = "show")]
         json: bool,
+        /// List one chain's variables instead of the global ones
+        #[arg(long)]
+        chain: Option<String>,
     },
     /// Remove a variable
     #[command(alias = "rm")]
     Remove {
         /// Variable name
         name: String,
+        /// Remove one chain's variable instead of a global one
+        #[arg(long)]
+        chain: Option<String>,
+    },
+    /// Move plaintext global variables into the OS keyring, leaving
+    /// `secret:keyring:var:<NAME>` references in the config
+    Migrate {
+        /// Variable to migrate (omit when using --all)
+        name: Option<String>,
+        /// Migrate every plaintext variable; individual failures are reported and skipped
+        #[arg(long, conflicts_with = "name")]
+        all: bool,
+    },
+    /// Show the variables each chain's URLs reference and whether they
+    /// resolve; exits nonzero if any does not
+    Check {
+        /// Chain name or ID (every chain in the active profile if omitted)
+        name_or_id: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ProfileCommand {
+    /// Create a profile from existing chains
+    Create {
+        /// Profile name
+        name: String,
+        /// Member chains (names, aliases, or IDs)
+        #[arg(long, value_delimiter = ',', required = true)]
+        chains: Vec<String>,
+        /// Default chain within the profile
+        #[arg(long)]
+        default: Option<String>,
+        /// Use a different key for a chain in this profile (CHAIN=KEY)
+        #[arg(long = "key", value_name = "CHAIN=KEY")]
+        keys: Vec<String>,
+    },
+    /// Activate a profile
+    Use {
+        /// Profile name
+        name: String,
+    },
+    /// Deactivate the active profile
+    Off,
+    /// List profiles, marking the active one
+    List,
+    /// Remove a profile (its chains are kept)
+    #[command(alias = "rm")]
+    Remove {
+        /// Profile name
+        name: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum TagCommand {
+    /// Add tags to a chain
+    Add {
+        /// Chain name, alias, or ID
+        name_or_id: String,
+        /// Tags to add
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// Remove tags from a chain
+    #[command(alias = "rm")]
+    Remove {
+        /// Chain name, alias, or ID
+        name_or_id: String,
+        /// Tags to remove
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// List tags with the chains that carry them
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum RpcCommand {
+    /// Run extended checks (freshness, archive, logs range, debug/trace,
+    /// batch) on each of a chain's RPCs and record capability tags
+    Probe {
+        /// Chain name or ID
+        name_or_id: String,
+        /// Capabilities an endpoint must have to match
+        #[arg(long, value_delimiter = ',', value_name = "CAPS")]
+        require: Vec<Capability>,
+        /// Make the best matching endpoint the chain's selected RPC
+        #[arg(long)]
+        select: bool,
+        /// Output as JSON (for scripting)
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show uptime and p50/p95 latency for each of a chain's RPCs
+    Stats {
+        /// Chain name or ID
+        name_or_id: String,
+        /// Output as JSON (for scripting)
+        #[arg(long)]
+        json: bool,
     },
 }
 
@@ -288,6 +718,18 @@ pub struct UpdateArgs {
     #[arg(long)]
     pub rpc_url: Option<String>,
 
+    /// Select and add a WebSocket RPC URL (ws:// or wss://)
+    #[arg(long, conflicts_with = "no_ws")]
+    pub ws_url: Option<String>,
+
+    /// Stop exporting a WebSocket URL for the chain
+    #[arg(long)]
+    pub no_ws: bool,
+
+    /// Mark the chain as a testnet (true) or mainnet (false)
+    #[arg(long, value_name = "BOOL")]
+    pub testnet: Option<bool>,
+
     /// Attach a stored key
     #[arg(long, conflicts_with = "no_key")]
     pub key: Option<String>,
@@ -334,6 +776,10 @@ pub struct AddArgs {
     #[arg(long)]
     pub rpc_url: Option<String>,
 
+    /// WebSocket RPC URL (ws:// or wss://)
+    #[arg(long)]
+    pub ws_url: Option<String>,
+
     /// Key name (omit for an RPC-only chain)
     #[arg(long)]
     pub key: Option<String>,
@@ -0,0 +1,703 @@
+//! `chainz serve`: a local JSON-RPC proxy that signs with the chain's key.
+//!
+//! Account and signing methods are answered in-process; every other call is
+//! forwarded to the chain's selected RPC. Tools get a keyless `ETH_RPC_URL`
+//! and the key itself never leaves this process.
+//!
+//! Anything on the machine can open a localhost port, including web pages
+//! through simple cross-origin POSTs and DNS rebinding. The proxy therefore
+//! answers only on a random per-run path, refuses requests that carry an
+//! `Origin` or a foreign `Host`, and requires a JSON content type, which a
+//! browser cannot send cross-origin without a preflight.
+
+use crate::sign::{TransactionRequest, sign_message, sign_request, sign_typed_data};
+use alloy::{
+    primitives::{Address, hex},
+    signers::local::PrivateKeySigner,
+};
+use anyhow::{Context, Result, anyhow};
+use serde_json::{Value, json};
+use std::{future::Future, net::SocketAddr, sync::Arc};
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::Mutex,
+};
+
+/// Requests larger than this are rejected rather than buffered.
+const MAX_BODY_BYTES: usize = 8 * 1024 * 1024;
+/// Longest request or header line read before answering 400 or 431.
+const MAX_LINE_BYTES: u64 = 4 * 1024;
+/// Most header lines read before answering 431.
+const MAX_HEADERS: usize = 64;
+
+/// A random path segment for [`serve_http`] and [`url`], so only tools
+/// handed the URL can reach the proxy.
+pub(crate) fn new_token() -> String {
+    hex::encode(rand::random::<[u8; 16]>())
+}
+
+/// The URL tools use to reach a proxy served on `listener`.
+pub(crate) fn url(listener: &TcpListener, token: &str) -> Result<String> {
+    Ok(format!("http://{}/{}", listener.local_addr()?, token))
+}
+
+/// Answers JSON-RPC request bodies for [`serve_http`].
+pub(crate) trait RpcHandler: Send + Sync + 'static {
+    fn handle_body(&self, body: &[u8]) -> impl Future<Output = Value> + Send;
+}
+
+/// What a request must match to be answered.
+struct Access {
+    /// `/<token>`
+    path: String,
+    /// The `Host` values a direct localhost client sends
+    hosts: [String; 2],
+}
+
+impl Access {
+    fn allows_host(&self, host: &str) -> bool {
+        self.hosts
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(host))
+    }
+}
+
+/// Serve HTTP JSON-RPC on `listener` at `/<token>` until the task is
+/// dropped. The HTTP side is deliberately minimal: POST with Content-Length,
+/// one request per connection.
+pub(crate) async fn serve_http<H: RpcHandler>(
+    handler: Arc<H>,
+    listener: TcpListener,
+    token: String,
+) -> Result<()> {
+    let port = listener.local_addr()?.port();
+    let access = Arc::new(Access {
+        path: format!("/{}", token),
+        hosts: [format!("127.0.0.1:{}", port), format!("localhost:{}", port)],
+    });
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let handler = Arc::clone(&handler);
+        let access = Arc::clone(&access);
+        tokio::spawn(async move {
+            let _ = handle_connection(&*handler, stream, &access).await;
+        });
+    }
+}
+
+async fn handle_connection(
+    handler: &impl RpcHandler,
+    stream: TcpStream,
+    access: &Access,
+) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let mut content_length = 0;
+    let mut has_origin = false;
+    let mut host = None;
+    let mut json_body = false;
+    let mut oversized = None;
+    let mut line = String::new();
+    if !read_bounded_line(&mut reader, &mut line).await? {
+        oversized = Some(("400 Bad Request", "Request line too long"));
+    }
+    let mut request_line = line.split_whitespace();
+    let is_post = request_line.next() == Some("POST");
+    let path_matches = request_line.next() == Some(access.path.as_str());
+    let mut headers = 0;
+    while oversized.is_none() {
+        if !read_bounded_line(&mut reader, &mut line).await? {
+            oversized = Some((
+                "431 Request Header Fields Too Large",
+                "Header line too long",
+            ));
+            break;
+        }
+        if line.is_empty() || line == "\r\n" || line == "\n" {
+            break;
+        }
+        headers += 1;
+        if headers > MAX_HEADERS {
+            oversized = Some(("431 Request Header Fields Too Large", "Too many headers"));
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (name, value) = (name.trim(), value.trim());
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse().context("Invalid Content-Length")?;
+        } else if name.eq_ignore_ascii_case("origin") {
+            has_origin = true;
+        } else if name.eq_ignore_ascii_case("host") {
+            host = Some(value.to_string());
+        } else if name.eq_ignore_ascii_case("content-type") {
+            json_body = value
+                .split(';')
+                .next()
+                .is_some_and(|media| media.trim().eq_ignore_ascii_case("application/json"));
+        }
+    }
+    let (status, body) = if let Some((status, message)) = oversized {
+        (status, error(Value::Null, -32600, message))
+    } else if !is_post {
+        (
+            "405 Method Not Allowed",
+            error(Value::Null, -32600, "Use POST"),
+        )
+    } else if !path_matches {
+        ("404 Not Found", error(Value::Null, -32600, "Not found"))
+    } else if has_origin {
+        (
+            "403 Forbidden",
+            error(Value::Null, -32600, "Browser requests are not allowed"),
+        )
+    } else if !host.is_some_and(|host| access.allows_host(&host)) {
+        (
+            "403 Forbidden",
+            error(Value::Null, -32600, "Invalid Host header"),
+        )
+    } else if !json_body {
+        (
+            "415 Unsupported Media Type",
+            error(Value::Null, -32600, "Use Content-Type: application/json"),
+        )
+    } else if content_length > MAX_BODY_BYTES {
+        (
+            "413 Payload Too Large",
+            error(Value::Null, -32600, "Request too large"),
+        )
+    } else {
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).await?;
+        ("200 OK", handler.handle_body(&body).await)
+    };
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    writer.write_all(response.as_bytes()).await?;
+    writer.shutdown().await?;
+    Ok(())
+}
+
+/// Read one line into `line`, returning `false` if it runs past
+/// [`MAX_LINE_BYTES`] without ending.
+async fn read_bounded_line(
+    reader: &mut (impl AsyncBufRead + Unpin),
+    line: &mut String,
+) -> Result<bool> {
+    line.clear();
+    let read = reader.take(MAX_LINE_BYTES).read_line(line).await?;
+    Ok((read as u64) < MAX_LINE_BYTES || line.ends_with('\n'))
+}
+
+pub(crate) struct Proxy {
+    signer: PrivateKeySigner,
+    chain_id: u64,
+    upstream: String,
+    client: reqwest::Client,
+    /// Serializes nonce selection so concurrent sends don't collide.
+    send_lock: Mutex<()>,
+}
+
+impl Proxy {
+    pub(crate) fn new(signer: PrivateKeySigner, chain_id: u64, upstream: String) -> Self {
+        Self {
+            signer,
+            chain_id,
+            upstream,
+            client: reqwest::Client::new(),
+            send_lock: Mutex::new(()),
+        }
+    }
+
+    pub(crate) fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    async fn handle_call(&self, call: Value) -> Value {
+        let id = call.get("id").cloned().unwrap_or(Value::Null);
+        let Some(method) = call.get("method").and_then(Value::as_str) else {
+            return error(id, -32600, "Invalid request");
+        };
+        let params = call.get("params").cloned().unwrap_or(json!([]));
+        let result = match method {
+            "eth_accounts" | "eth_requestAccounts" => Ok(json!([self.address()])),
+            "eth_sign" => self.sign_message_param(&params, 1, 0),
+            "personal_sign" => self.sign_message_param(&params, 0, 1),
+            "eth_signTypedData" | "eth_signTypedData_v3" | "eth_signTypedData_v4" => {
+                self.sign_typed_data_param(&params)
+            }
+            "eth_signTransaction" => self
+                .sign_transaction_param(&params)
+                .await
+                .map(|raw| json!(raw)),
+            "eth_sendTransaction" => self.send_transaction(&params).await,
+            _ => return self.forward(&call).await,
+        };
+        match result {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err(err) => error(id, -32000, &format!("{:#}", err)),
+        }
+    }
+
+    fn check_account(&self, value: Option<&Value>) -> Result<()> {
+        let address: Address = serde_json::from_value(value.cloned().unwrap_or_default())
+            .context("Missing or invalid account parameter")?;
+        if address != self.address() {
+            anyhow::bail!(
+                "Unknown account {}; this proxy signs as {}",
+                address,
+                self.address()
+            );
+        }
+        Ok(())
+    }
+
+    fn sign_message_param(&self, params: &Value, data: usize, account: usize) -> Result<Value> {
+        self.check_account(params.get(account))?;
+        let data = params
+            .get(data)
+            .and_then(Value::as_str)
+            .ok_or(anyhow!("Missing message parameter"))?;
+        // Wallets treat 0x-prefixed messages as bytes and anything else as text.
+        let bytes = match data.strip_prefix("0x") {
+            Some(_) => hex::decode(data).context("Message is not valid hex")?,
+            None => data.as_bytes().to_vec(),
+        };
+        Ok(json!(sign_message(&self.signer, &bytes)?))
+    }
+
+    fn sign_typed_data_param(&self, params: &Value) -> Result<Value> {
+        self.check_account(params.get(0))?;
+        let typed_data = match params.get(1) {
+            Some(Value::String(json)) => json.clone(),
+            Some(object) => object.to_string(),
+            None => anyhow::bail!("Missing typed data parameter"),
+        };
+        Ok(json!(sign_typed_data(&self.signer, &typed_data)?))
+    }
+
+    async fn sign_transaction_param(&self, params: &Value) -> Result<String> {
+        let request: TransactionRequest =
+            serde_json::from_value(params.get(0).cloned().unwrap_or_default())
+                .context("Missing or invalid transaction parameter")?;
+        let request = self.fill(request).await?;
+        Ok(hex::encode_prefixed(sign_request(
+            &self.signer,
+            request,
+            self.chain_id,
+        )?))
+    }
+
+    async fn send_transaction(&self, params: &Value) -> Result<Value> {
+        let _guard = self.send_lock.lock().await;
+        let raw = self.sign_transaction_param(params).await?;
+        self.call("eth_sendRawTransaction", json!([raw])).await
+    }
+
+    /// Fill the fields tools usually leave to the wallet: sender, nonce,
+    /// fees, and gas.
+    async fn fill(&self, mut request: TransactionRequest) -> Result<TransactionRequest> {
+        let address = self.address();
+        if let Some(from) = request.from
+            && from != address
+        {
+            anyhow::bail!("Unknown account {}; this proxy signs as {}", from, address);
+        }
+        request.from = Some(address);
+        request.chain_id.get_or_insert(self.chain_id);
+        if request.nonce.is_none() {
+            let nonce = self
+                .call("eth_getTransactionCount", json!([address, "pending"]))
+                .await?;
+            request.nonce = Some(quantity(&nonce)? as u64);
+        }
+        if request.gas_price.is_none() && request.max_fee_per_gas.is_none() {
+            let block = self
+                .call("eth_getBlockByNumber", json!(["latest", false]))
+                .await?;
+            match block.get("baseFeePerGas") {
+                Some(base_fee) => {
+                    let base_fee = quantity(base_fee)?;
+                    let tip = match request.max_priority_fee_per_gas {
+                        Some(tip) => tip,
+                        None => quantity(&self.call("eth_maxPriorityFeePerGas", json!([])).await?)?,
+                    };
+                    request.max_priority_fee_per_gas = Some(tip);
+                    request.max_fee_per_gas = Some(base_fee * 2 + tip);
+                }
+                None => {
+                    request.gas_price =
+                        Some(quantity(&self.call("eth_gasPrice", json!([])).await?)?);
+                }
+            }
+        }
+        if request.gas.is_none() {
+            let estimate = self.call("eth_estimateGas", json!([request])).await?;
+            request.gas = Some(quantity(&estimate)? as u64);
+        }
+        Ok(request)
+    }
+
+    /// Call the upstream RPC and unwrap its result.
+    async fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let mut response = self
+            .post(&json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params}))
+            .await?;
+        if let Some(err) = response.get("error") {
+            anyhow::bail!(
+                "{} failed: {}",
+                method,
+                err.get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or("unknown error")
+            );
+        }
+        Ok(response["result"].take())
+    }
+
+    async fn forward(&self, call: &Value) -> Value {
+        let id = call.get("id").cloned().unwrap_or(Value::Null);
+        match self.post(call).await {
+            Ok(response) => response,
+            Err(err) => error(id, -32603, &format!("{:#}", err)),
+        }
+    }
+
+    async fn post(&self, body: &Value) -> Result<Value> {
+        // Upstream errors can echo the URL, which may embed API keys.
+        self.client
+            .post(&self.upstream)
+            .json(body)
+            .send()
+            .await
+            .map_err(|_| anyhow!("Upstream RPC request failed"))?
+            .json()
+            .await
+            .map_err(|_| anyhow!("Upstream RPC returned invalid JSON"))
+    }
+}
+
+impl RpcHandler for Proxy {
+    /// Handle a single call or a batch.
+    async fn handle_body(&self, body: &[u8]) -> Value {
+        match serde_json::from_slice::<Value>(body) {
+            Ok(Value::Array(calls)) => {
+                let mut responses = Vec::with_capacity(calls.len());
+                for call in calls {
+                    responses.push(self.handle_call(call).await);
+                }
+                Value::Array(responses)
+            }
+            Ok(call) => self.handle_call(call).await,
+            Err(_) => error(Value::Null, -32700, "Parse error"),
+        }
+    }
+}
+
+pub(crate) fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+fn quantity(value: &Value) -> Result<u128> {
+    let text = value.as_str().ok_or(anyhow!("Expected a hex quantity"))?;
+    u128::from_str_radix(text.trim_start_matches("0x"), 16)
+        .with_context(|| format!("Invalid hex quantity '{}'", text))
+}
+
+/// Bind the proxy on localhost and serve until ctrl-c.
+pub async fn run(proxy: Proxy, chain_name: &str, port: u16) -> Result<()> {
+    let address = SocketAddr::from(([127, 0, 0, 1], port));
+    let listener = TcpListener::bind(address)
+        .await
+        .with_context(|| format!("Failed to listen on {}; try --port", address))?;
+    let token = new_token();
+    let url = url(&listener, &token)?;
+    eprintln!(
+        "Serving {} as {} — ctrl-c to stop",
+        chain_name,
+        proxy.address()
+    );
+    println!("export ETH_RPC_URL={}", url);
+    tokio::select! {
+        result = serve_http(Arc::new(proxy), listener, token) => result,
+        result = tokio::signal::ctrl_c() => result.map_err(Into::into),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+
+    /// Serve HTTP on a random localhost port, answering each request with
+    /// `respond(request)` as JSON, and return the base URL. The request is
+    /// the JSON body, or the query parameters as an object when there is
+    /// none. A `null` answer is sent as 429 Too Many Requests.
+    pub(crate) async fn mock_http(
+        respond: impl Fn(Value) -> Value + Send + Sync + 'static,
+    ) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let respond = Arc::new(respond);
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let respond = Arc::clone(&respond);
+                tokio::spawn(async move {
+                    let (reader, mut writer) = stream.into_split();
+                    let mut reader = BufReader::new(reader);
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).await.unwrap();
+                    let mut length = 0;
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).await.unwrap() > 0 && line != "\r\n" {
+                        if let Some(value) =
+                            line.to_ascii_lowercase().strip_prefix("content-length:")
+                        {
+                            length = value.trim().parse().unwrap();
+                        }
+                        line.clear();
+                    }
+                    let request = if length > 0 {
+                        let mut body = vec![0; length];
+                        reader.read_exact(&mut body).await.unwrap();
+                        serde_json::from_slice(&body).unwrap()
+                    } else {
+                        let target = request_line.split_whitespace().nth(1).unwrap();
+                        let url = reqwest::Url::parse(&format!("http://mock{}", target)).unwrap();
+                        url.query_pairs()
+                            .map(|(name, value)| (name.into_owned(), json!(value)))
+                            .collect::<serde_json::Map<_, _>>()
+                            .into()
+                    };
+                    let (status, body) = match respond(request) {
+                        Value::Null => ("429 Too Many Requests", String::new()),
+                        answer => ("200 OK", answer.to_string()),
+                    };
+                    let response = format!(
+                        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        body.len(),
+                        body
+                    );
+                    writer.write_all(response.as_bytes()).await.unwrap();
+                });
+            }
+        });
+        url
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::{
+        consensus::TxEnvelope,
+        network::eip2718::Decodable2718,
+        primitives::{Signature, eip191_hash_message},
+    };
+    use std::sync::Mutex as StdMutex;
+
+    const TEST_PRIVATE_KEY: &str =
+        "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    /// Answer the fill-in calls with fixed values and record every method.
+    async fn mock_upstream(calls: Arc<StdMutex<Vec<Value>>>) -> String {
+        testing::mock_http(move |call| {
+            let result = match call["method"].as_str().unwrap() {
+                "eth_getTransactionCount" => json!("0x7"),
+                "eth_getBlockByNumber" => json!({"baseFeePerGas": "0x64"}),
+                "eth_maxPriorityFeePerGas" => json!("0x2"),
+                "eth_estimateGas" => json!("0x5208"),
+                "eth_sendRawTransaction" => json!("0xabc"),
+                "eth_blockNumber" => json!("0x10"),
+                other => panic!("unexpected upstream call {}", other),
+            };
+            calls.lock().unwrap().push(call.clone());
+            json!({"jsonrpc": "2.0", "id": call["id"], "result": result})
+        })
+        .await
+    }
+
+    async fn proxy() -> (Proxy, Arc<StdMutex<Vec<Value>>>) {
+        let calls = Arc::new(StdMutex::new(Vec::new()));
+        let upstream = mock_upstream(Arc::clone(&calls)).await;
+        let proxy = Proxy::new(TEST_PRIVATE_KEY.parse().unwrap(), 31337, upstream);
+        (proxy, calls)
+    }
+
+    async fn call(proxy: &Proxy, method: &str, params: Value) -> Value {
+        let body = json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params});
+        proxy.handle_body(body.to_string().as_bytes()).await
+    }
+
+    #[tokio::test]
+    async fn accounts_and_signatures_are_answered_locally() {
+        let (proxy, calls) = proxy().await;
+        let address = proxy.address();
+        assert_eq!(
+            call(&proxy, "eth_accounts", json!([])).await["result"],
+            json!([address])
+        );
+
+        let response = call(&proxy, "personal_sign", json!(["hello", address])).await;
+        let signature = hex::decode(response["result"].as_str().unwrap()).unwrap();
+        let signature = Signature::try_from(signature.as_slice()).unwrap();
+        assert_eq!(
+            signature
+                .recover_address_from_prehash(&eip191_hash_message(b"hello"))
+                .unwrap(),
+            address
+        );
+
+        let response = call(&proxy, "eth_sign", json!([Address::ZERO, "0x00"])).await;
+        assert!(
+            response["error"]["message"]
+                .as_str()
+                .unwrap()
+                .contains("Unknown account")
+        );
+        assert!(calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn send_transaction_fills_signs_and_forwards() {
+        let (proxy, calls) = proxy().await;
+        let to = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+        let response = call(
+            &proxy,
+            "eth_sendTransaction",
+            json!([{"to": to, "value": "0x1"}]),
+        )
+        .await;
+        assert_eq!(response["result"], json!("0xabc"));
+
+        let calls = calls.lock().unwrap();
+        let raw = calls
+            .iter()
+            .find(|call| call["method"] == "eth_sendRawTransaction")
+            .unwrap()["params"][0]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let envelope = TxEnvelope::decode_2718(&mut hex::decode(raw).unwrap().as_slice()).unwrap();
+        let signed = envelope.as_eip1559().unwrap();
+        assert_eq!(signed.tx().nonce, 7);
+        assert_eq!(signed.tx().gas_limit, 21000);
+        assert_eq!(signed.tx().max_fee_per_gas, 202);
+        assert_eq!(signed.tx().chain_id, 31337);
+        assert_eq!(
+            signed
+                .signature()
+                .recover_address_from_prehash(&signed.signature_hash())
+                .unwrap(),
+            proxy.address()
+        );
+    }
+
+    #[tokio::test]
+    async fn other_methods_are_forwarded_over_http() {
+        let (proxy, _) = proxy().await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = url(&listener, "t0ken").unwrap();
+        tokio::spawn(serve_http(Arc::new(proxy), listener, "t0ken".into()));
+
+        let response: Value = reqwest::Client::new()
+            .post(&url)
+            .json(&json!([
+                {"jsonrpc": "2.0", "id": 1, "method": "eth_blockNumber", "params": []},
+                {"jsonrpc": "2.0", "id": 2, "method": "eth_accounts", "params": []},
+            ]))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(response[0]["result"], json!("0x10"));
+        assert_eq!(response[1]["id"], json!(2));
+    }
+
+    /// Send `headers` with a small JSON-RPC body and return the status line.
+    async fn raw_status(address: SocketAddr, path: &str, headers: &str) -> String {
+        let body = r#"{"jsonrpc":"2.0","id":1,"method":"eth_accounts","params":[]}"#;
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        let request = format!(
+            "POST {} HTTP/1.1\r\n{}Content-Length: {}\r\n\r\n{}",
+            path,
+            headers,
+            body.len(),
+            body
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response.lines().next().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn browser_and_foreign_requests_are_refused() {
+        let (proxy, _) = proxy().await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(serve_http(Arc::new(proxy), listener, "t0ken".into()));
+        let host = format!("Host: 127.0.0.1:{}\r\n", address.port());
+        let json = "Content-Type: application/json\r\n";
+
+        let ok = format!("{host}{json}");
+        assert_eq!(raw_status(address, "/t0ken", &ok).await, "HTTP/1.1 200 OK");
+        let localhost = format!("Host: localhost:{}\r\n{json}", address.port());
+        assert_eq!(
+            raw_status(address, "/t0ken", &localhost).await,
+            "HTTP/1.1 200 OK"
+        );
+
+        assert_eq!(
+            raw_status(address, "/", &ok).await,
+            "HTTP/1.1 404 Not Found",
+            "the token is required"
+        );
+        let origin = format!("{host}{json}Origin: https://evil.example\r\n");
+        assert_eq!(
+            raw_status(address, "/t0ken", &origin).await,
+            "HTTP/1.1 403 Forbidden"
+        );
+        let rebound = format!("Host: evil.example:{}\r\n{json}", address.port());
+        assert_eq!(
+            raw_status(address, "/t0ken", &rebound).await,
+            "HTTP/1.1 403 Forbidden"
+        );
+        assert_eq!(
+            raw_status(address, "/t0ken", json).await,
+            "HTTP/1.1 403 Forbidden",
+            "a Host header is required"
+        );
+        let simple = format!("{host}Content-Type: text/plain\r\n");
+        assert_eq!(
+            raw_status(address, "/t0ken", &simple).await,
+            "HTTP/1.1 415 Unsupported Media Type"
+        );
+
+        let long = format!("{ok}X-Padding: {}\r\n", "a".repeat(5000));
+        assert_eq!(
+            raw_status(address, "/t0ken", &long).await,
+            "HTTP/1.1 431 Request Header Fields Too Large"
+        );
+        let many = format!("{ok}{}", "X-Filler: 1\r\n".repeat(MAX_HEADERS));
+        assert_eq!(
+            raw_status(address, "/t0ken", &many).await,
+            "HTTP/1.1 431 Request Header Fields Too Large"
+        );
+        let path = format!("/{}", "a".repeat(5000));
+        assert_eq!(
+            raw_status(address, &path, &ok).await,
+            "HTTP/1.1 400 Bad Request"
+        );
+    }
+}
@@ -0,0 +1,227 @@
+//! `chainz sign`: sign messages, typed data, and transactions in-process.
+//!
+//! The resolved key only ever becomes a local signer; commands print the
+//! signature or signed transaction, never the key itself.
+
+use crate::{chain::ChainInstance, opt::SignCommand};
+use alloy::{
+    dyn_abi::TypedData,
+    network::{Ethereum, Network, TxSignerSync, eip2718::Encodable2718},
+    primitives::hex,
+    signers::{SignerSync, local::PrivateKeySigner},
+};
+use anyhow::{Context, Result};
+use std::{io::Read, path::Path};
+
+pub(crate) type TransactionRequest = <Ethereum as Network>::TransactionRequest;
+
+impl SignCommand {
+    pub fn handle(self, chain: &ChainInstance) -> Result<()> {
+        let key = chain.key.as_ref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Chain '{}' has no key attached; use `chainz update {} --key <name>` or --key",
+                chain.definition.name,
+                chain.definition.name
+            )
+        })?;
+        let input = match &self {
+            SignCommand::Message { message, .. } => match message {
+                Some(message) => message.clone(),
+                None => read_input(Path::new("-"))?,
+            },
+            SignCommand::TypedData { file } | SignCommand::Tx { file } => read_input(file)?,
+        };
+        let signer: PrivateKeySigner = key.private_key()?.parse()?;
+        let output = match self {
+            SignCommand::Message { hex: true, .. } => sign_message(
+                &signer,
+                &hex::decode(input.trim()).context("Message is not valid hex")?,
+            )?,
+            SignCommand::Message { .. } => sign_message(&signer, input.as_bytes())?,
+            SignCommand::TypedData { .. } => sign_typed_data(&signer, &input)?,
+            SignCommand::Tx { .. } => sign_transaction(&signer, &input, chain.definition.chain_id)?,
+        };
+        println!("{}", output);
+        Ok(())
+    }
+}
+
+fn read_input(path: &Path) -> Result<String> {
+    let mut input = String::new();
+    if path == Path::new("-") {
+        std::io::stdin()
+            .read_to_string(&mut input)
+            .context("Failed to read stdin")?;
+        input.truncate(input.trim_end_matches(['\r', '\n']).len());
+    } else {
+        input = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+    }
+    Ok(input)
+}
+
+pub(crate) fn sign_message(signer: &PrivateKeySigner, message: &[u8]) -> Result<String> {
+    Ok(hex::encode_prefixed(
+        signer.sign_message_sync(message)?.as_bytes(),
+    ))
+}
+
+pub(crate) fn sign_typed_data(signer: &PrivateKeySigner, json: &str) -> Result<String> {
+    let typed_data: TypedData =
+        serde_json::from_str(json).context("Failed to parse EIP-712 typed data")?;
+    Ok(hex::encode_prefixed(
+        signer.sign_dynamic_typed_data_sync(&typed_data)?.as_bytes(),
+    ))
+}
+
+/// Returns the EIP-2718 encoded signed transaction, ready for
+/// `eth_sendRawTransaction`.
+fn sign_transaction(signer: &PrivateKeySigner, json: &str, chain_id: u64) -> Result<String> {
+    let request: TransactionRequest =
+        serde_json::from_str(json).context("Failed to parse transaction request")?;
+    Ok(hex::encode_prefixed(sign_request(
+        signer, request, chain_id,
+    )?))
+}
+
+/// Sign a complete request, defaulting `chainId` and refusing a request that
+/// names another chain or sender.
+pub(crate) fn sign_request(
+    signer: &PrivateKeySigner,
+    mut request: TransactionRequest,
+    chain_id: u64,
+) -> Result<Vec<u8>> {
+    match request.chain_id {
+        Some(requested) if requested != chain_id => anyhow::bail!(
+            "Transaction is for chain ID {} but the selected chain is {}",
+            requested,
+            chain_id
+        ),
+        Some(_) => {}
+        None => request.chain_id = Some(chain_id),
+    }
+    if let Some(from) = request.from
+        && from != signer.address()
+    {
+        anyhow::bail!(
+            "Transaction 'from' is {} but the signing key is {}",
+            from,
+            signer.address()
+        );
+    }
+    if let Err((tx_type, missing)) = request.missing_keys() {
+        anyhow::bail!(
+            "Transaction request is incomplete for a {} transaction; missing: {}",
+            tx_type,
+            missing.join(", ")
+        );
+    }
+    let mut tx = request
+        .build_typed_tx()
+        .map_err(|_| anyhow::anyhow!("Transaction request cannot be built"))?;
+    let signature = signer.sign_transaction_sync(&mut tx)?;
+    Ok(tx.into_envelope(signature).encoded_2718())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::{
+        consensus::{Transaction, TxEnvelope},
+        network::eip2718::Decodable2718,
+        primitives::{Address, Signature, eip191_hash_message},
+    };
+
+    const TEST_PRIVATE_KEY: &str =
+        "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    fn signer() -> PrivateKeySigner {
+        TEST_PRIVATE_KEY.parse().unwrap()
+    }
+
+    #[test]
+    fn message_signature_recovers_the_signer() -> Result<()> {
+        let signature = sign_message(&signer(), b"hello chainz")?;
+        let signature = Signature::try_from(hex::decode(signature)?.as_slice())?;
+        assert_eq!(
+            signature.recover_address_from_prehash(&eip191_hash_message(b"hello chainz"))?,
+            signer().address()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn typed_data_signature_recovers_the_signer() -> Result<()> {
+        let json = r#"{
+            "types": {
+                "EIP712Domain": [
+                    {"name": "name", "type": "string"},
+                    {"name": "chainId", "type": "uint256"}
+                ],
+                "Mail": [{"name": "contents", "type": "string"}]
+            },
+            "primaryType": "Mail",
+            "domain": {"name": "chainz", "chainId": 1},
+            "message": {"contents": "hello"}
+        }"#;
+        let signature = sign_typed_data(&signer(), json)?;
+        let typed_data: TypedData = serde_json::from_str(json)?;
+        let signature = Signature::try_from(hex::decode(signature)?.as_slice())?;
+        assert_eq!(
+            signature.recover_address_from_prehash(&typed_data.eip712_signing_hash()?)?,
+            signer().address()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn transaction_defaults_to_the_selected_chain() -> Result<()> {
+        let json = r#"{
+            "to": "0x70997970C51812dc3A010C7d01b50e0d17dc79C8",
+            "value": "0x1",
+            "nonce": "0x0",
+            "gas": "0x5208",
+            "maxFeePerGas": "0x3b9aca00",
+            "maxPriorityFeePerGas": "0x1"
+        }"#;
+        let raw = hex::decode(sign_transaction(&signer(), json, 8453)?)?;
+        let envelope = TxEnvelope::decode_2718(&mut raw.as_slice())?;
+        assert_eq!(envelope.chain_id(), Some(8453));
+        let signed = envelope.as_eip1559().expect("fee fields select EIP-1559");
+        assert_eq!(
+            signed
+                .signature()
+                .recover_address_from_prehash(&signed.signature_hash())?,
+            signer().address()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn transaction_guards_reject_mismatches_and_gaps() {
+        let wrong_chain = r#"{"chainId": "0x1", "nonce": "0x0", "gas": "0x5208",
+            "maxFeePerGas": "0x1", "maxPriorityFeePerGas": "0x1", "to": null}"#;
+        assert!(
+            sign_transaction(&signer(), wrong_chain, 8453)
+                .unwrap_err()
+                .to_string()
+                .contains("chain ID 1")
+        );
+
+        let wrong_from = format!(r#"{{"from": "{}", "nonce": "0x0"}}"#, Address::ZERO);
+        assert!(
+            sign_transaction(&signer(), &wrong_from, 1)
+                .unwrap_err()
+                .to_string()
+                .contains("signing key")
+        );
+
+        let missing_gas = r#"{"to": "0x70997970C51812dc3A010C7d01b50e0d17dc79C8", "nonce": "0x0"}"#;
+        assert!(
+            sign_transaction(&signer(), missing_gas, 1)
+                .unwrap_err()
+                .to_string()
+                .contains("missing")
+        );
+    }
+}
@@ -1,12 +1,18 @@
+mod secret;
+mod template;
+mod token;
+
 use crate::{
     chain::ChainInstance,
     config::Chainz,
+    key::SystemKeyBackend,
     opt::VarCommand,
     prompt::{Prompt, SystemPrompt},
+    ui,
 };
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::io::Read;
 use zeroize::Zeroize;
@@ -16,6 +22,10 @@ pub struct GlobalVariables {
     /// INFURA_API_KEY etc
     #[serde(flatten)]
     rpc_expansions: HashMap<String, String>,
+    /// Resolve only the variables defined here, never the environment; set
+    /// for project overlays
+    #[serde(skip)]
+    isolated: bool,
 }
 
 impl fmt::Debug for GlobalVariables {
@@ -28,6 +38,72 @@ impl fmt::Debug for GlobalVariables {
     }
 }
 
+/// Environment variables `exec` always sets; per-chain variables cannot
+/// shadow them.
+const BUILTIN_ENV: &[&str] = &[
+    "ETH_RPC_URL",
+    "CHAIN_ID",
+    "CHAIN_NAME",
+    "VERIFIER_URL",
+    "VERIFIER_API_KEY",
+    "VERIFIER",
+    "ETH_WS_URL",
+    "NATIVE_SYMBOL",
+    "EXPLORER_URL",
+    "WALLET_ADDRESS",
+    "RAW_PRIVATE_KEY",
+];
+
+/// Built-in `@` expansions, without the `@`.
+const BUILTIN_TOKENS: &[&str] = &[
+    "rpc",
+    "chainid",
+    "chainname",
+    "verification_url",
+    "verifier_api_key",
+    "verifier",
+    "ws",
+    "explorer",
+    "wallet",
+    "key",
+];
+
+/// Per-chain variable names double as environment variable names and `@`
+/// tokens, so they follow shell naming and stay clear of the built-ins.
+pub(crate) fn validate_chain_variable(name: &str) -> Result<()> {
+    let mut chars = name.chars();
+    let shell_name = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !shell_name {
+        anyhow::bail!(
+            "Invalid variable name '{}': use letters, digits, and underscores",
+            name
+        );
+    }
+    if BUILTIN_ENV.contains(&name)
+        || BUILTIN_TOKENS
+            .iter()
+            .any(|token| token.eq_ignore_ascii_case(name))
+    {
+        anyhow::bail!("Variable name '{}' is reserved by chainz", name);
+    }
+    Ok(())
+}
+
+/// The `@` tokens `command` uses, without the `@`.
+pub(crate) fn requested_tokens(command: &[String]) -> Vec<&str> {
+    command
+        .iter()
+        .flat_map(|arg| token::parse(arg))
+        .filter_map(|part| match part {
+            token::Part::Token(name) => Some(name),
+            token::Part::Text(_) => None,
+        })
+        .collect()
+}
+
 pub struct ChainVariables {
     env: HashMap<String, String>,
     expansions: HashMap<String, String>,
@@ -35,8 +111,11 @@ pub struct ChainVariables {
 
 impl ChainVariables {
     pub fn new(chain: &ChainInstance, command: &[String], expose_key: bool) -> Result<Self> {
-        let needs_key_arg = command.iter().any(|arg| arg.contains("@key"));
-        let needs_wallet = command.iter().any(|arg| arg.contains("@wallet"));
+        let requested = requested_tokens(command);
+        let needs_key_arg = requested.contains(&"key");
+        let needs_wallet = requested.contains(&"wallet");
+        let needs_ws = requested.contains(&"ws");
+        let needs_explorer = requested.contains(&"explorer");
 
         let mut env = HashMap::new();
         let mut expansions = HashMap::new();
@@ -74,6 +153,47 @@ impl ChainVariables {
             env.insert(env_var.to_string(), val.clone());
             expansions.insert(expansion.to_string(), val.clone());
         }
+        for (name, value) in &chain.definition.env {
+            env.insert(name.clone(), value.clone());
+            expansions.insert(format!("@{}", name), value.clone());
+        }
+
+        if let Some(kind) = crate::verifier::registry().kind_for(
+            chain.definition.chain_id,
+            chain.definition.verification_url.as_deref(),
+        ) {
+            env.insert("VERIFIER".to_string(), kind.to_string());
+            expansions.insert("@verifier".to_string(), kind.to_string());
+        }
+
+        // Unlike the verifier settings, a missing WebSocket is left unset
+        // rather than "UNDEFINED": tools probe for its presence.
+        match &chain.ws_url {
+            Some(ws_url) => {
+                env.insert("ETH_WS_URL".to_string(), ws_url.clone());
+                expansions.insert("@ws".to_string(), ws_url.clone());
+            }
+            None if needs_ws => anyhow::bail!(
+                "Chain '{}' has no WebSocket URL; use `chainz update {} --ws-url <url>`",
+                chain.definition.name,
+                chain.definition.name
+            ),
+            None => {}
+        }
+        if let Some(currency) = &chain.definition.metadata.native_currency {
+            env.insert("NATIVE_SYMBOL".to_string(), currency.symbol.clone());
+        }
+        match chain.definition.explorer_url() {
+            Some(url) => {
+                env.insert("EXPLORER_URL".to_string(), url.to_string());
+                expansions.insert("@explorer".to_string(), url.to_string());
+            }
+            None if needs_explorer => anyhow::bail!(
+                "Chain '{}' has no block explorer; re-add it from chainlist to fill one in",
+                chain.definition.name
+            ),
+            None => {}
+        }
 
         // Only resolve the private key when the command explicitly needs it.
         // New safe-storage records cache the public address, so @wallet does
@@ -151,23 +271,109 @@ impl ChainVariables {
         &self.env
     }
 
-    pub fn expand(&self, input: Vec<String>) -> Vec<String> {
-        input
-            .into_iter()
-            .map(|arg| {
-                let mut result = arg;
-                for (key, value) in &self.expansions {
-                    result = result.replace(key, value);
+    /// Expand `@` tokens in each argument. Unknown tokens are an error;
+    /// an argument without tokens that reads like it meant one is passed
+    /// through with a warning.
+    pub fn expand(&self, input: Vec<String>) -> Result<Vec<String>> {
+        input.iter().map(|arg| self.expand_arg(arg)).collect()
+    }
+
+    fn expand_arg(&self, arg: &str) -> Result<String> {
+        let parts = token::parse(arg);
+        let mut expanded = String::with_capacity(arg.len());
+        let mut tokens = 0;
+        for part in &parts {
+            match part {
+                token::Part::Text(text) => expanded.push_str(text),
+                token::Part::Token(name) => {
+                    let value = self.expansion(name).ok_or_else(|| {
+                        let mut known: Vec<_> =
+                            self.expansions.keys().map(String::as_str).collect();
+                        known.sort();
+                        anyhow::anyhow!(
+                            "Unknown token '@{}' (available: {}); write @@ for a literal @",
+                            name,
+                            known.join(", ")
+                        )
+                    })?;
+                    expanded.push_str(value);
+                    tokens += 1;
                 }
-                result
-            })
-            .collect()
+            }
+        }
+        if tokens == 0
+            && let Some(fragment) = token::lookalike(&parts, |name| self.expansion(name).is_some())
+        {
+            eprintln!(
+                "Warning: '{}' was passed through unexpanded; write @{{name}} to expand a token inside a word, or @@ for a literal @",
+                fragment
+            );
+        }
+        Ok(expanded)
+    }
+
+    fn expansion(&self, name: &str) -> Option<&str> {
+        self.expansions
+            .get(&format!("@{}", name))
+            .map(String::as_str)
     }
 }
 
 impl GlobalVariables {
+    /// Expand a URL template, leaving unresolved references as written.
     pub fn expand_rpc_url(&self, rpc_url: &str) -> String {
-        interpolate_variables(rpc_url, &self.rpc_expansions)
+        template::render(rpc_url, |name| self.lookup(name)).text
+    }
+
+    /// Expand a URL template for use. Unresolved `${VAR:?message}`
+    /// references are always an error; in strict mode, so is any other
+    /// unresolved reference.
+    pub(crate) fn expand_checked(&self, rpc_url: &str, strict: bool) -> Result<String> {
+        let rendered = template::render(rpc_url, |name| self.lookup(name));
+        let missing: Vec<_> = rendered
+            .missing
+            .iter()
+            .filter(|missing| strict || missing.required)
+            .map(|missing| match &missing.message {
+                Some(message) => format!("{} ({})", missing.name, message),
+                None => missing.name.clone(),
+            })
+            .collect();
+        if !missing.is_empty() {
+            anyhow::bail!("Unresolved variables: {}", missing.join(", "));
+        }
+        Ok(rendered.text)
+    }
+
+    /// Where a variable's value comes from, or `None` when it does not
+    /// resolve.
+    pub(crate) fn source(&self, name: &str) -> Option<String> {
+        match self.rpc_expansions.get(name) {
+            Some(value) => {
+                secret::resolve(name, value)?;
+                Some(match secret::SecretRef::parse(value) {
+                    Ok(Some(reference)) => format!("{} reference", reference.kind()),
+                    _ => "variable".to_string(),
+                })
+            }
+            None if self.isolated => None,
+            None => std::env::var_os(name).map(|_| "environment".to_string()),
+        }
+    }
+
+    /// A configured variable, resolving references, else the environment.
+    fn lookup(&self, name: &str) -> Option<String> {
+        match self.rpc_expansions.get(name) {
+            Some(value) => secret::resolve(name, value),
+            None if self.isolated => None,
+            None => std::env::var(name).ok(),
+        }
+    }
+
+    /// Variables that never fall back to the environment, for URLs from an
+    /// untrusted project file.
+    pub(crate) fn isolate(&mut self) {
+        self.isolated = true;
     }
 
     pub fn add_rpc_expansion(&mut self, key: &str, value: &str) {
@@ -175,11 +381,25 @@ impl GlobalVariables {
             .insert(key.to_string(), value.to_string());
     }
 
+    /// Variables whose values are secret references, sorted by name.
+    pub(crate) fn secret_references(&self) -> Vec<&str> {
+        let mut names: Vec<_> = self
+            .rpc_expansions
+            .iter()
+            .filter(|(_, value)| secret::SecretRef::is_reference(value))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        names.sort();
+        names
+    }
+
     pub(crate) fn validate(&self) -> Result<()> {
-        for key in self.rpc_expansions.keys() {
+        for (key, value) in &self.rpc_expansions {
             if key.is_empty() || key.contains(['{', '}']) {
                 anyhow::bail!("Invalid variable name '{}'", key);
             }
+            secret::SecretRef::parse(value)
+                .with_context(|| format!("Invalid reference in variable '{}'", key))?;
         }
         Ok(())
     }
@@ -200,16 +420,25 @@ impl GlobalVariables {
 impl VarCommand {
     pub async fn handle(self, chainz: &mut Chainz) -> Result<()> {
         match self {
-            VarCommand::Set { name, value, stdin } => {
+            VarCommand::Set {
+                name,
+                value,
+                stdin,
+                chain,
+            } => {
                 if stdin && value.is_some() {
                     anyhow::bail!("Provide a value or --stdin, not both");
                 }
                 let value = if stdin {
                     read_value_from_stdin()?
                 } else if let Some(value) = value {
-                    eprintln!(
-                        "Warning: variable values in argv may be visible in shell history; prefer --stdin"
-                    );
+                    let reference =
+                        chain.is_none() && matches!(secret::SecretRef::parse(&value), Ok(Some(_)));
+                    if !reference {
+                        eprintln!(
+                            "Warning: variable values in argv may be visible in shell history; prefer --stdin"
+                        );
+                    }
                     value
                 } else {
                     let mut prompt = SystemPrompt;
@@ -218,48 +447,199 @@ impl VarCommand {
                     }
                     prompt.secret(&format!("Value for {}: ", name))?
                 };
-                chainz.config.globals.add_rpc_expansion(&name, &value);
-                chainz.save().await?;
-                println!("Set variable {}", name);
+                match chain {
+                    Some(chain) => {
+                        let mut chain = chainz.config.get_chain(&chain)?.clone();
+                        chain.env.insert(name.clone(), value);
+                        let chain_name = chain.name.clone();
+                        chainz.replace_chain(&chain_name, chain)?;
+                        chainz.save().await?;
+                        println!("Set variable {} for '{}'", name, chain_name);
+                    }
+                    None => {
+                        chainz.config.globals.add_rpc_expansion(&name, &value);
+                        chainz.save().await?;
+                        println!("Set variable {}", name);
+                    }
+                }
             }
-            VarCommand::Get { name, show } => {
-                match chainz.config.globals.get_rpc_expansion(&name) {
-                    Some(value) if show => println!("{} = {}", name, value),
-                    Some(_) => println!("{} = [REDACTED]", name),
+            VarCommand::Get { name, show, chain } => {
+                let value = match &chain {
+                    Some(chain) => chainz
+                        .config
+                        .get_chain(chain)?
+                        .env
+                        .get(&name)
+                        .map(String::as_str),
+                    None => chainz.config.globals.get_rpc_expansion(&name),
+                };
+                match value {
+                    Some(value) => {
+                        println!("{} = {}", name, display_value(value, show, chain.is_none()))
+                    }
                     None => anyhow::bail!("Variable '{}' not found", name),
                 }
             }
-            VarCommand::List { show, json } => {
-                let vars = chainz.config.globals.list_rpc_expansions();
+            VarCommand::List { show, json, chain } => {
+                let (header, vars): (_, BTreeMap<_, _>) = match &chain {
+                    Some(chain) => {
+                        let chain = chainz.config.get_chain(chain)?;
+                        (
+                            format!("Variables for '{}':", chain.name),
+                            chain.env.iter().collect(),
+                        )
+                    }
+                    None => (
+                        "Variables:".to_string(),
+                        chainz.config.globals.list_rpc_expansions().iter().collect(),
+                    ),
+                };
                 if json {
-                    println!("{}", serde_json::to_string_pretty(vars)?);
+                    println!("{}", serde_json::to_string_pretty(&vars)?);
                 } else if vars.is_empty() {
                     println!("No variables set");
                 } else {
-                    println!("Variables:");
-                    let mut entries: Vec<_> = vars.iter().collect();
-                    entries.sort_by_key(|(name, _)| *name);
-                    for (name, value) in entries {
+                    println!("{}", header);
+                    for (name, value) in vars {
                         println!(
                             "  {} = {}",
                             name,
-                            if show { value.as_str() } else { "[REDACTED]" }
+                            display_value(value, show, chain.is_none())
                         );
                     }
                 }
             }
-            VarCommand::Remove { name } => {
-                if chainz.config.globals.remove_rpc_expansion(&name).is_none() {
-                    anyhow::bail!("Variable '{}' not found", name);
+            VarCommand::Remove { name, chain } => {
+                match chain {
+                    Some(chain) => {
+                        let mut chain = chainz.config.get_chain(&chain)?.clone();
+                        if chain.env.remove(&name).is_none() {
+                            anyhow::bail!("Variable '{}' not found for '{}'", name, chain.name);
+                        }
+                        let chain_name = chain.name.clone();
+                        chainz.replace_chain(&chain_name, chain)?;
+                    }
+                    None => {
+                        if chainz.config.globals.remove_rpc_expansion(&name).is_none() {
+                            anyhow::bail!("Variable '{}' not found", name);
+                        }
+                    }
                 }
                 chainz.save().await?;
                 println!("Removed variable '{}'", name);
             }
+            VarCommand::Migrate { name, all } => {
+                let globals = chainz.config.globals.list_rpc_expansions();
+                let mut plain: Vec<(String, String)> = if all {
+                    globals
+                        .iter()
+                        .filter(|(_, value)| matches!(secret::SecretRef::parse(value), Ok(None)))
+                        .map(|(name, value)| (name.clone(), value.clone()))
+                        .collect()
+                } else {
+                    let name = name
+                        .ok_or_else(|| anyhow::anyhow!("Provide a variable name or use --all"))?;
+                    let value = globals
+                        .get(&name)
+                        .ok_or_else(|| anyhow::anyhow!("Variable '{}' not found", name))?;
+                    if let Some(reference) = secret::SecretRef::parse(value)? {
+                        anyhow::bail!(
+                            "Variable '{}' is already a reference ({})",
+                            name,
+                            reference.kind()
+                        );
+                    }
+                    vec![(name, value.clone())]
+                };
+                plain.sort();
+                let backend = SystemKeyBackend;
+                let migration = secret::migrate(&backend, &plain, all)?;
+                let previous = chainz.config.globals.rpc_expansions.clone();
+                for (name, reference) in &migration.references {
+                    chainz.config.globals.add_rpc_expansion(name, reference);
+                }
+                if !migration.references.is_empty()
+                    && let Err(error) = chainz.save().await
+                {
+                    chainz.config.globals.rpc_expansions = previous;
+                    migration.rollback(&backend);
+                    return Err(error);
+                }
+                println!("Migrated {} variable(s)", migration.references.len());
+            }
+            VarCommand::Check { name_or_id } => {
+                let chains = match name_or_id {
+                    Some(name_or_id) => vec![chainz.config.get_chain(&name_or_id)?.clone()],
+                    None => chainz.profile_chains(),
+                };
+                let mut checked = 0;
+                let mut unresolved = 0;
+                for chain in &chains {
+                    let globals = chainz.variables_for(chain);
+                    let mut references: Vec<template::Reference> = Vec::new();
+                    for url in chain.rpc_urls.iter().chain(&chain.ws_urls) {
+                        for reference in template::references(url) {
+                            if !references.iter().any(|seen| seen.name == reference.name) {
+                                references.push(reference);
+                            }
+                        }
+                    }
+                    if references.is_empty() {
+                        continue;
+                    }
+                    println!("{}", ui::section(&chain.name));
+                    for reference in references {
+                        checked += 1;
+                        let line = match (globals.source(reference.name), reference.fallback) {
+                            (Some(source), _) => {
+                                ui::success(&format!("{} ({})", reference.name, source))
+                            }
+                            (None, template::Fallback::Default(_)) => {
+                                ui::warn(&format!("{} (unset, using the default)", reference.name))
+                            }
+                            (None, fallback) => {
+                                unresolved += 1;
+                                match fallback {
+                                    template::Fallback::Required(message)
+                                        if !message.is_empty() =>
+                                    {
+                                        ui::fail(&format!(
+                                            "{} (missing: {})",
+                                            reference.name, message
+                                        ))
+                                    }
+                                    _ => ui::fail(&format!("{} (missing)", reference.name)),
+                                }
+                            }
+                        };
+                        println!("  {}", line);
+                    }
+                }
+                if checked == 0 {
+                    println!("No chain URL references a variable");
+                }
+                if unresolved > 0 {
+                    anyhow::bail!("{} variable reference(s) do not resolve", unresolved);
+                }
+            }
         }
         Ok(())
     }
 }
 
+/// What `var get`/`var list` print: the value when asked for, otherwise a
+/// redacted marker that names the backend of a global reference. Chain
+/// variables are exported verbatim, so they never hold references.
+fn display_value(value: &str, show: bool, global: bool) -> String {
+    if show {
+        return value.to_string();
+    }
+    match secret::SecretRef::parse(value) {
+        Ok(Some(reference)) if global => format!("[{}]", reference.kind()),
+        _ => "[REDACTED]".to_string(),
+    }
+}
+
 fn read_value_from_stdin() -> Result<String> {
     let mut value = String::new();
     std::io::stdin().read_to_string(&mut value)?;
@@ -271,51 +651,5 @@ fn read_value_from_stdin() -> Result<String> {
     Ok(normalized)
 }
 
-fn interpolate_variables(input: &str, variables: &HashMap<String, String>) -> String {
-    let mut result = input.to_string();
-
-    // First replace from config variables
-    for (key, value) in variables {
-        let pattern = format!("${{{}}}", key);
-        result = result.replace(&pattern, value);
-    }
-
-    // Then try to replace any remaining ${VAR} patterns with environment variables
-    let mut final_result = String::new();
-    let mut last_end = 0;
-
-    while let Some((start, end)) = find_next_var(&result[last_end..]) {
-        let absolute_start = last_end + start;
-        let absolute_end = last_end + end;
-
-        // Add the part before the variable
-        final_result.push_str(&result[last_end..absolute_start]);
-
-        // Get the variable name
-        let var_name = &result[absolute_start + 2..absolute_end - 1]; // strip ${ and }
-
-        // Try to get the environment variable
-        if let Ok(value) = std::env::var(var_name) {
-            final_result.push_str(&value);
-        } else {
-            // If not found, keep the original ${VAR} syntax
-            final_result.push_str(&result[absolute_start..absolute_end]);
-        }
-
-        last_end = absolute_end;
-    }
-
-    // Add any remaining part of the string
-    final_result.push_str(&result[last_end..]);
-
-    final_result
-}
-
-fn find_next_var(input: &str) -> Option<(usize, usize)> {
-    let start = input.find("${")?;
-    let end = input[start..].find("}")?.checked_add(start + 1)?;
-    Some((start, end))
-}
-
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,227 @@
+//! Variable values that point at a secret instead of holding it.
+//!
+//! A value such as `secret:keyring:var:ALCHEMY_KEY` or `secret:op://vault/item`
+//! is kept in the config as written and only resolved when a URL actually
+//! references the variable. The `secret:` marker keeps plain values that
+//! merely start with `env:` or `cmd:` plain. Each reference resolves at most
+//! once per process: later expansions, and later failures, come from the
+//! cache.
+
+use crate::key::{KEYRING_SERVICE, KeyBackend, SystemKeyBackend};
+use anyhow::{Context, Result, anyhow};
+use std::{
+    collections::HashMap,
+    process::Command,
+    sync::{Mutex, OnceLock},
+};
+use zeroize::Zeroizing;
+
+/// Marks a value as a reference rather than a plain value.
+const SECRET_PREFIX: &str = "secret:";
+/// Keyring usernames for migrated variables, apart from key names.
+const KEYRING_PREFIX: &str = "var:";
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum SecretRef<'a> {
+    /// `secret:keyring:var:<NAME>` in the chainz keyring service; other
+    /// usernames there hold keys and are never readable as variables
+    Keyring(&'a str),
+    /// `secret:op://<vault>/<item>[/<field>]`, read with the 1Password CLI
+    OnePassword { vault: &'a str, item: &'a str },
+    /// `secret:env:<NAME>`, read from the chainz process environment
+    Env(&'a str),
+    /// `secret:cmd:<command>`, run through the shell; stdout is the value
+    Command(&'a str),
+}
+
+impl<'a> SecretRef<'a> {
+    /// `None` for a plain value; an error for a malformed reference.
+    pub(crate) fn parse(value: &'a str) -> Result<Option<Self>> {
+        let Some(value) = value.strip_prefix(SECRET_PREFIX) else {
+            return Ok(None);
+        };
+        let reference = if let Some(username) = value.strip_prefix("keyring:") {
+            SecretRef::Keyring(username)
+        } else if let Some(path) = value.strip_prefix("op://") {
+            let (vault, item) = path
+                .split_once('/')
+                .filter(|(vault, item)| !vault.is_empty() && !item.is_empty())
+                .ok_or_else(|| anyhow!("1Password references look like secret:op://vault/item"))?;
+            SecretRef::OnePassword { vault, item }
+        } else if let Some(name) = value.strip_prefix("env:") {
+            SecretRef::Env(name)
+        } else if let Some(command) = value.strip_prefix("cmd:") {
+            SecretRef::Command(command)
+        } else {
+            anyhow::bail!(
+                "Unknown reference; use {0}keyring:, {0}op://, {0}env:, or {0}cmd:",
+                SECRET_PREFIX
+            );
+        };
+        if let SecretRef::Keyring(rest) | SecretRef::Env(rest) | SecretRef::Command(rest) =
+            &reference
+            && rest.trim().is_empty()
+        {
+            anyhow::bail!("Empty {} reference", reference.kind());
+        }
+        if let SecretRef::Keyring(username) = reference
+            && username
+                .strip_prefix(KEYRING_PREFIX)
+                .is_none_or(|name| name.trim().is_empty())
+        {
+            anyhow::bail!(
+                "Keyring references look like {}keyring:{}<NAME>",
+                SECRET_PREFIX,
+                KEYRING_PREFIX
+            );
+        }
+        Ok(Some(reference))
+    }
+
+    /// Whether `value` is written as a reference, well-formed or not.
+    pub(crate) fn is_reference(value: &str) -> bool {
+        !matches!(SecretRef::parse(value), Ok(None))
+    }
+
+    /// The storage named in listings instead of a redacted marker.
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            SecretRef::Keyring(_) => "keyring",
+            SecretRef::OnePassword { .. } => "1password",
+            SecretRef::Env(_) => "env",
+            SecretRef::Command(_) => "cmd",
+        }
+    }
+
+    pub(crate) fn resolve<B: KeyBackend>(&self, backend: &B) -> Result<Zeroizing<String>> {
+        match self {
+            SecretRef::Keyring(username) => backend.keyring_get(KEYRING_SERVICE, username),
+            SecretRef::OnePassword { vault, item } => backend.one_password_get(vault, item),
+            SecretRef::Env(name) => std::env::var(name)
+                .map(Zeroizing::new)
+                .with_context(|| format!("Environment variable {} is not set", name)),
+            SecretRef::Command(command) => run_command(command),
+        }
+    }
+}
+
+fn run_command(command: &str) -> Result<Zeroizing<String>> {
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let output = Command::new(shell)
+        .args([flag, command])
+        .output()
+        .context("Failed to run the variable command")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Variable command failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let stdout = Zeroizing::new(String::from_utf8(output.stdout)?);
+    Ok(Zeroizing::new(
+        stdout.trim_end_matches(['\r', '\n']).to_string(),
+    ))
+}
+
+/// The value behind a variable: plain values as stored, references through
+/// the system backends. A failing reference warns once and stays `None`.
+pub(crate) fn resolve(name: &str, value: &str) -> Option<String> {
+    static CACHE: OnceLock<Mutex<HashMap<String, Option<Zeroizing<String>>>>> = OnceLock::new();
+    let reference = match SecretRef::parse(value) {
+        Ok(Some(reference)) => reference,
+        Ok(None) => return Some(value.to_string()),
+        Err(error) => {
+            eprintln!("Warning: variable {}: {:#}", name, error);
+            return None;
+        }
+    };
+    let mut cache = CACHE
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    cache
+        .entry(value.to_string())
+        .or_insert_with(|| match reference.resolve(&SystemKeyBackend) {
+            Ok(secret) => Some(secret),
+            Err(error) => {
+                eprintln!(
+                    "Warning: could not resolve variable {} from {}: {:#}",
+                    name,
+                    reference.kind(),
+                    error
+                );
+                None
+            }
+        })
+        .as_ref()
+        .map(|secret| secret.to_string())
+}
+
+/// Variables moved into the keyring by [`migrate`].
+pub(crate) struct Migration {
+    /// Each migrated variable with the reference that replaces its value
+    pub(crate) references: Vec<(String, String)>,
+    /// Keyring entries this migration created, removed again on rollback
+    created: Vec<String>,
+}
+
+impl Migration {
+    /// Undo a migration whose config write failed.
+    pub(crate) fn rollback<B: KeyBackend>(&self, backend: &B) {
+        rollback(backend, &self.created);
+    }
+}
+
+/// Move plaintext values into the OS keyring.
+pub(crate) fn migrate<B: KeyBackend>(
+    backend: &B,
+    variables: &[(String, String)],
+    continue_on_error: bool,
+) -> Result<Migration> {
+    if !backend.keyring_available() {
+        anyhow::bail!("The OS keyring is unavailable; variables can only migrate to the keyring");
+    }
+    let mut references = Vec::new();
+    let mut created = Vec::new();
+    for (name, value) in variables {
+        let username = format!("{}{}", KEYRING_PREFIX, name);
+        match backend.keyring_set(KEYRING_SERVICE, &username, value) {
+            Ok(was_created) => {
+                if was_created {
+                    created.push(username.clone());
+                }
+                references.push((
+                    name.clone(),
+                    format!("{}keyring:{}", SECRET_PREFIX, username),
+                ));
+            }
+            Err(error) if continue_on_error => {
+                eprintln!("Failed to migrate '{}': {:#}", name, error);
+            }
+            Err(error) => {
+                rollback(backend, &created);
+                return Err(error);
+            }
+        }
+    }
+    Ok(Migration {
+        references,
+        created,
+    })
+}
+
+fn rollback<B: KeyBackend>(backend: &B, created: &[String]) {
+    for username in created {
+        if let Err(error) = backend.keyring_delete(KEYRING_SERVICE, username) {
+            eprintln!(
+                "Warning: could not remove keyring entry '{}': {error}",
+                username
+            );
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! `${...}` templates in RPC URLs.
+//!
+//! The grammar is a small subset of POSIX parameter expansion:
+//! `${VAR}`, `${VAR:-default}` (used when VAR is unset or empty),
+//! `${VAR:?message}` (an error naming VAR when it is unset or empty), and
+//! `$${...}`, which stands for a literal `${...}`. Anything else, including
+//! a lone `$` or an unterminated `${`, is literal text.
+
+/// What a reference falls back to when its variable does not resolve.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Fallback<'a> {
+    None,
+    Default(&'a str),
+    Required(&'a str),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Part<'a> {
+    Text(&'a str),
+    Var {
+        name: &'a str,
+        fallback: Fallback<'a>,
+        /// The reference as written, kept when it cannot be resolved
+        raw: &'a str,
+    },
+}
+
+/// A variable reference found in a template.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Reference<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) fallback: Fallback<'a>,
+}
+
+/// A reference that neither resolved nor had a default.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Missing {
+    pub(crate) name: String,
+    /// Written as `${VAR:?...}`, so an error even outside strict mode
+    pub(crate) required: bool,
+    /// The `:?` message, when the template gave one
+    pub(crate) message: Option<String>,
+}
+
+pub(crate) struct Rendered {
+    /// The expansion, with unresolved references left as written
+    pub(crate) text: String,
+    pub(crate) missing: Vec<Missing>,
+}
+
+fn parse(input: &str) -> Vec<Part<'_>> {
+    let mut parts = Vec::new();
+    let mut rest = input;
+    while let Some(start) = rest.find('$') {
+        let (before, from_dollar) = rest.split_at(start);
+        if !before.is_empty() {
+            parts.push(Part::Text(before));
+        }
+        if let Some(escaped) = from_dollar.strip_prefix("$$")
+            && escaped.starts_with('{')
+            && let Some(end) = escaped.find('}')
+        {
+            // `$${NAME}` is the literal `${NAME}`.
+            parts.push(Part::Text(&from_dollar[1..end + 3]));
+            rest = &escaped[end + 1..];
+            continue;
+        }
+        if let Some(body) = from_dollar.strip_prefix("${")
+            && let Some(end) = body.find('}')
+            && end > 0
+        {
+            let inner = &body[..end];
+            // The operator follows the first `:`, so a default or message
+            // may itself contain `:-` or `:?`.
+            let (name, fallback) = match inner.split_once(':') {
+                None => (inner, Some(Fallback::None)),
+                Some((name, rest)) => match rest.split_at_checked(1) {
+                    Some(("-", default)) => (name, Some(Fallback::Default(default))),
+                    Some(("?", message)) => (name, Some(Fallback::Required(message))),
+                    _ => (name, None),
+                },
+            };
+            if let Some(fallback) = fallback
+                && !name.is_empty()
+            {
+                parts.push(Part::Var {
+                    name,
+                    fallback,
+                    raw: &from_dollar[..end + 3],
+                });
+                rest = &body[end + 1..];
+                continue;
+            }
+        }
+        parts.push(Part::Text("$"));
+        rest = &from_dollar[1..];
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Text(rest));
+    }
+    parts
+}
+
+/// Expand `input`, asking `lookup` for each referenced variable once per
+/// reference. Empty values count as unset only where a fallback is given.
+pub(crate) fn render(input: &str, mut lookup: impl FnMut(&str) -> Option<String>) -> Rendered {
+    let mut text = String::with_capacity(input.len());
+    let mut missing = Vec::new();
+    for part in parse(input) {
+        match part {
+            Part::Text(literal) => text.push_str(literal),
+            Part::Var {
+                name,
+                fallback,
+                raw,
+            } => match (lookup(name), fallback) {
+                (Some(value), Fallback::None) => text.push_str(&value),
+                (Some(value), _) if !value.is_empty() => text.push_str(&value),
+                (_, Fallback::Default(default)) => text.push_str(default),
+                (_, fallback) => {
+                    text.push_str(raw);
+                    missing.push(Missing {
+                        name: name.to_string(),
+                        required: matches!(fallback, Fallback::Required(_)),
+                        message: match fallback {
+                            Fallback::Required(message) if !message.is_empty() => {
+                                Some(message.to_string())
+                            }
+                            _ => None,
+                        },
+                    });
+                }
+            },
+        }
+    }
+    Rendered { text, missing }
+}
+
+/// Every variable reference in `input`, in order of appearance.
+pub(crate) fn references(input: &str) -> Vec<Reference<'_>> {
+    parse(input)
+        .into_iter()
+        .filter_map(|part| match part {
+            Part::Var { name, fallback, .. } => Some(Reference { name, fallback }),
+            Part::Text(_) => None,
+        })
+        .collect()
+}
@@ -178,7 +178,7 @@ fn make_chain_variables() -> ChainVariables {
 #[test]
 fn test_expand_wallet_token() {
     let cv = make_chain_variables();
-    let result = cv.expand(vec!["--from".into(), "@wallet".into()]);
+    let result = cv.expand(vec!["--from".into(), "@wallet".into()]).unwrap();
     assert_eq!(result, vec!["--from", "0xABCD"]);
 }
 
@@ -188,14 +188,20 @@ fn cached_wallet_address_does_not_unlock_keyring() {
         definition: crate::chain::ChainDefinition {
             name: "mainnet".into(),
             aliases: vec![],
+            tags: vec![],
             chain_id: 1,
             rpc_urls: vec!["http://localhost:8545".into()],
             selected_rpc: "http://localhost:8545".into(),
+            ws_urls: vec![],
+            selected_ws: None,
             verification_api_key: None,
             verification_url: None,
             key_name: Some("deployer".into()),
+            env: Default::default(),
+            metadata: Default::default(),
         },
         rpc_url: "http://localhost:8545".into(),
+        ws_url: None,
         key: Some(crate::key::Key {
             name: "deployer".into(),
             address: Some("0xABCD".into()),
@@ -212,47 +218,192 @@ fn cached_wallet_address_does_not_unlock_keyring() {
     assert!(!cv.as_map().contains_key("RAW_PRIVATE_KEY"));
 }
 
+#[test]
+fn ws_url_is_exported_only_when_configured() {
+    let mut definition = crate::chain::ChainDefinition {
+        name: "mainnet".into(),
+        aliases: vec![],
+        tags: vec![],
+        chain_id: 1,
+        rpc_urls: vec!["http://localhost:8545".into()],
+        selected_rpc: "http://localhost:8545".into(),
+        ws_urls: vec![],
+        selected_ws: None,
+        verification_api_key: None,
+        verification_url: None,
+        key_name: None,
+        env: Default::default(),
+        metadata: Default::default(),
+    };
+    let chain = |definition: &crate::chain::ChainDefinition, ws_url: Option<&str>| {
+        crate::chain::ChainInstance {
+            definition: definition.clone(),
+            rpc_url: "http://localhost:8545".into(),
+            ws_url: ws_url.map(str::to_string),
+            key: None,
+        }
+    };
+
+    let without = chain(&definition, None);
+    let cv = ChainVariables::new(&without, &[], false).unwrap();
+    assert!(!cv.as_map().contains_key("ETH_WS_URL"));
+    let error = ChainVariables::new(&without, &["@ws".into()], false)
+        .err()
+        .unwrap();
+    assert!(error.to_string().contains("--ws-url"), "{error}");
+
+    definition.select_ws("ws://localhost:8546".into());
+    let with = chain(&definition, Some("ws://localhost:8546"));
+    let command = vec!["cast".into(), "--rpc-url".into(), "@ws".into()];
+    let cv = ChainVariables::new(&with, &command, false).unwrap();
+    assert_eq!(
+        cv.as_map().get("ETH_WS_URL"),
+        Some(&"ws://localhost:8546".into())
+    );
+    assert_eq!(cv.expand(command).unwrap()[2], "ws://localhost:8546");
+}
+
+#[test]
+fn chainlist_metadata_is_exported_when_known() {
+    let mut definition = crate::chain::ChainDefinition {
+        name: "base".into(),
+        aliases: vec![],
+        tags: vec![],
+        chain_id: 8453,
+        rpc_urls: vec!["http://localhost:8545".into()],
+        selected_rpc: "http://localhost:8545".into(),
+        ws_urls: vec![],
+        selected_ws: None,
+        verification_api_key: None,
+        verification_url: None,
+        key_name: None,
+        env: Default::default(),
+        metadata: Default::default(),
+    };
+    let chain = |definition: &crate::chain::ChainDefinition| crate::chain::ChainInstance {
+        definition: definition.clone(),
+        rpc_url: "http://localhost:8545".into(),
+        ws_url: None,
+        key: None,
+    };
+
+    let cv = ChainVariables::new(&chain(&definition), &[], false).unwrap();
+    assert!(!cv.as_map().contains_key("NATIVE_SYMBOL"));
+    assert!(!cv.as_map().contains_key("EXPLORER_URL"));
+    let error = ChainVariables::new(&chain(&definition), &["@explorer".into()], false)
+        .err()
+        .unwrap();
+    assert!(error.to_string().contains("no block explorer"), "{error}");
+
+    definition.metadata.native_currency = Some(crate::chain::NativeCurrency {
+        name: "Ether".into(),
+        symbol: "ETH".into(),
+        decimals: 18,
+    });
+    definition.metadata.explorers = vec![crate::chain::Explorer {
+        name: "basescan".into(),
+        url: "https://basescan.org/".into(),
+        standard: None,
+    }];
+    let command = vec!["open".into(), "@explorer/tx/0x1".into()];
+    let cv = ChainVariables::new(&chain(&definition), &command, false).unwrap();
+    assert_eq!(cv.as_map().get("NATIVE_SYMBOL"), Some(&"ETH".into()));
+    assert_eq!(
+        cv.as_map().get("EXPLORER_URL"),
+        Some(&"https://basescan.org".into())
+    );
+    assert_eq!(
+        cv.expand(command).unwrap()[1],
+        "https://basescan.org/tx/0x1"
+    );
+}
+
+#[test]
+fn verifier_kind_is_exported_from_the_registry() {
+    let mut definition = crate::chain::ChainDefinition {
+        name: "gnosis".into(),
+        aliases: vec![],
+        tags: vec![],
+        chain_id: 100,
+        rpc_urls: vec!["http://localhost:8545".into()],
+        selected_rpc: "http://localhost:8545".into(),
+        ws_urls: vec![],
+        selected_ws: None,
+        verification_api_key: None,
+        verification_url: None,
+        key_name: None,
+        env: Default::default(),
+        metadata: Default::default(),
+    };
+    let chain = |definition: &crate::chain::ChainDefinition| crate::chain::ChainInstance {
+        definition: definition.clone(),
+        rpc_url: "http://localhost:8545".into(),
+        ws_url: None,
+        key: None,
+    };
+
+    let command = vec!["--verifier".into(), "@verifier".into()];
+    let cv = ChainVariables::new(&chain(&definition), &command, false).unwrap();
+    assert_eq!(cv.as_map().get("VERIFIER"), Some(&"blockscout".into()));
+    assert_eq!(cv.expand(command).unwrap()[1], "blockscout");
+
+    definition.verification_url = Some("https://sourcify.dev/server".into());
+    let cv = ChainVariables::new(&chain(&definition), &[], false).unwrap();
+    assert_eq!(cv.as_map().get("VERIFIER"), Some(&"sourcify".into()));
+
+    definition.chain_id = 31337;
+    definition.verification_url = None;
+    let cv = ChainVariables::new(&chain(&definition), &[], false).unwrap();
+    assert!(!cv.as_map().contains_key("VERIFIER"));
+}
+
 #[test]
 fn test_expand_rpc_token() {
     let cv = make_chain_variables();
-    let result = cv.expand(vec!["--rpc-url".into(), "@rpc".into()]);
+    let result = cv.expand(vec!["--rpc-url".into(), "@rpc".into()]).unwrap();
     assert_eq!(result, vec!["--rpc-url", "http://localhost:8545"]);
 }
 
 #[test]
 fn test_expand_chainid_token() {
     let cv = make_chain_variables();
-    let result = cv.expand(vec!["--chain".into(), "@chainid".into()]);
+    let result = cv
+        .expand(vec!["--chain".into(), "@chainid".into()])
+        .unwrap();
     assert_eq!(result, vec!["--chain", "1"]);
 }
 
 #[test]
 fn test_expand_chainname_token() {
     let cv = make_chain_variables();
-    let result = cv.expand(vec!["echo".into(), "@chainname".into()]);
+    let result = cv.expand(vec!["echo".into(), "@chainname".into()]).unwrap();
     assert_eq!(result, vec!["echo", "mainnet"]);
 }
 
 #[test]
 fn test_expand_key_token() {
     let cv = make_chain_variables();
-    let result = cv.expand(vec!["--private-key".into(), "@key".into()]);
+    let result = cv
+        .expand(vec!["--private-key".into(), "@key".into()])
+        .unwrap();
     assert_eq!(result, vec!["--private-key", "0xdeadbeef"]);
 }
 
 #[test]
 fn test_expand_multiple_tokens_in_args() {
     let cv = make_chain_variables();
-    let result = cv.expand(vec![
-        "cast".into(),
-        "send".into(),
-        "--from".into(),
-        "@wallet".into(),
-        "--rpc-url".into(),
-        "@rpc".into(),
-        "--chain".into(),
-        "@chainid".into(),
-    ]);
+    let result = cv
+        .expand(vec![
+            "cast".into(),
+            "send".into(),
+            "--from".into(),
+            "@wallet".into(),
+            "--rpc-url".into(),
+            "@rpc".into(),
+            "--chain".into(),
+            "@chainid".into(),
+        ])
+        .unwrap();
     assert_eq!(
         result,
         vec![
@@ -271,31 +422,51 @@ fn test_expand_multiple_tokens_in_args() {
 #[test]
 fn test_expand_token_embedded_in_string() {
     let cv = make_chain_variables();
-    let result = cv.expand(vec!["network=@chainname".into()]);
+    let result = cv.expand(vec!["network=@chainname".into()]).unwrap();
     assert_eq!(result, vec!["network=mainnet"]);
 }
 
 #[test]
-fn test_expand_leaves_unknown_tokens_unchanged() {
+fn test_expand_rejects_unknown_tokens() {
     let cv = make_chain_variables();
-    let result = cv.expand(vec!["@unknown".into(), "plain".into()]);
-    assert_eq!(result, vec!["@unknown", "plain"]);
+    let error = cv
+        .expand(vec!["@unknown".into(), "plain".into()])
+        .unwrap_err();
+    assert!(error.to_string().contains("Unknown token '@unknown'"));
+    assert!(error.to_string().contains("@chainid, @chainname"));
 }
 
 #[test]
 fn test_expand_empty_input() {
     let cv = make_chain_variables();
-    let result = cv.expand(vec![]);
+    let result = cv.expand(vec![]).unwrap();
     assert!(result.is_empty());
 }
 
 #[test]
 fn test_expand_no_tokens_passes_through() {
     let cv = make_chain_variables();
-    let result = cv.expand(vec!["echo".into(), "hello".into(), "world".into()]);
+    let result = cv
+        .expand(vec!["echo".into(), "hello".into(), "world".into()])
+        .unwrap();
     assert_eq!(result, vec!["echo", "hello", "world"]);
 }
 
+#[test]
+fn requested_tokens_ignore_lookalikes() {
+    let command: Vec<String> = [
+        "cast",
+        "user@keystore.io",
+        "@keys",
+        "@{wallet}",
+        "--pk=@key",
+    ]
+    .map(String::from)
+    .to_vec();
+    assert_eq!(requested_tokens(&command), ["keys", "wallet", "key"]);
+    assert!(!requested_tokens(&["ops@key".to_string()]).contains(&"key"));
+}
+
 // ── ChainVariables::as_map() ─────────────────────────────────────
 
 #[test]
@@ -366,30 +537,351 @@ fn test_no_key_vars_when_command_doesnt_need_key() {
 }
 
 #[test]
-fn test_key_token_unexpanded_without_key_vars() {
+fn test_key_token_rejected_without_key_vars() {
     let cv = make_chain_variables_without_key();
-    let result = cv.expand(vec!["--private-key".into(), "@key".into()]);
-    assert_eq!(result, vec!["--private-key", "@key"]);
+    assert!(
+        cv.expand(vec!["--private-key".into(), "@key".into()])
+            .is_err()
+    );
 }
 
 #[test]
-fn test_wallet_token_unexpanded_without_key_vars() {
+fn test_wallet_token_rejected_without_key_vars() {
     let cv = make_chain_variables_without_key();
-    let result = cv.expand(vec!["--from".into(), "@wallet".into()]);
-    assert_eq!(result, vec!["--from", "@wallet"]);
+    assert!(cv.expand(vec!["--from".into(), "@wallet".into()]).is_err());
 }
 
 #[test]
 fn test_non_key_vars_still_expand_without_key() {
     let cv = make_chain_variables_without_key();
-    let result = cv.expand(vec![
-        "--rpc-url".into(),
-        "@rpc".into(),
-        "--chain".into(),
-        "@chainid".into(),
-    ]);
+    let result = cv
+        .expand(vec![
+            "--rpc-url".into(),
+            "@rpc".into(),
+            "--chain".into(),
+            "@chainid".into(),
+        ])
+        .unwrap();
     assert_eq!(
         result,
         vec!["--rpc-url", "http://localhost:8545", "--chain", "1"]
     );
 }
+
+#[test]
+fn chain_variables_are_exported_and_expanded() {
+    let mut definition = crate::chain::ChainDefinition {
+        name: "base".into(),
+        aliases: vec![],
+        tags: vec![],
+        chain_id: 8453,
+        rpc_urls: vec!["http://localhost:8545".into()],
+        selected_rpc: "http://localhost:8545".into(),
+        ws_urls: vec![],
+        selected_ws: None,
+        verification_api_key: None,
+        verification_url: None,
+        key_name: None,
+        env: Default::default(),
+        metadata: Default::default(),
+    };
+    definition.env.insert("SAFE".into(), "0xSAFE".into());
+    definition.env.insert("SAFE_OWNER".into(), "0xOWNER".into());
+    let chain = crate::chain::ChainInstance {
+        definition,
+        rpc_url: "http://localhost:8545".into(),
+        ws_url: None,
+        key: None,
+    };
+
+    let command = vec!["@SAFE".into(), "@SAFE_OWNER".into()];
+    let cv = ChainVariables::new(&chain, &command, false).unwrap();
+    assert_eq!(cv.as_map().get("SAFE"), Some(&"0xSAFE".into()));
+    assert_eq!(cv.expand(command).unwrap(), vec!["0xSAFE", "0xOWNER"]);
+}
+
+#[test]
+fn chain_variable_names_are_shell_safe_and_not_reserved() {
+    assert!(validate_chain_variable("DEPLOYER_SAFE").is_ok());
+    assert!(validate_chain_variable("_private2").is_ok());
+    assert!(validate_chain_variable("2FAST").is_err());
+    assert!(validate_chain_variable("MY-VAR").is_err());
+    assert!(validate_chain_variable("").is_err());
+    assert!(validate_chain_variable("ETH_RPC_URL").is_err());
+    assert!(
+        validate_chain_variable("WALLET").is_err(),
+        "shadows @wallet"
+    );
+}
+
+#[test]
+fn secret_references_need_the_secret_marker() {
+    use secret::SecretRef;
+    assert_eq!(SecretRef::parse("plain").unwrap(), None);
+    assert_eq!(SecretRef::parse("env:HOME").unwrap(), None, "unmarked");
+    assert_eq!(
+        SecretRef::parse("secret:keyring:var:ALCHEMY_KEY").unwrap(),
+        Some(SecretRef::Keyring("var:ALCHEMY_KEY"))
+    );
+    assert_eq!(
+        SecretRef::parse("secret:op://dev/alchemy/credential").unwrap(),
+        Some(SecretRef::OnePassword {
+            vault: "dev",
+            item: "alchemy/credential"
+        })
+    );
+    assert_eq!(
+        SecretRef::parse("secret:env:ALCHEMY_KEY").unwrap(),
+        Some(SecretRef::Env("ALCHEMY_KEY"))
+    );
+    assert!(SecretRef::parse("secret:op://vault-only").is_err());
+    assert!(SecretRef::parse("secret:keyring:").is_err());
+    assert!(
+        SecretRef::parse("secret:keyring:default").is_err(),
+        "key entries are not readable as variables"
+    );
+    assert!(SecretRef::parse("secret:keyring:var:").is_err());
+    assert!(SecretRef::parse("secret:cmd:  ").is_err());
+    assert!(SecretRef::parse("secret:vault").is_err());
+
+    let mut globals = GlobalVariables::default();
+    globals.add_rpc_expansion("BAD", "secret:op://");
+    assert!(globals.validate().is_err());
+}
+
+#[test]
+fn secret_references_resolve_through_the_backend() -> Result<()> {
+    use crate::key::{KEYRING_SERVICE, testing::MemoryBackend};
+    use secret::SecretRef;
+    setup();
+    let backend = MemoryBackend::new(false, true, &[]);
+    backend.keyring.borrow_mut().insert(
+        (KEYRING_SERVICE.into(), "var:API".into()),
+        "from-keyring".into(),
+    );
+    backend
+        .one_password
+        .borrow_mut()
+        .insert(("dev".into(), "rpc".into()), "from-op".into());
+
+    let resolve = |value| SecretRef::parse(value).unwrap().unwrap().resolve(&backend);
+    assert_eq!(resolve("secret:keyring:var:API")?.as_str(), "from-keyring");
+    assert_eq!(resolve("secret:op://dev/rpc")?.as_str(), "from-op");
+    assert_eq!(resolve("secret:env:TEST_ENV_KEY")?.as_str(), "env_key");
+    assert!(resolve("secret:env:CHAINZ_TEST_UNSET_VARIABLE").is_err());
+    #[cfg(unix)]
+    {
+        assert_eq!(
+            resolve("secret:cmd:printf 'from-cmd\\n'")?.as_str(),
+            "from-cmd"
+        );
+        assert!(resolve("secret:cmd:exit 3").is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn referenced_variables_expand_in_rpc_urls() {
+    setup();
+    let mut globals = GlobalVariables::default();
+    globals.add_rpc_expansion("API_KEY", "secret:env:TEST_OTHER_KEY");
+    assert_eq!(
+        globals.expand_rpc_url("https://rpc.example/${API_KEY}"),
+        "https://rpc.example/other_value"
+    );
+    globals.add_rpc_expansion("GONE", "secret:env:CHAINZ_TEST_UNSET_VARIABLE");
+    assert_eq!(
+        globals.expand_rpc_url("https://rpc.example/${GONE}"),
+        "https://rpc.example/${GONE}",
+        "an unresolvable reference leaves the placeholder"
+    );
+}
+
+#[test]
+fn migration_stores_values_in_the_keyring_and_rolls_back() -> Result<()> {
+    use crate::key::{KEYRING_SERVICE, testing::MemoryBackend};
+    let backend = MemoryBackend::new(false, true, &[]);
+    let variables = vec![
+        ("ALCHEMY_KEY".to_string(), "abc".to_string()),
+        ("INFURA_KEY".to_string(), "def".to_string()),
+    ];
+    let migration = secret::migrate(&backend, &variables, false)?;
+    assert_eq!(
+        migration.references,
+        vec![
+            (
+                "ALCHEMY_KEY".to_string(),
+                "secret:keyring:var:ALCHEMY_KEY".to_string()
+            ),
+            (
+                "INFURA_KEY".to_string(),
+                "secret:keyring:var:INFURA_KEY".to_string()
+            ),
+        ]
+    );
+    assert_eq!(
+        backend.keyring.borrow()[&(KEYRING_SERVICE.to_string(), "var:ALCHEMY_KEY".to_string())],
+        "abc"
+    );
+
+    migration.rollback(&backend);
+    assert!(backend.keyring.borrow().is_empty());
+
+    let unavailable = MemoryBackend::new(false, false, &[]);
+    assert!(secret::migrate(&unavailable, &variables, false).is_err());
+    Ok(())
+}
+
+#[test]
+fn templates_support_defaults_required_and_escapes() {
+    let mut globals = GlobalVariables::default();
+    globals.add_rpc_expansion("KEY", "abc");
+    globals.add_rpc_expansion("BLANK", "");
+
+    assert_eq!(
+        globals.expand_rpc_url("https://x/${KEY:-fallback}/${CHAINZ_TEST_UNSET_A:-free}"),
+        "https://x/abc/free"
+    );
+    assert_eq!(globals.expand_rpc_url("${BLANK:-used}"), "used");
+    assert_eq!(
+        globals.expand_rpc_url("$${KEY} costs $5 ${"),
+        "${KEY} costs $5 ${"
+    );
+    assert_eq!(
+        globals.expand_rpc_url("https://x/${CHAINZ_TEST_UNSET_A:?set it}"),
+        "https://x/${CHAINZ_TEST_UNSET_A:?set it}"
+    );
+}
+
+#[test]
+fn checked_expansion_reports_unresolved_references() {
+    let mut globals = GlobalVariables::default();
+    globals.add_rpc_expansion("KEY", "abc");
+
+    assert_eq!(
+        globals
+            .expand_checked("https://x/${KEY}/${CHAINZ_TEST_UNSET_B}", false)
+            .unwrap(),
+        "https://x/abc/${CHAINZ_TEST_UNSET_B}"
+    );
+    let error = globals
+        .expand_checked("https://x/${CHAINZ_TEST_UNSET_B}", true)
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Unresolved variables: CHAINZ_TEST_UNSET_B"
+    );
+
+    let error = globals
+        .expand_checked("https://x/${CHAINZ_TEST_UNSET_B:?get one at x.io}", false)
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Unresolved variables: CHAINZ_TEST_UNSET_B (get one at x.io)"
+    );
+    assert_eq!(
+        globals
+            .expand_checked("https://x/${CHAINZ_TEST_UNSET_B:-d}", true)
+            .unwrap(),
+        "https://x/d"
+    );
+}
+
+#[test]
+fn template_references_are_listed_in_order() {
+    use template::{Fallback, references};
+
+    let found = references("https://${HOST:-x.io}/$${LITERAL}/${KEY:?needed}/${KEY}");
+    let found: Vec<_> = found.iter().map(|r| (r.name, r.fallback)).collect();
+    assert_eq!(
+        found,
+        [
+            ("HOST", Fallback::Default("x.io")),
+            ("KEY", Fallback::Required("needed")),
+            ("KEY", Fallback::None),
+        ]
+    );
+    assert!(references("https://plain/$/${}").is_empty());
+    assert!(
+        references("${KEY:+alt}").is_empty(),
+        "unsupported operators stay literal"
+    );
+}
+
+#[test]
+fn template_operator_follows_the_first_colon() {
+    use template::{Fallback, references};
+
+    let found = references("${KEY:?set KEY:-see docs}");
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].name, "KEY");
+    assert_eq!(found[0].fallback, Fallback::Required("set KEY:-see docs"));
+
+    let found = references("${HOST:-a:?b}");
+    assert_eq!(found[0].name, "HOST");
+    assert_eq!(found[0].fallback, Fallback::Default("a:?b"));
+
+    let error = GlobalVariables::default()
+        .expand_checked("${CHAINZ_TEST_UNSET_C:?set it:-see docs}", false)
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Unresolved variables: CHAINZ_TEST_UNSET_C (set it:-see docs)"
+    );
+}
+
+#[test]
+fn tokens_parse_with_braces_escapes_and_word_boundaries() {
+    use token::{Part, parse};
+
+    assert_eq!(
+        parse("@chainid/@chainname"),
+        [
+            Part::Token("chainid"),
+            Part::Text("/"),
+            Part::Token("chainname")
+        ]
+    );
+    assert_eq!(
+        parse("tx-@{chainid}x"),
+        [Part::Text("tx-"), Part::Token("chainid"), Part::Text("x")]
+    );
+    assert_eq!(parse("ops@wallet.io"), [Part::Text("ops@wallet.io")]);
+    assert_eq!(parse("@@rpc"), [Part::Text("@"), Part::Text("rpc")]);
+    assert_eq!(parse("@ @{} @{a b}"), [Part::Text("@ @{} @{a b}")]);
+}
+
+#[test]
+fn expand_is_independent_of_token_order_and_honors_escapes() {
+    let cv = make_chain_variables();
+    let result = cv
+        .expand(vec![
+            "@chainname:@chainid".into(),
+            "ops@wallet.io".into(),
+            "@@wallet".into(),
+            "id@{chainid}".into(),
+            "--rpc-url=@rpc".into(),
+        ])
+        .unwrap();
+    assert_eq!(
+        result,
+        vec![
+            "mainnet:1",
+            "ops@wallet.io",
+            "@wallet",
+            "id1",
+            "--rpc-url=http://localhost:8545"
+        ]
+    );
+}
+
+#[test]
+fn token_lookalikes_are_found_only_without_tokens() {
+    use token::{lookalike, parse};
+
+    let known = |name: &str| ["wallet", "rpc"].contains(&name);
+    assert_eq!(lookalike(&parse("ops@wallet.io"), known), Some("@wallet"));
+    assert_eq!(lookalike(&parse("ops@example.io"), known), None);
+    assert_eq!(lookalike(&parse("x@{rpc"), known), Some("@{"));
+    assert_eq!(lookalike(&parse("@@wallet"), known), None);
+}
@@ -0,0 +1,81 @@
+//! `@` tokens in `exec` and `shell` arguments.
+//!
+//! `@name` is a token where the `@` starts the argument or follows a
+//! character that cannot be part of a name, so `ops@wallet.io` stays as
+//! written. `@{name}` is a token anywhere, and `@@` is a literal `@`. A name
+//! is the longest run of letters, digits, and underscores, so `@chainid`
+//! never matches the start of `@chainname`.
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Part<'a> {
+    Text(&'a str),
+    Token(&'a str),
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// The length of the name at the start of `input`.
+fn name_len(input: &str) -> usize {
+    input.find(|c| !is_name_char(c)).unwrap_or(input.len())
+}
+
+pub(crate) fn parse(arg: &str) -> Vec<Part<'_>> {
+    let mut parts = Vec::new();
+    let mut text_start = 0;
+    let mut index = 0;
+    while let Some(offset) = arg[index..].find('@') {
+        let at = index + offset;
+        let after = &arg[at + 1..];
+        let (name, end) = if after.starts_with('@') {
+            // Keep the first `@` of `@@` and drop the second.
+            parts.push(Part::Text(&arg[text_start..=at]));
+            text_start = at + 2;
+            index = at + 2;
+            continue;
+        } else if let Some(body) = after.strip_prefix('{')
+            && let Some(close) = body.find('}')
+            && close > 0
+            && name_len(body) == close
+        {
+            (&body[..close], at + close + 3)
+        } else {
+            let len = name_len(after);
+            let in_word = arg[..at].chars().next_back().is_some_and(is_name_char);
+            if len == 0 || in_word {
+                index = at + 1;
+                continue;
+            }
+            (&after[..len], at + 1 + len)
+        };
+        if text_start < at {
+            parts.push(Part::Text(&arg[text_start..at]));
+        }
+        parts.push(Part::Token(name));
+        text_start = end;
+        index = end;
+    }
+    if text_start < arg.len() {
+        parts.push(Part::Text(&arg[text_start..]));
+    }
+    parts
+}
+
+/// Text that was passed through but reads like a token: a `known` name
+/// after an `@` inside a word, or an `@{` without a valid name and `}`.
+pub(crate) fn lookalike<'a>(parts: &[Part<'a>], known: impl Fn(&str) -> bool) -> Option<&'a str> {
+    parts.iter().find_map(|part| {
+        let Part::Text(text) = part else {
+            return None;
+        };
+        text.match_indices('@').find_map(|(at, _)| {
+            let after = &text[at + 1..];
+            if after.starts_with('{') {
+                return Some(&text[at..at + 2]);
+            }
+            let len = name_len(after);
+            (len > 0 && known(&after[..len])).then(|| &text[at..at + 1 + len])
+        })
+    })
+}
@@ -0,0 +1,290 @@
+//! Known contract verifiers per chain.
+//!
+//! Verifier URLs are easy to get wrong: Etherscan v2 serves every chain it
+//! supports from one endpoint keyed by `chainid`, Routescan and Blockscout
+//! each have their own layouts. The built-in table ships with chainz; a
+//! `verifiers.json` of the same shape next to the config adds chains or
+//! replaces entries without waiting for a release.
+
+use crate::config::config_dir;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{fmt, path::Path, sync::OnceLock};
+
+const BUILTIN: &str = include_str!("verifiers.json");
+const OVERRIDE_FILE: &str = "verifiers.json";
+
+/// The verification API flavor, named as Foundry's `--verifier` expects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifierKind {
+    /// Etherscan-compatible API, including Etherscan v2 and Routescan
+    Etherscan,
+    Blockscout,
+    Sourcify,
+}
+
+impl VerifierKind {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Etherscan => "etherscan",
+            Self::Blockscout => "blockscout",
+            Self::Sourcify => "sourcify",
+        }
+    }
+
+    /// Guess the flavor of a hand-entered URL. Etherscan-compatible is the
+    /// common denominator, so anything unrecognized is treated as that.
+    fn from_url(url: &str) -> Self {
+        let url = url.to_ascii_lowercase();
+        if url.contains("sourcify") {
+            Self::Sourcify
+        } else if url.contains("blockscout") {
+            Self::Blockscout
+        } else {
+            Self::Etherscan
+        }
+    }
+}
+
+impl fmt::Display for VerifierKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownVerifier {
+    pub chain_id: u64,
+    pub verifier: VerifierKind,
+    pub url: String,
+}
+
+pub(crate) struct Registry {
+    entries: Vec<KnownVerifier>,
+}
+
+impl Registry {
+    fn builtin() -> Self {
+        Self {
+            entries: serde_json::from_str(BUILTIN).expect("built-in verifier registry is valid"),
+        }
+    }
+
+    /// The built-in table with the user's override file, if any, on top.
+    fn load_from(path: Option<&Path>) -> Result<Self> {
+        let mut registry = Self::builtin();
+        let Some(path) = path.filter(|path| path.exists()) else {
+            return Ok(registry);
+        };
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let overrides: Vec<KnownVerifier> = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid verifier registry at {}", path.display()))?;
+        for entry in overrides {
+            registry
+                .entries
+                .retain(|known| known.chain_id != entry.chain_id);
+            registry.entries.push(entry);
+        }
+        Ok(registry)
+    }
+
+    pub(crate) fn lookup(&self, chain_id: u64) -> Option<&KnownVerifier> {
+        self.entries.iter().find(|known| known.chain_id == chain_id)
+    }
+
+    /// The verifier behind a chain's configured URL: the registry's flavor
+    /// when the URL is the known one, a guess from the URL otherwise, and
+    /// the registry's flavor when no URL is set.
+    pub(crate) fn kind_for(&self, chain_id: u64, url: Option<&str>) -> Option<VerifierKind> {
+        let known = self.lookup(chain_id);
+        match url {
+            Some(url) => Some(
+                known
+                    .filter(|known| known.url.trim_end_matches('/') == url.trim_end_matches('/'))
+                    .map_or_else(|| VerifierKind::from_url(url), |known| known.verifier),
+            ),
+            None => known.map(|known| known.verifier),
+        }
+    }
+}
+
+/// The process-wide registry. An unreadable override file is reported once
+/// and ignored, so a typo there never blocks `exec`.
+pub(crate) fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let path = config_dir().map(|dir| dir.join(OVERRIDE_FILE));
+        Registry::load_from(path.as_deref()).unwrap_or_else(|err| {
+            eprintln!("Warning: {:#}; using the built-in verifier registry", err);
+            Registry::builtin()
+        })
+    })
+}
+
+/// How an explorer API answered an authenticated call.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum KeyCheck {
+    Valid,
+    /// The explorer rejected the key; carries its message.
+    Rejected(String),
+    RateLimited,
+    Unreachable(&'static str),
+    BadResponse,
+}
+
+/// Ask the explorer for the latest block number, a cheap call that still
+/// needs a valid key on Etherscan-style APIs. Transport errors are reduced
+/// to a fixed reason, since they echo the URL and with it the key.
+pub(crate) async fn check_api_key(
+    client: &reqwest::Client,
+    kind: VerifierKind,
+    url: &str,
+    api_key: Option<&str>,
+) -> KeyCheck {
+    let (module, action) = match kind {
+        VerifierKind::Blockscout => ("block", "eth_block_number"),
+        VerifierKind::Etherscan | VerifierKind::Sourcify => ("proxy", "eth_blockNumber"),
+    };
+    let mut request = client
+        .get(url)
+        .query(&[("module", module), ("action", action)]);
+    if let Some(api_key) = api_key {
+        request = request.query(&[("apikey", api_key)]);
+    }
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(err) if err.is_timeout() => return KeyCheck::Unreachable("timeout"),
+        Err(_) => return KeyCheck::Unreachable("request failed"),
+    };
+    if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return KeyCheck::RateLimited;
+    }
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED
+        || response.status() == reqwest::StatusCode::FORBIDDEN
+    {
+        return KeyCheck::Rejected(format!("HTTP {}", response.status().as_u16()));
+    }
+    let Ok(body) = response.json::<serde_json::Value>().await else {
+        return KeyCheck::BadResponse;
+    };
+    match body["result"].as_str() {
+        Some(result) if result.starts_with("0x") => KeyCheck::Valid,
+        // Etherscan reports errors as {"status":"0","result":"<reason>"}.
+        Some(reason) if reason.to_ascii_lowercase().contains("rate limit") => KeyCheck::RateLimited,
+        Some(reason) => KeyCheck::Rejected(reason.to_string()),
+        None => KeyCheck::BadResponse,
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use serde_json::{Value, json};
+
+    /// An Etherscan-style API that accepts the key "good", rejects "bad"
+    /// and throttles "busy".
+    pub(crate) async fn mock_explorer() -> String {
+        let url = crate::serve::testing::mock_http(|query| match query["apikey"].as_str() {
+            Some("good") => json!({"jsonrpc": "2.0", "id": 83, "result": "0x10"}),
+            Some("busy") => Value::Null,
+            Some(_) => json!({"status": "0", "message": "NOTOK", "result": "Invalid API Key"}),
+            None => {
+                json!({"status": "0", "message": "NOTOK", "result": "Missing/Invalid API Key"})
+            }
+        })
+        .await;
+        format!("{}/api?chainid=1", url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::mock_explorer;
+    #[tokio::test]
+    async fn api_key_checks_classify_explorer_answers() {
+        let url = mock_explorer().await;
+        let client = reqwest::Client::new();
+        let check = |key| check_api_key(&client, VerifierKind::Etherscan, &url, key);
+        assert_eq!(check(Some("good")).await, KeyCheck::Valid);
+        assert_eq!(
+            check(Some("bad")).await,
+            KeyCheck::Rejected("Invalid API Key".into())
+        );
+        assert_eq!(check(Some("busy")).await, KeyCheck::RateLimited);
+        assert!(matches!(check(None).await, KeyCheck::Rejected(_)));
+        assert_eq!(
+            check_api_key(
+                &client,
+                VerifierKind::Etherscan,
+                "http://127.0.0.1:1/api",
+                None
+            )
+            .await,
+            KeyCheck::Unreachable("request failed")
+        );
+    }
+
+    #[test]
+    fn builtin_registry_covers_major_chains_once() {
+        let registry = Registry::builtin();
+        let mainnet = registry.lookup(1).unwrap();
+        assert_eq!(mainnet.verifier, VerifierKind::Etherscan);
+        assert!(mainnet.url.ends_with("chainid=1"));
+        assert_eq!(
+            registry.lookup(100).unwrap().verifier,
+            VerifierKind::Blockscout
+        );
+        let mut ids: Vec<_> = registry
+            .entries
+            .iter()
+            .map(|known| known.chain_id)
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), registry.entries.len(), "duplicate chain IDs");
+    }
+
+    #[test]
+    fn override_file_adds_and_replaces_entries() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(OVERRIDE_FILE);
+        std::fs::write(
+            &path,
+            r#"[
+                {"chain_id": 1, "verifier": "sourcify", "url": "https://sourcify.dev/server"},
+                {"chain_id": 31337, "verifier": "blockscout", "url": "http://localhost:4000/api"}
+            ]"#,
+        )?;
+        let registry = Registry::load_from(Some(&path))?;
+        assert_eq!(registry.lookup(1).unwrap().verifier, VerifierKind::Sourcify);
+        assert_eq!(
+            registry.lookup(31337).unwrap().url,
+            "http://localhost:4000/api"
+        );
+
+        std::fs::write(&path, "not json")?;
+        assert!(Registry::load_from(Some(&path)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn kind_follows_the_configured_url() {
+        let registry = Registry::builtin();
+        assert_eq!(
+            registry.kind_for(100, Some("https://gnosis.blockscout.com/api/")),
+            Some(VerifierKind::Blockscout)
+        );
+        assert_eq!(
+            registry.kind_for(1, Some("https://sourcify.dev/server")),
+            Some(VerifierKind::Sourcify)
+        );
+        assert_eq!(
+            registry.kind_for(31337, Some("http://localhost:4000/api")),
+            Some(VerifierKind::Etherscan)
+        );
+        assert_eq!(registry.kind_for(100, None), Some(VerifierKind::Blockscout));
+        assert_eq!(registry.kind_for(31337, None), None);
+    }
+}
@@ -22,6 +22,9 @@ fn chainz(home: &Path) -> Command {
     // XDG_CONFIG_HOME is honored on every supported OS and keeps parallel
     // tests away from the runner's real platform-specific home directory.
     cmd.env("XDG_CONFIG_HOME", home.join("xdg"));
+    // Likewise for the chainlist and RPC history caches on Linux; macOS
+    // and Windows derive theirs from HOME.
+    cmd.env("XDG_CACHE_HOME", home.join("cache"));
     cmd
 }
 
@@ -32,7 +35,8 @@ fn config_path(home: &Path) -> std::path::PathBuf {
 fn process_chainz(home: &Path) -> std::process::Command {
     let mut cmd = std::process::Command::new(assert_cmd::cargo::cargo_bin!("chainz"));
     cmd.env("HOME", home)
-        .env("XDG_CONFIG_HOME", home.join("xdg"));
+        .env("XDG_CONFIG_HOME", home.join("xdg"))
+        .env("XDG_CACHE_HOME", home.join("cache"));
     cmd
 }
 
@@ -72,12 +76,17 @@ fn seed_config(home: &Path, chains: &[(&str, u64)]) {
             .map(|(name, id)| ChainDefinition {
                 name: name.to_string(),
                 aliases: vec![],
+                tags: vec![],
                 chain_id: *id,
                 rpc_urls: vec!["http://localhost:1".to_string()],
                 selected_rpc: "http://localhost:1".to_string(),
+                ws_urls: vec![],
+                selected_ws: None,
                 verification_api_key: None,
                 verification_url: None,
                 key_name: Some("default".to_string()),
+                env: Default::default(),
+                metadata: Default::default(),
             })
             .collect(),
         keys: std::collections::HashMap::from([(
@@ -142,6 +151,201 @@ fn var_set_get_list_rm_roundtrip() {
         .stderr(predicate::str::contains("not found"));
 }
 
+#[cfg(unix)]
+#[test]
+fn chain_variables_are_scoped_exported_and_redacted() {
+    let home = TempDir::new().unwrap();
+    seed_config(home.path(), &[("base", 8453), ("optimism", 10)]);
+    chainz(home.path())
+        .args(["var", "set", "--chain", "base", "DEPLOYER_SAFE", "--stdin"])
+        .write_stdin("0xSAFE\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Set variable DEPLOYER_SAFE for 'base'",
+        ));
+    chainz(home.path())
+        .args(["var", "set", "--chain", "base", "CHAIN_ID", "1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("reserved"));
+
+    chainz(home.path())
+        .args([
+            "exec",
+            "base",
+            "--",
+            "sh",
+            "-c",
+            "echo $DEPLOYER_SAFE @DEPLOYER_SAFE",
+        ])
+        .assert()
+        .success()
+        .stdout("0xSAFE 0xSAFE\n");
+    chainz(home.path())
+        .args([
+            "exec",
+            "optimism",
+            "--",
+            "sh",
+            "-c",
+            "echo ${DEPLOYER_SAFE:-unset}",
+        ])
+        .assert()
+        .success()
+        .stdout("unset\n");
+
+    chainz(home.path())
+        .args(["var", "list", "--chain", "base"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("DEPLOYER_SAFE = [REDACTED]"));
+    chainz(home.path())
+        .args(["var", "get", "DEPLOYER_SAFE"])
+        .assert()
+        .failure();
+    chainz(home.path())
+        .args(["show", "base", "--json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""DEPLOYER_SAFE": "[REDACTED]""#));
+    chainz(home.path())
+        .args(["show", "base", "--json", "--show-secrets"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""DEPLOYER_SAFE": "0xSAFE""#));
+
+    chainz(home.path())
+        .args(["var", "rm", "--chain", "base", "DEPLOYER_SAFE"])
+        .assert()
+        .success();
+    chainz(home.path())
+        .args(["var", "list", "--chain", "base"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No variables set"));
+}
+
+#[cfg(unix)]
+#[test]
+fn show_neither_resolves_secrets_nor_fails_on_unresolved_urls() {
+    let home = TempDir::new().unwrap();
+    seed_config(home.path(), &[("demo", 8453)]);
+    let marker = home.path().join("ran");
+    let path = config_path(home.path());
+    let mut config: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    let url = "http://localhost:1/${KEYV}/${NEED:?set NEED}";
+    config["chains"][0]["rpc_urls"] = serde_json::json!([url]);
+    config["chains"][0]["selected_rpc"] = url.into();
+    config["variables"]["KEYV"] = format!("secret:cmd:touch {} && echo k", marker.display()).into();
+    fs::write(&path, serde_json::to_string(&config).unwrap()).unwrap();
+
+    chainz(home.path())
+        .env("CHAINZ_STRICT_VARIABLES", "1")
+        .args(["show", "demo"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("demo"));
+    assert!(!marker.exists(), "show ran a secret command");
+}
+
+#[test]
+fn variable_references_resolve_only_when_used() {
+    let home = TempDir::new().unwrap();
+    seed_config(home.path(), &[("base", 8453)]);
+    let path = config_path(home.path());
+    let mut config: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    config["chains"][0]["rpc_urls"] = serde_json::json!(["http://localhost:1/${TOKEN}"]);
+    config["chains"][0]["selected_rpc"] = "http://localhost:1/${TOKEN}".into();
+    fs::write(&path, serde_json::to_string(&config).unwrap()).unwrap();
+
+    chainz(home.path())
+        .args(["var", "set", "TOKEN", "secret:env:CHAINZ_TEST_TOKEN"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("shell history").not());
+    chainz(home.path())
+        .args(["var", "set", "UNUSED", "secret:cmd:exit 1"])
+        .assert()
+        .success();
+    chainz(home.path())
+        .args(["var", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("TOKEN = [env]"));
+    chainz(home.path())
+        .args(["env", "base", "--format", "json"])
+        .env("CHAINZ_TEST_TOKEN", "s3cret")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("http://localhost:1/s3cret"))
+        .stderr(predicate::str::contains("UNUSED").not());
+
+    chainz(home.path())
+        .args(["var", "set", "BROKEN", "secret:op://nowhere"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("secret:op://vault/item"));
+    chainz(home.path())
+        .args(["var", "migrate", "TOKEN"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already a reference (env)"));
+    chainz(home.path())
+        .args(["var", "migrate", "--all"])
+        .env("CHAINZ_DISABLE_KEYRING", "1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("keyring is unavailable"));
+}
+
+#[test]
+fn strict_variables_and_var_check_report_missing_references() {
+    let home = TempDir::new().unwrap();
+    seed_config(home.path(), &[("base", 8453)]);
+    let path = config_path(home.path());
+    let mut config: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    let url = "http://localhost:1/${HOSTED:-v1}/${CHAINZ_TEST_MISSING}";
+    config["chains"][0]["rpc_urls"] = serde_json::json!([url]);
+    config["chains"][0]["selected_rpc"] = url.into();
+    fs::write(&path, serde_json::to_string(&config).unwrap()).unwrap();
+
+    chainz(home.path())
+        .args(["env", "base", "--format", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("/v1/${CHAINZ_TEST_MISSING}"));
+    chainz(home.path())
+        .args(["exec", "base", "--", "true"])
+        .env("CHAINZ_STRICT_VARIABLES", "1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Unresolved variables: CHAINZ_TEST_MISSING",
+        ));
+
+    chainz(home.path())
+        .args(["var", "check"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "HOSTED (unset, using the default)",
+        ))
+        .stdout(predicate::str::contains("CHAINZ_TEST_MISSING (missing)"))
+        .stderr(predicate::str::contains("1 variable reference(s)"));
+    chainz(home.path())
+        .args(["var", "check", "base"])
+        .env("CHAINZ_TEST_MISSING", "x")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "CHAINZ_TEST_MISSING (environment)",
+        ));
+}
+
 #[test]
 fn concurrent_updates_do_not_lose_changes() {
     let home = TempDir::new().unwrap();
@@ -582,6 +786,134 @@ fn list_json_outputs_machine_readable_chains() {
     assert!(parsed[0]["aliases"].is_array(), "shape must be regular");
 }
 
+#[test]
+fn list_filters_by_testnet_flag() {
+    let home = TempDir::new().unwrap();
+    seed_config(home.path(), &[("ethereum", 1), ("sepolia", 11155111)]);
+    chainz(home.path())
+        .args(["update", "sepolia", "--testnet", "true"])
+        .assert()
+        .success();
+
+    let names = |filter: &str| {
+        let output = chainz(home.path())
+            .args(["list", filter, "--json"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        parsed
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|chain| chain["name"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names("--testnets"), ["sepolia"]);
+    assert_eq!(names("--mainnets"), ["ethereum"]);
+}
+
+#[test]
+fn tags_are_managed_and_filter_list_and_doctor() {
+    let home = TempDir::new().unwrap();
+    seed_config(
+        home.path(),
+        &[("ethereum", 1), ("base", 8453), ("optimism", 10)],
+    );
+    chainz(home.path())
+        .args(["tag", "add", "base", "L2", "prod"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Tags for 'base': l2, prod"));
+    chainz(home.path())
+        .args(["tag", "add", "optimism", "l2"])
+        .assert()
+        .success();
+    chainz(home.path())
+        .args(["tag", "add", "ethereum", "team x"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot contain whitespace"));
+    chainz(home.path())
+        .args(["tag", "rm", "ethereum", "l2"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is not tagged 'l2'"));
+
+    let names = |args: &[&str]| {
+        let output = chainz(home.path())
+            .args(["list", "--json"])
+            .args(args)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        parsed
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|chain| chain["name"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names(&["--tag", "l2"]), ["base", "optimism"]);
+    assert_eq!(names(&["--tag", "l2,prod"]), ["base"]);
+
+    chainz(home.path())
+        .args(["tag", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("l2: base, optimism"))
+        .stdout(predicate::str::contains("prod: base"));
+    chainz(home.path())
+        .args(["tag", "rm", "base", "prod"])
+        .assert()
+        .success();
+    assert!(names(&["--tag", "prod"]).is_empty());
+
+    let output = chainz(home.path())
+        .args(["doctor", "--json", "--only", "rpc.health", "--tag", "l2"])
+        .output()
+        .unwrap();
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let chains: Vec<_> = parsed["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|check| check["chain"].as_str().unwrap())
+        .collect();
+    assert_eq!(chains, ["base", "optimism"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn exec_tag_runs_once_per_tagged_chain() {
+    let home = TempDir::new().unwrap();
+    seed_config(home.path(), &[("alpha", 1), ("beta", 2), ("gamma", 3)]);
+    for chain in ["alpha", "gamma"] {
+        chainz(home.path())
+            .args(["tag", "add", chain, "l2"])
+            .assert()
+            .success();
+    }
+    chainz(home.path())
+        .args(["exec", "--tag", "l2", "--", "sh", "-c", "echo id=@chainid"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("alpha │ id=1"))
+        .stdout(predicate::str::contains("gamma │ id=3"))
+        .stdout(predicate::str::contains("beta").not())
+        .stderr(predicate::str::contains("2 of 2 chain(s) succeeded"));
+    chainz(home.path())
+        .args(["exec", "--tag", "prod", "--", "true"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No chains tagged prod"));
+}
+
 #[test]
 fn key_list_json_never_leaks_key_material() {
     let home = TempDir::new().unwrap();
@@ -626,6 +958,157 @@ fn doctor_reports_dead_rpc_and_exits_nonzero() {
         .stdout(predicate::str::contains("no healthy alternative"));
 }
 
+#[test]
+fn doctor_json_reports_each_check_by_id() {
+    let home = TempDir::new().unwrap();
+    seed_config(home.path(), &[("deadchain", 31337)]);
+
+    let output = chainz(home.path())
+        .args(["doctor", "--json"])
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["failures"], 1);
+    let checks = report["checks"].as_array().unwrap();
+    let rpc = checks
+        .iter()
+        .find(|check| check["id"] == "rpc.health")
+        .unwrap();
+    assert_eq!(rpc["chain"], "deadchain");
+    assert_eq!(rpc["severity"], "failure");
+    assert_eq!(rpc["detail"], "unreachable");
+    assert_eq!(rpc["endpoint"], "http://localhost:1/");
+    assert!(checks.iter().any(|check| check["id"] == "keys.storage"));
+
+    chainz(home.path())
+        .args(["doctor", "--format", "junit"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::starts_with("<?xml"))
+        .stdout(predicate::str::contains(
+            r#"<testcase classname="rpc.health" name="deadchain""#,
+        ));
+}
+
+#[test]
+fn doctor_runs_selected_checks_for_selected_chains() {
+    let home = TempDir::new().unwrap();
+    seed_config(home.path(), &[("deadchain", 31337), ("otherchain", 31338)]);
+
+    // Offline sections only: the dead RPCs are never contacted.
+    chainz(home.path())
+        .args(["doctor", "--only", "config,keys"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("plaintext"))
+        .stdout(predicate::str::contains("RPC health").not());
+
+    let output = chainz(home.path())
+        .args([
+            "doctor",
+            "--chain",
+            "otherchain",
+            "--skip",
+            "keys",
+            "--json",
+        ])
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let checks = report["checks"].as_array().unwrap();
+    assert!(checks.iter().all(|check| check["id"] != "keys.storage"));
+    let chains: Vec<_> = checks
+        .iter()
+        .filter(|check| check["id"] == "rpc.health")
+        .map(|check| check["chain"].as_str().unwrap())
+        .collect();
+    assert_eq!(chains, ["otherchain"]);
+
+    chainz(home.path())
+        .args(["doctor", "--skip", "verifcation"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Unknown doctor check 'verifcation'",
+        ));
+}
+
+#[test]
+fn doctor_severity_overrides_come_from_config() {
+    let home = TempDir::new().unwrap();
+    seed_config(home.path(), &[("deadchain", 31337)]);
+    let path = config_path(home.path());
+    let mut config: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    config["doctor"] = serde_json::json!({"rpc.health": "warning", "keys": "off"});
+    write_raw_config(home.path(), &serde_json::to_string_pretty(&config).unwrap());
+
+    chainz(home.path())
+        .arg("doctor")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("deadchain"))
+        .stdout(predicate::str::contains("plaintext").not())
+        .stdout(predicate::str::contains("0 failure(s), 1 warning(s)"));
+}
+
+#[test]
+fn rpc_stats_reports_history_recorded_by_doctor() {
+    let home = TempDir::new().unwrap();
+    seed_config(home.path(), &[("deadchain", 31337)]);
+
+    chainz(home.path())
+        .args(["rpc", "stats", "deadchain"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("no samples"));
+
+    chainz(home.path()).arg("doctor").assert().code(1);
+    chainz(home.path())
+        .args(["rpc", "stats", "deadchain"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("uptime 0.0%"))
+        .stdout(predicate::str::contains("last error: unreachable"));
+
+    let output = chainz(home.path())
+        .args(["rpc", "stats", "deadchain", "--json"])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["endpoints"][0]["selected"], true);
+    assert_eq!(json["endpoints"][0]["stats"]["samples"], 1);
+    assert_eq!(json["endpoints"][0]["stats"]["last_error"], "unreachable");
+}
+
+#[test]
+fn rpc_probe_select_requires_a_matching_endpoint() {
+    let home = TempDir::new().unwrap();
+    seed_config(home.path(), &[("deadchain", 31337)]);
+
+    chainz(home.path())
+        .args([
+            "rpc",
+            "probe",
+            "deadchain",
+            "--require",
+            "archive,trace",
+            "--select",
+        ])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("unreachable"))
+        .stderr(predicate::str::contains(
+            "No healthy RPC for 'deadchain' supports: archive, trace",
+        ));
+}
+
 #[test]
 fn doctor_warns_on_plaintext_keys() {
     let home = TempDir::new().unwrap();
@@ -972,6 +1455,278 @@ fn update_accepts_target_and_direct_changes() {
         .failure();
 }
 
+#[cfg(unix)]
+#[test]
+fn exec_all_runs_once_per_chain_with_prefixes_and_summary() {
+    let home = TempDir::new().unwrap();
+    seed_config(home.path(), &[("alpha", 1), ("beta", 2)]);
+    chainz(home.path())
+        .args([
+            "exec",
+            "--all",
+            "-j",
+            "2",
+            "--",
+            "sh",
+            "-c",
+            "echo id=@chainid",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("alpha │ id=1"))
+        .stdout(predicate::str::contains("beta  │ id=2"))
+        .stderr(predicate::str::contains("2 of 2 chain(s) succeeded"));
+    chainz(home.path())
+        .args([
+            "exec",
+            "--chains",
+            "alpha,2",
+            "--",
+            "sh",
+            "-c",
+            "test @chainid = 1",
+        ])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("beta: exit 1"))
+        .stderr(predicate::str::contains("1 of 2 chain(s) succeeded"));
+}
+
+#[cfg(unix)]
+#[test]
+fn exec_failover_points_the_child_at_a_local_proxy() {
+    let home = TempDir::new().unwrap();
+    seed_config(home.path(), &[("testchain", 31337)]);
+    chainz(home.path())
+        .args([
+            "exec",
+            "testchain",
+            "--failover",
+            "--",
+            "sh",
+            "-c",
+            "echo $ETH_RPC_URL",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("http://127.0.0.1:"))
+        .stdout(predicate::str::contains("localhost:1").not())
+        .stderr(predicate::str::contains("failover: testchain via"));
+}
+
+#[test]
+fn env_prints_chain_variables_without_the_key() {
+    let home = TempDir::new().unwrap();
+    seed_config(home.path(), &[("testchain", 31337)]);
+    chainz(home.path())
+        .args(["env", "testchain", "--format", "sh"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("export CHAIN_ID='31337'"))
+        .stdout(predicate::str::contains(
+            "export ETH_RPC_URL='http://localhost:1'",
+        ))
+        .stdout(predicate::str::contains("RAW_PRIVATE_KEY").not());
+    chainz(home.path())
+        .args(["env", "testchain", "--format", "json", "--expose-key"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(TEST_KEY));
+    chainz(home.path())
+        .args(["env", "--format", "foundry-toml"])
+        .assert()
+        .success()
+        .stdout("[rpc_endpoints]\ntestchain = \"http://localhost:1\"\n");
+    chainz(home.path())
+        .args(["env", "--format", "foundry-toml", "--expose-key"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("never includes keys"));
+}
+
+#[test]
+fn ws_url_is_exported_and_validated() {
+    let home = TempDir::new().unwrap();
+    seed_config(home.path(), &[("testchain", 31337)]);
+    let mut config: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(config_path(home.path())).unwrap()).unwrap();
+    config["chains"][0]["ws_urls"] = serde_json::json!(["wss://ws.example/v2/ws-secret"]);
+    config["chains"][0]["selected_ws"] = serde_json::json!("wss://ws.example/v2/ws-secret");
+    write_raw_config(home.path(), &config.to_string());
+
+    chainz(home.path())
+        .args(["env", "testchain", "--format", "sh"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "export ETH_WS_URL='wss://ws.example/v2/ws-secret'",
+        ));
+    chainz(home.path())
+        .args(["show", "testchain"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("wss://ws.example/REDACTED"))
+        .stdout(predicate::str::contains("ws-secret").not());
+    chainz(home.path())
+        .args(["update", "testchain", "--ws-url", "https://ws.example"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("must start with ws:// or wss://"));
+}
+
+#[test]
+fn active_profile_scopes_list_and_exec() {
+    let home = TempDir::new().unwrap();
+    seed_config(home.path(), &[("ethereum", 1), ("sepolia", 11155111)]);
+    chainz(home.path())
+        .args(["profile", "create", "testnets", "--chains", "sepolia"])
+        .assert()
+        .success();
+    chainz(home.path())
+        .args(["profile", "use", "testnets"])
+        .assert()
+        .success();
+
+    chainz(home.path())
+        .args(["list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("sepolia"))
+        .stdout(predicate::str::contains("ethereum").not());
+    chainz(home.path())
+        .args(["exec", "ethereum", "--", "echo", "@chainid"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "not in the active profile 'testnets'",
+        ));
+    // Signing paths are scoped the same way.
+    for args in [
+        &["sign", "--chain", "ethereum", "message", "hello"][..],
+        &["serve", "ethereum", "--port", "0"][..],
+    ] {
+        chainz(home.path())
+            .args(args)
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "not in the active profile 'testnets'",
+            ));
+    }
+    chainz(home.path())
+        .args(["env", "--format", "foundry-toml"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("sepolia"))
+        .stdout(predicate::str::contains("ethereum").not());
+    // With a profile active, `use` sets the profile's default.
+    chainz(home.path())
+        .args(["use", "sepolia"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("profile 'testnets'"));
+    chainz(home.path())
+        .args(["exec", "--", "echo", "@chainid"])
+        .assert()
+        .success()
+        .stdout("11155111\n");
+
+    chainz(home.path())
+        .args(["profile", "off"])
+        .assert()
+        .success();
+    chainz(home.path())
+        .env("CHAINZ_PROFILE", "testnets")
+        .args(["list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ethereum").not());
+    chainz(home.path())
+        .env("CHAINZ_PROFILE", "mainnets")
+        .args(["list"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("CHAINZ_PROFILE"));
+    chainz(home.path())
+        .args(["list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ethereum"));
+}
+
+#[test]
+fn project_overlay_is_merged_and_reported_by_show() {
+    let home = TempDir::new().unwrap();
+    seed_config(home.path(), &[("ethereum", 1)]);
+    let project = home.path().join("work").join("repo");
+    let nested = project.join("contracts");
+    fs::create_dir_all(&nested).unwrap();
+    fs::write(
+        project.join(".chainz.toml"),
+        r#"default_chain = "devnet"
+
+[[chains]]
+name = "devnet"
+chain_id = 1337
+rpc_urls = ["http://localhost:8545"]
+"#,
+    )
+    .unwrap();
+
+    chainz(home.path())
+        .current_dir(&nested)
+        .args(["show", "devnet"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Source: project ("))
+        .stdout(predicate::str::contains(".chainz.toml)"))
+        .stdout(predicate::str::contains("Default: Yes (project)"));
+    chainz(home.path())
+        .current_dir(&nested)
+        .args(["show", "ethereum", "--json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""source": "global""#));
+    chainz(home.path())
+        .current_dir(&nested)
+        .args(["remove", "devnet"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("edit that file instead"));
+
+    // Outside the project only the global config applies, and the failed
+    // removal above left it untouched.
+    let config = fs::read_to_string(config_path(home.path())).unwrap();
+    assert!(!config.contains("devnet"));
+    chainz(home.path())
+        .current_dir(home.path())
+        .args(["show", "devnet"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn project_overlay_warns_when_it_replaces_a_global_chain() {
+    let home = TempDir::new().unwrap();
+    seed_config(home.path(), &[("ethereum", 1)]);
+    let project = home.path().join("repo");
+    fs::create_dir_all(&project).unwrap();
+    fs::write(
+        project.join(".chainz.toml"),
+        "[[chains]]\nname = \"mainnet\"\nchain_id = 1\nrpc_urls = [\"http://127.0.0.1:1\"]\n",
+    )
+    .unwrap();
+
+    chainz(home.path())
+        .current_dir(&project)
+        .args(["list"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "replaces global chain(s) ethereum (1)",
+        ));
+}
+
 #[test]
 fn update_flags_require_a_target() {
     let home = TempDir::new().unwrap();
@@ -1011,12 +1766,17 @@ fn rpc_only_chain_executes_until_key_material_is_requested() {
         chains: vec![ChainDefinition {
             name: "readonly".to_string(),
             aliases: vec![],
+            tags: vec![],
             chain_id: 31337,
             rpc_urls: vec!["http://localhost:1".to_string()],
             selected_rpc: "http://localhost:1".to_string(),
+            ws_urls: vec![],
+            selected_ws: None,
             verification_api_key: None,
             verification_url: None,
             key_name: None,
+            env: Default::default(),
+            metadata: Default::default(),
         }],
         ..Default::default()
     };
@@ -1061,12 +1821,17 @@ fn doctor_does_not_offer_rpc_fix_for_key_only_failure() {
         chains: vec![ChainDefinition {
             name: "local".to_string(),
             aliases: vec![],
+            tags: vec![],
             chain_id: 31337,
             rpc_urls: vec![rpc.clone()],
             selected_rpc: rpc,
+            ws_urls: vec![],
+            selected_ws: None,
             verification_api_key: None,
             verification_url: None,
             key_name: Some("missing".to_string()),
+            env: Default::default(),
+            metadata: Default::default(),
         }],
         ..Default::default()
     };
@@ -1121,3 +1886,139 @@ fn migrate_all_failure_never_prints_key_material() {
         .stderr(predicate::str::contains(TEST_KEY).not())
         .stderr(predicate::str::contains("Failed to migrate"));
 }
+
+#[test]
+fn key_generate_prints_only_the_address() {
+    let home = TempDir::new().unwrap();
+    let output = chainz(home.path())
+        .args(["key", "generate", "fresh", "--type", "private-key"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Generated key 'fresh' (0x"))
+        .get_output()
+        .stdout
+        .clone();
+    let config: Config =
+        serde_json::from_str(&fs::read_to_string(config_path(home.path())).unwrap()).unwrap();
+    let KeyType::PrivateKey { value } = &config.keys["fresh"].kind else {
+        panic!("expected an explicit plaintext key");
+    };
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(!stdout.contains(value.as_str()));
+    assert!(stdout.contains(config.keys["fresh"].address.as_deref().unwrap()));
+}
+
+#[test]
+fn key_lock_without_an_agent_is_a_no_op() {
+    let home = TempDir::new().unwrap();
+    chainz(home.path())
+        .args(["key", "lock"])
+        .env("CHAINZ_AGENT_SOCK", home.path().join("missing.sock"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No chainz agent is running"));
+}
+
+#[test]
+fn sign_message_prints_only_the_signature() {
+    let home = TempDir::new().unwrap();
+    seed_config(home.path(), &[("testchain", 31337)]);
+    let output = chainz(home.path())
+        .args(["sign", "--chain", "testchain", "message", "hello"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    let signature = stdout.trim();
+    assert!(signature.starts_with("0x"));
+    assert_eq!(signature.len(), 2 + 65 * 2);
+    assert!(!stdout.contains(TEST_KEY.trim_start_matches("0x")));
+}
+
+/// Pick a free localhost port for a child process to bind.
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+/// Retry `cast` until the child server it targets accepts connections.
+fn cast_when_ready(args: &[&str]) -> String {
+    for _ in 0..50 {
+        let output = std::process::Command::new("cast")
+            .args(args)
+            .output()
+            .unwrap();
+        if output.status.success() {
+            return String::from_utf8(output.stdout).unwrap().trim().to_string();
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    panic!("cast {:?} never succeeded", args);
+}
+
+#[cfg(unix)]
+#[test]
+#[ignore = "requires anvil and cast on PATH"]
+fn serve_signs_transactions_against_anvil() {
+    let home = TempDir::new().unwrap();
+    let anvil_port = free_port();
+    let mut anvil = std::process::Command::new("anvil")
+        .args(["--port", &anvil_port.to_string()])
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let anvil_url = format!("http://127.0.0.1:{anvil_port}");
+    seed_config(home.path(), &[("testchain", 31337)]);
+    let mut config: Config =
+        serde_json::from_str(&fs::read_to_string(config_path(home.path())).unwrap()).unwrap();
+    config.chains[0].rpc_urls = vec![anvil_url.clone()];
+    config.chains[0].selected_rpc = anvil_url.clone();
+    write_raw_config(home.path(), &serde_json::to_string_pretty(&config).unwrap());
+
+    let proxy_port = free_port();
+    let mut proxy = process_chainz(home.path())
+        .args(["serve", "testchain", "--port", &proxy_port.to_string()])
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    // The URL carries a per-run token, so read it from the export line.
+    let mut export = String::new();
+    std::io::BufRead::read_line(
+        &mut std::io::BufReader::new(proxy.stdout.take().unwrap()),
+        &mut export,
+    )
+    .unwrap();
+    let proxy_url = export
+        .trim()
+        .strip_prefix("export ETH_RPC_URL=")
+        .unwrap()
+        .to_string();
+    assert!(proxy_url.starts_with(&format!("http://127.0.0.1:{proxy_port}/")));
+    cast_when_ready(&["chain-id", "--rpc-url", &anvil_url]);
+    let before = cast_when_ready(&["balance", TEST_ADDRESS_2, "--rpc-url", &proxy_url]);
+    cast_when_ready(&[
+        "send",
+        "--rpc-url",
+        &proxy_url,
+        "--unlocked",
+        "--from",
+        TEST_ADDRESS,
+        TEST_ADDRESS_2,
+        "--value",
+        "1",
+    ]);
+    let after = cast_when_ready(&["balance", TEST_ADDRESS_2, "--rpc-url", &proxy_url]);
+    for child in [&mut proxy, &mut anvil] {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    assert_eq!(
+        after.parse::<u128>().unwrap(),
+        before.parse::<u128>().unwrap() + 1
+    );
+}